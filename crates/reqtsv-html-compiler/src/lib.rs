@@ -1,4 +1,5 @@
 use std::{
+	collections::{BTreeMap, HashMap, HashSet},
 	fmt::{Display, Write},
 	fs::{File, OpenOptions, copy, create_dir},
 	io::Write as _,
@@ -10,9 +11,16 @@ use log::{debug, error, info};
 use maud::{Markup, Render, html};
 use out_dir::{
 	OutDir, OutDirAllRequirements, OutDirCSS, OutDirComponents, OutDirComponentsDir, OutDirIndex,
+	OutDirTags, OutDirTagsDir,
 };
 use reqtsv_lib::{
-	COMPONENT_HEADER, Component, REQUIREMENT_HEADER, Requirement, SaveFileError, save_file_strict,
+	COMPONENT_HEADER, Component, Link, REQUIREMENT_HEADER, Requirement, RequirementRevision,
+	SaveFileError, Tags, TestCase, TestResult, ancestry, component_path,
+	config::{ProjectConfigErr, init_project_config, load_project_config},
+	custom_field::FieldDef,
+	latest_result,
+	lock::{LockErr, acquire_lock},
+	save_file_strict, verification_status,
 };
 use sha3::Digest;
 use thiserror::Error;
@@ -41,22 +49,24 @@ pub enum InitProjectErr {
 	WriteComponentTbl(SaveFileError),
 	#[error("can't write Requirement table due to: {0:?}")]
 	WriteRequirementTbl(SaveFileError),
+	#[error("{0:}")]
+	Config(#[source] ProjectConfigErr),
+	#[error("{0:}")]
+	Lock(#[source] LockErr),
 }
 
 pub fn init_project(project_root: impl AsRef<Path>) -> Result<(), InitProjectErr> {
-	// reqcsv.toml
-	// reqcsv.lock
 	// styles.css
+	let _lock = acquire_lock(project_root.as_ref()).map_err(InitProjectErr::Lock)?;
+	let config = load_project_config(project_root.as_ref()).map_err(InitProjectErr::Config)?;
 
-	let component_path = project_root.as_ref().join(reqtsv_lib::COMPONENT_TABLE_NAME);
+	let component_path = project_root.as_ref().join(&config.tables.component);
 	if component_path.exists() {
 		return Err(InitProjectErr::ComponentTblExists(
 			component_path.into_boxed_path(),
 		));
 	}
-	let requirement_path = project_root
-		.as_ref()
-		.join(reqtsv_lib::REQUIREMENT_TABLE_NAME);
+	let requirement_path = project_root.as_ref().join(&config.tables.requirement);
 	if requirement_path.exists() {
 		return Err(InitProjectErr::RequirementTblExists(
 			requirement_path.into_boxed_path(),
@@ -91,15 +101,23 @@ pub fn init_project(project_root: impl AsRef<Path>) -> Result<(), InitProjectErr
 	save_file_strict(requirement_file, REQUIREMENT_HEADER.as_bytes())
 		.map_err(InitProjectErr::WriteRequirementTbl)?;
 
+	init_project_config(project_root.as_ref()).map_err(InitProjectErr::Config)?;
+
 	Ok(())
 }
 
 pub trait ProjectCtx {
 	fn get_project_title(&self) -> &str;
+	fn get_project_description(&self) -> &str;
 	fn get_requirement_tbl_hash(&self) -> &str;
 	fn get_component_tbl_hash(&self) -> &str;
 	fn get_components(&self) -> &[Component];
 	fn get_requirements(&self) -> &[Requirement];
+	fn get_links(&self) -> &[Link];
+	fn get_fields(&self) -> &[FieldDef];
+	fn get_history(&self) -> &[RequirementRevision];
+	fn get_test_cases(&self) -> &[TestCase];
+	fn get_test_results(&self) -> &[TestResult];
 }
 
 fn open_overwrite<P: AsRef<Path>>(path: P) -> Result<File, std::io::Error> {
@@ -127,6 +145,10 @@ pub struct DisplayComponentPageName<'c>(&'c Component);
 
 impl<'c> Display for DisplayComponentPageName<'c> {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		// the prefix never changes so the page name is stable
+		if !self.0.prefix.is_empty() {
+			return write!(f, "{}.html", self.0.prefix);
+		}
 		write!(f, "{}-", self.0.id)?;
 		let name_chars = self
 			.0
@@ -155,6 +177,10 @@ pub enum BuildDocsErr {
 	CreateComponentsDir(#[source] std::io::Error, Box<Path>),
 	#[error("a file with the name \"components\" exists in the build directory at: {0:?}")]
 	ComponentsDirFileConflict(Box<Path>),
+	#[error("can't create tags directory: {1:?}, source error: {0:}")]
+	CreateTagsDir(#[source] std::io::Error, Box<Path>),
+	#[error("a file with the name \"tags\" exists in the build directory at: {0:?}")]
+	TagsDirFileConflict(Box<Path>),
 	#[error("can't copy {1:?} to {2:?}, source error: {0:}")]
 	CopyCss(#[source] std::io::Error, Box<Path>, Box<Path>),
 }
@@ -185,6 +211,11 @@ where
 			error!("{e}");
 			return Err(e);
 		}
+		if out_dir.tags_dir_is_file() {
+			let e = BuildDocsErr::TagsDirFileConflict(out_dir.as_path().into());
+			error!("{e}");
+			return Err(e);
+		}
 	} else {
 		info!("creating output directory at: {:?}", &out_dir);
 		create_dir(&out_dir)
@@ -244,8 +275,40 @@ where
 
 		file_name_buf.clear();
 	}
+
+	info!("Building tags.html");
+	let tags_path = OutDirTags::from_parent(components_dir.to_parent());
+	let tags_str = build_tags(ctx);
+	info!("Saving: {:?}", tags_path.as_path());
+	open_and_save(tags_path.as_path(), &tags_str).inspect_err(|e| error!("{e}"))?;
+
+	// tags/{tag}.html
+	let mut tags_dir = OutDirTagsDir::from_parent(tags_path.to_parent());
+
+	if !tags_dir.exists() {
+		info!("Createing tags directory {:?}", &tags_dir);
+		create_dir(&tags_dir)
+			.map_err(|e| BuildDocsErr::CreateTagsDir(e, tags_dir.as_path().into()))
+			.inspect_err(|e| error!("{e}"))?;
+	}
+
+	info!("Building tag pages");
+	for tag in collect_tags(ctx).keys() {
+		write!(&mut file_name_buf, "{}", DisplayTagPageName(tag)).unwrap();
+		debug!("createing tag file: {:?}", file_name_buf);
+
+		tags_dir
+			.with_pushed(file_name_buf.as_str(), |path| {
+				let tag_str = build_a_tag(ctx, tag);
+				open_and_save(path, &tag_str)
+			})
+			.inspect_err(|e| error!("{e}"))?;
+
+		file_name_buf.clear();
+	}
+
 	info!("Copying CSS");
-	let css_out_path = OutDirCSS::from_parent(components_dir.to_parent());
+	let css_out_path = OutDirCSS::from_parent(tags_dir.to_parent());
 	copy(&inputs.css_path, css_out_path.as_path())
 		.map_err(|e| BuildDocsErr::CopyCss(e, inputs.css_path, css_out_path.as_path().into()))
 		.inspect_err(|e| error!("{e}"))?;
@@ -287,17 +350,7 @@ pub struct RenderComponentPagePath<'c>(&'c Component);
 
 impl<'c> Display for RenderComponentPagePath<'c> {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "./components/{}-", self.0.id)?;
-		let name_chars = self
-			.0
-			.name
-			.chars()
-			.map(|ch| if ch == ' ' { '_' } else { ch });
-
-		for ch in name_chars {
-			f.write_char(ch)?;
-		}
-		write!(f, ".html")
+		write!(f, "./components/{}", DisplayComponentPageName(self.0))
 	}
 }
 
@@ -320,44 +373,90 @@ where
 		}
 
 		fn render_to(&self, buffer: &mut String) {
-			write!(buffer, "Component: {} - {}", self.0.id, self.0.name).unwrap()
+			write!(buffer, "Component: {} - {}", self.0.key(), self.0.name).unwrap()
 		}
 	}
 	// The component with links to each requirement, in order of ID.
 	let requirements = ctx.get_requirements();
+	let fields = ctx.get_fields();
+	let test_cases = ctx.get_test_cases();
+	let test_results = ctx.get_test_results();
 	let component_tbl_hash = ctx.get_component_tbl_hash();
 	let requirements_tbl_hash = ctx.get_requirement_tbl_hash();
 	let date = DateWrapper(&component.creation_date);
-	let filtered = requirements
+	let filtered: Vec<&Requirement> = requirements
+		.iter()
+		.filter(|requriemnt| requriemnt.component_id == component.id)
+		.collect();
+	let tree = RecordTree::new(&filtered);
+	let components = ctx.get_components();
+	let parent = component
+		.parent_id
+		.and_then(|id| find_component_by_id(id, components));
+	let sub_components: Vec<&Component> = components
 		.iter()
-		.filter(|requriemnt| requriemnt.component_id == component.id);
+		.filter(|c| c.parent_id == Some(component.id) && c.id != component.id)
+		.collect();
+	let rolled_up = count_requirements_under(component.id, components, requirements);
 	let body = html! {
 		p {a href="../components.html" { "All Components" }}
-		h1 { "ID: " (component.id) " - " (component.name)}
+		h1 { "Key: " (component.key()) " - " (component.name)}
 		p { span class="key" {"Components table hash: "} (component_tbl_hash)}
 		p { span class="key" {"Requirements table hash: "} (requirements_tbl_hash)}
+		@if let Some(parent) = parent {
+			p {span class="key" {"Path: "} span class="value" {(component_path(component.id, components))}}
+			p {span class="key" {"Parent: "} a href=(DisplayComponentPageName(parent)) {(parent.key()) " - " (parent.name)}}
+		}
+		p {span class="key" {"Requirements: "} span class="value" {(filtered.len())}}
+		p {span class="key" {"Requirements Including Sub-Components: "} span class="value" {(rolled_up)}}
 		p {span class="key" {"Status: "} span class="value" {(component.status)}}
 		p {span class="key" {"Date Created: "} span class="value" {(date)}}
 		p {span class="key" {"Author: "} span class="value" {(component.author)}}
 		h2 {"Description"}
 		p {(component.description)}
+		(render_tags(&component.tags, "../tags/"))
+		@if !sub_components.is_empty() {
+			h2 {"Sub-Components"}
+			@for sub in sub_components {
+				p {
+					a href=(DisplayComponentPageName(sub)) {(sub.key()) " - " (sub.name)}
+					" - " (count_requirements_under(sub.id, components, requirements)) " requirements"
+				}
+			}
+		}
 		h2 {"Requirements"}
 		br;
-		@for requirement in filtered {
-			article id=(requirement.id) class="entry" {
-				h2 { (requirement.id) " - " (requirement.title)}
+		(tree.render(&|requirement, children| html! {
+			@let key = requirement.key();
+			article id=(key) class="entry" {
+				h2 { (key) " - " (requirement.title)}
 				p {span class="key" {"Status: "} span class="value" {(requirement.status)}}
+				@if !requirement.status_justification.is_empty() {
+					p {span class="key" {"Status Justification: "} span class="value" {(requirement.status_justification)}}
+				}
 				p {span class="key" {"Version: "} span class="value" {(requirement.version)}}
 				p {span class="key" {"Date Created: "} span class="value" {(date)}}
 				p {span class="key" {"Author: "} span class="value" {(requirement.author)}}
+				@if let Some(parent) = requirement.parent_id.and_then(|id| find_requirement_by_id(id, requirements)) {
+					p {span class="key" {"Parent: "} a href={"../all-requirements.html#" (parent.key())} {(parent.key()) " - " (parent.title)}}
+				}
 				p {span class="key" {"Type: "} span class="value" {(requirement.functional)}}
 				p {span class="key" {"Priority: "} span class="value" {(requirement.priority)}}
+				(render_custom_fields(requirement, fields))
 				h3 {"Requirement Text"}
 				p {(requirement.requirement_text)}
+				h3 {"Acceptance Criteria"}
+				p {span class="key" {"Verification Method: "} span class="value" {(requirement.verification_method)}}
+				p {(requirement.acceptance_criteria)}
+				(render_verification(requirement, test_cases, test_results))
 				h3 {"Risks"}
 				p {(requirement.risks)}
+				(render_tags(&requirement.tags, "../tags/"))
+				@if let Some(children) = children {
+					(children)
+				}
 			}
-		}
+		}))
 	};
 	generic_sub_page(body, ctx.get_project_title(), Some(SubTitle(component)))
 }
@@ -396,6 +495,156 @@ fn find_component_by_id(id: u64, components: &[Component]) -> Option<&Component>
 		.or_else(|| components.iter().find(|comp| comp.id == id))
 }
 
+fn find_requirement_by_id(id: u64, requirements: &[Requirement]) -> Option<&Requirement> {
+	requirements
+		.get(id as usize)
+		.and_then(|req| if req.id == id { Some(req) } else { None })
+		.or_else(|| requirements.iter().find(|req| req.id == id))
+}
+
+/// Requirements of the component at `component_id` and of every component below it.
+fn count_requirements_under(
+	component_id: u64,
+	components: &[Component],
+	requirements: &[Requirement],
+) -> usize {
+	let parent_of = |id: u64| find_component_by_id(id, components).and_then(|c| c.parent_id);
+	requirements
+		.iter()
+		.filter(|r| ancestry(r.component_id, parent_of).contains(&component_id))
+		.count()
+}
+
+/// A record that can be nested under a parent record of the same type.
+trait TreeNode {
+	fn node_id(&self) -> u64;
+	fn parent_id(&self) -> Option<u64>;
+}
+
+impl TreeNode for Requirement {
+	fn node_id(&self) -> u64 {
+		self.id
+	}
+
+	fn parent_id(&self) -> Option<u64> {
+		self.parent_id
+	}
+}
+
+impl TreeNode for Component {
+	fn node_id(&self) -> u64 {
+		self.id
+	}
+
+	fn parent_id(&self) -> Option<u64> {
+		self.parent_id
+	}
+}
+
+/// Records grouped under their parent, in table order.
+/// A record whose parent isn't in the given set is a root.
+#[derive(Debug)]
+struct RecordTree<'r, T> {
+	all: Vec<&'r T>,
+	ids: HashSet<u64>,
+	children: HashMap<u64, Vec<&'r T>>,
+}
+
+impl<'r, T: TreeNode> RecordTree<'r, T> {
+	fn new(records: &[&'r T]) -> Self {
+		let ids: HashSet<u64> = records.iter().map(|r| r.node_id()).collect();
+		let mut children: HashMap<u64, Vec<&'r T>> = HashMap::new();
+		for record in records {
+			if let Some(parent_id) = record.parent_id().filter(|id| ids.contains(id)) {
+				children.entry(parent_id).or_default().push(record);
+			}
+		}
+		Self {
+			all: records.to_vec(),
+			ids,
+			children,
+		}
+	}
+
+	fn is_root(&self, record: &T) -> bool {
+		!record.parent_id().is_some_and(|id| self.ids.contains(&id))
+	}
+
+	/// `article` renders one record with its already rendered children, if any.
+	fn render(&self, article: &dyn Fn(&T, Option<Markup>) -> Markup) -> Markup {
+		let mut visited: HashSet<u64> = HashSet::with_capacity(self.all.len());
+		let mut buffer = String::new();
+		for root in self.all.iter().filter(|r| self.is_root(r)) {
+			self.render_node(root, article, &mut visited)
+				.render_to(&mut buffer);
+		}
+		// a hand edited table can hold a parent cycle that no root reaches
+		for record in self.all.iter() {
+			if !visited.contains(&record.node_id()) {
+				self.render_node(record, article, &mut visited)
+					.render_to(&mut buffer);
+			}
+		}
+		maud::PreEscaped(buffer)
+	}
+
+	fn render_node(
+		&self,
+		record: &T,
+		article: &dyn Fn(&T, Option<Markup>) -> Markup,
+		visited: &mut HashSet<u64>,
+	) -> Markup {
+		visited.insert(record.node_id());
+		let mut rendered: Vec<Markup> = Vec::new();
+		for child in self.children.get(&record.node_id()).into_iter().flatten() {
+			if !visited.contains(&child.node_id()) {
+				rendered.push(self.render_node(child, article, visited));
+			}
+		}
+		let children = if rendered.is_empty() {
+			None
+		} else {
+			Some(html! { div class="children" { @for child in rendered { (child) } } })
+		};
+		article(record, children)
+	}
+}
+
+/// Outgoing then incoming links of `requirement`, nothing if it has none.
+fn render_links(requirement: &Requirement, links: &[Link], requirements: &[Requirement]) -> Markup {
+	let outgoing: Vec<&Link> = links
+		.iter()
+		.filter(|l| l.is_active() && l.from_id == requirement.id)
+		.collect();
+	let incoming: Vec<&Link> = links
+		.iter()
+		.filter(|l| l.is_active() && l.to_id == requirement.id)
+		.collect();
+	let linked = |id: u64| {
+		html! {
+			@if let Some(other) = find_requirement_by_id(id, requirements) {
+				a href={"#" (other.key())} {(other.key()) " - " (other.title)}
+			} @else {
+				span class="value" {"Not Found"}
+			}
+		}
+	};
+	html! {
+		@if !outgoing.is_empty() {
+			h3 {"Outgoing Links"}
+			@for link in outgoing {
+				p {span class="key" {(link.kind) ": "} (linked(link.to_id))}
+			}
+		}
+		@if !incoming.is_empty() {
+			h3 {"Incoming Links"}
+			@for link in incoming {
+				p {(linked(link.from_id)) span class="key" {" " (link.kind) " this"}}
+			}
+		}
+	}
+}
+
 #[derive(Debug)]
 struct OpCompNameRender<'c>(pub Option<&'c Component>);
 
@@ -408,7 +657,7 @@ impl<'c> Render for OpCompNameRender<'c> {
 
 	fn render_to(&self, buffer: &mut String) {
 		match self.0 {
-			Some(component) => write!(buffer, "{} - {}", component.id, component.name).unwrap(),
+			Some(component) => write!(buffer, "{} - {}", component.key(), component.name).unwrap(),
 			None => write!(buffer, "Not Found").unwrap(),
 		}
 	}
@@ -422,20 +671,31 @@ where
 	let requirements_tbl_hash = ctx.get_requirement_tbl_hash();
 	let requirements: &[Requirement] = ctx.get_requirements();
 	let components = ctx.get_components();
+	let links = ctx.get_links();
+	let fields = ctx.get_fields();
+	let history = ctx.get_history();
+	let test_cases = ctx.get_test_cases();
+	let test_results = ctx.get_test_results();
 
 	let len = requirements.len();
+	let all: Vec<&Requirement> = requirements.iter().collect();
+	let tree = RecordTree::new(&all);
 	let body = html! {
 			h1 { "Requirement Table Info" }
 			p { span class="key" {"Requirements table hash: "} (requirements_tbl_hash)}
 			p {"Number of Requirements: " (len)}
 			h1 {"Requirement List"}
-			@for requirement in requirements {
+			(tree.render(&|requirement, children| html! {
 				@let date: DateWrapper = (&requirement.creation_date).into();
 				@let op_component = find_component_by_id(requirement.component_id, components);
 				@let comp_render = OpCompNameRender(op_component);
-				article id=(requirement.id) class="entry" {
-					h2 { (requirement.id) " - " (requirement.title)}
+				@let key = requirement.key();
+				article id=(key) class="entry" {
+					h2 { (key) " - " (requirement.title)}
 					p {span class="key" {"Status: "} span class="value" {(requirement.status)}}
+					@if !requirement.status_justification.is_empty() {
+						p {span class="key" {"Status Justification: "} span class="value" {(requirement.status_justification)}}
+					}
 					p {span class="key" {"Version: "} span class="value" {(requirement.version)}}
 					p {span class="key" {"Date Created: "} span class="value" {(date)}}
 					p {span class="key" {"Author: "} span class="value" {(requirement.author)}}
@@ -446,14 +706,28 @@ where
 						p {span class="key" {"Component: "} span class="value" {(comp_render)}}
 
 					}
+					@if let Some(parent) = requirement.parent_id.and_then(|id| find_requirement_by_id(id, requirements)) {
+						p {span class="key" {"Parent: "} a href={"#" (parent.key())} {(parent.key()) " - " (parent.title)}}
+					}
 					p {span class="key" {"Type: "} span class="value" {(requirement.functional)}}
 					p {span class="key" {"Priority: "} span class="value" {(requirement.priority)}}
+					(render_custom_fields(requirement, fields))
 					h3 {"Requirement Text"}
 					p {(requirement.requirement_text)}
+					h3 {"Acceptance Criteria"}
+					p {span class="key" {"Verification Method: "} span class="value" {(requirement.verification_method)}}
+					p {(requirement.acceptance_criteria)}
+					(render_verification(requirement, test_cases, test_results))
 					h3 {"Risks"}
 					p {(requirement.risks)}
+					(render_tags(&requirement.tags, "./tags/"))
+					(render_links(requirement, links, requirements))
+					(render_history(requirement, history))
+					@if let Some(children) = children {
+						(children)
+					}
 				}
-			}
+			}))
 	};
 	generic_root_page(body, project_title, Some("All Requirements"))
 }
@@ -466,55 +740,243 @@ where
 	let components_table_hash = ctx.get_component_tbl_hash();
 	let components = ctx.get_components();
 	let len = components.len();
+	let all: Vec<&Component> = components.iter().collect();
+	let tree = RecordTree::new(&all);
 	let body = html! {
 		h1 { "Component Table Info" }
 		p { span class="key" {"Components table hash: "} (components_table_hash)}
 		p {"Number of Components: " (len)}
 		h1 {"Component List"}
-		@for component in components {
+		(tree.render(&|component, children| html! {
 			@let date: DateWrapper = (&component.creation_date).into();
-			article id=(component.id) class="entry" {
+			@let key = component.key();
+			article id=(key) class="entry" {
 				@let component_page_path = RenderComponentPagePath(component);
 				h2 {
-					a href=(component_page_path) { (component.id) " - " (component.name)}
+					a href=(component_page_path) { (key) " - " (component.name)}
 				}
 				p {span class="key" {"Status: "} span class="value" {(component.status)}}
 				p {span class="key" {"Date Created: "} span class="value" {(date)}}
 				p {span class="key" {"Author: "} span class="value" {(component.author)}}
 				h3 {"Description"}
 				p {(component.description)}
+				(render_tags(&component.tags, "./tags/"))
+				@if let Some(children) = children {
+					(children)
+				}
 			}
-		}
+		}))
 	};
 	generic_root_page(body, project_title, Some("Components"))
 }
 
 pub fn build_index(ctx: &impl ProjectCtx) -> Box<str> {
 	let project_title = ctx.get_project_title();
+	let project_description = ctx.get_project_description();
 	let requirements_table_hash = ctx.get_requirement_tbl_hash();
 	let components_table_hash = ctx.get_component_tbl_hash();
 	let components = ctx.get_components();
+	let all: Vec<&Component> = components.iter().collect();
+	let tree = RecordTree::new(&all);
 	let body = html! {
 			h1 { "Project Info" }
 			p { span class="key" {"Title: "} (project_title) }
+			@if !project_description.is_empty() {
+				p { (project_description) }
+			}
 			a href="https://github.com/jman4747/reqtsv" { "Project Repository" }
 			p { span class="key" {"Requirements Table Hash: "} (requirements_table_hash) }
 			p { span class="key" {"Components Table Hash: "} (components_table_hash) }
 			h1 { "Pages" }
 			p { a href="./components.html" {"Components"}}
 			p { a href="./all-requirements.html" {"Requirements"}}
+			p { a href="./tags.html" {"Tags"}}
 			h2 {"Component Pages"}
-			@for component in components {
+			(tree.render(&|component, children| html! {
 				@let component_page_path = RenderComponentPagePath(component);
 				p {
-					a href=(component_page_path) { (component.id) " - " (component.name)}
+					a href=(component_page_path) { (component.key()) " - " (component.name)}
 				}
-			}
+				@if let Some(children) = children {
+					(children)
+				}
+			}))
 	};
 	let _n: Option<&str> = None;
 	generic_root_page(body, project_title, _n)
 }
 
+/// Escapes a tag into a file name, anything but ASCII letters, digits, and '-' becomes "_XX" hex.
+#[derive(Debug)]
+pub struct DisplayTagPageName<'t>(&'t str);
+
+impl<'t> Display for DisplayTagPageName<'t> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for byte in self.0.bytes() {
+			if byte.is_ascii_alphanumeric() || byte == b'-' {
+				f.write_char(byte as char)?;
+			} else {
+				write!(f, "_{byte:02X}")?;
+			}
+		}
+		write!(f, ".html")
+	}
+}
+
+impl<'t> Render for DisplayTagPageName<'t> {
+	fn render_to(&self, buffer: &mut String) {
+		write!(buffer, "{}", &self).unwrap()
+	}
+}
+
+/// Every tag in the project with the number of components and requirements using it.
+fn collect_tags<Ctx: ProjectCtx>(ctx: &Ctx) -> BTreeMap<&str, (usize, usize)> {
+	let mut tags: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+	for tag in ctx.get_components().iter().flat_map(|c| c.tags.iter()) {
+		tags.entry(tag).or_default().0 += 1;
+	}
+	for tag in ctx.get_requirements().iter().flat_map(|r| r.tags.iter()) {
+		tags.entry(tag).or_default().1 += 1;
+	}
+	tags
+}
+
+/// One line per custom field that has a value, in the order the project declares them.
+fn render_custom_fields(requirement: &Requirement, fields: &[FieldDef]) -> Markup {
+	html! {
+		@for def in fields {
+			@let value = requirement.custom.get(&def.name);
+			@if !value.is_empty() {
+				p {span class="key" {(def.label()) ": "} span class="value" {(value)}}
+			}
+		}
+	}
+}
+
+/// The requirement's verification status and the latest run of each test case covering it.
+fn render_verification(
+	requirement: &Requirement,
+	test_cases: &[TestCase],
+	results: &[TestResult],
+) -> Markup {
+	let status = verification_status(requirement.id, test_cases, results);
+	let covering: Vec<&TestCase> = test_cases
+		.iter()
+		.filter(|tc| tc.is_active() && tc.requirement_ids.contains(requirement.id))
+		.collect();
+	html! {
+		p {span class="key" {"Verification Status: "} span class="value" {(status)}}
+		@for test_case in covering {
+			p class="test-case" {
+				span class="key" {(test_case.key()) " - " (test_case.title) ": "}
+				@if let Some(result) = latest_result(test_case.id, results) {
+					@let date: DateWrapper = (&result.run_date).into();
+					span class="value" {(result.outcome) " on " (date)}
+					@if !result.evidence.is_empty() {
+						" (" (result.evidence) ")"
+					}
+				} @else {
+					span class="value" {"Not Run"}
+				}
+			}
+		}
+	}
+}
+
+/// Every prior version of the requirement, newest first, nothing if it was never edited.
+fn render_history(requirement: &Requirement, history: &[RequirementRevision]) -> Markup {
+	let mut revisions: Vec<&RequirementRevision> = history
+		.iter()
+		.filter(|r| r.requirement_id == requirement.id)
+		.collect();
+	revisions.sort_by_key(|r| std::cmp::Reverse(r.version));
+	html! {
+		@if !revisions.is_empty() {
+			details class="history" {
+				summary {"History (" (revisions.len()) " prior versions)"}
+				@for revision in revisions {
+					@let date: DateWrapper = (&revision.superseded_on).into();
+					section class="revision" {
+						h4 {"Version " (revision.version)}
+						p {span class="key" {"Superseded: "} span class="value" {(date)}}
+						p {span class="key" {"Title: "} span class="value" {(revision.title)}}
+						p {span class="key" {"Author: "} span class="value" {(revision.author)}}
+						p {span class="key" {"Type: "} span class="value" {(revision.functional)}}
+						p {span class="key" {"Priority: "} span class="value" {(revision.priority)}}
+						p {span class="key" {"Requirement Text: "} span class="value" {(revision.requirement_text)}}
+						p {span class="key" {"Verification Method: "} span class="value" {(revision.verification_method)}}
+						p {span class="key" {"Acceptance Criteria: "} span class="value" {(revision.acceptance_criteria)}}
+						p {span class="key" {"Risks: "} span class="value" {(revision.risks)}}
+					}
+				}
+			}
+		}
+	}
+}
+
+/// Links to each tag page, nothing if there are no tags.
+fn render_tags(tags: &Tags, tags_dir: &str) -> Markup {
+	html! {
+		@if !tags.is_empty() {
+			p {
+				span class="key" {"Tags: "}
+				@for tag in tags.iter() {
+					a class="tag" href={(tags_dir) (DisplayTagPageName(tag))} {(tag)} " "
+				}
+			}
+		}
+	}
+}
+
+pub fn build_tags<Ctx>(ctx: &Ctx) -> Box<str>
+where
+	Ctx: ProjectCtx,
+{
+	let project_title = ctx.get_project_title();
+	let tags = collect_tags(ctx);
+	let len = tags.len();
+	let body = html! {
+		h1 { "Tags" }
+		p {"Number of Tags: " (len)}
+		@for (tag, (components, requirements)) in tags {
+			p {
+				a href={"./tags/" (DisplayTagPageName(tag))} {(tag)}
+				" - " (components) " components, " (requirements) " requirements"
+			}
+		}
+	};
+	generic_root_page(body, project_title, Some("Tags"))
+}
+
+pub fn build_a_tag<Ctx>(ctx: &Ctx, tag: &str) -> Box<str>
+where
+	Ctx: ProjectCtx,
+{
+	let components = ctx.get_components().iter().filter(|c| c.tags.contains(tag));
+	let requirements = ctx
+		.get_requirements()
+		.iter()
+		.filter(|r| r.tags.contains(tag));
+	let body = html! {
+		p {a href="../tags.html" { "All Tags" }}
+		h1 { "Tag: " (tag) }
+		h2 { "Components" }
+		@for component in components {
+			@let component_page_path = format!("../components/{}", DisplayComponentPageName(component));
+			p {
+				a href=(component_page_path) { (component.key()) " - " (component.name)}
+			}
+		}
+		h2 { "Requirements" }
+		@for requirement in requirements {
+			p {
+				a href={"../all-requirements.html#" (requirement.key())} { (requirement.key()) " - " (requirement.title)}
+			}
+		}
+	};
+	generic_sub_page(body, ctx.get_project_title(), Some(format!("Tag: {tag}")))
+}
+
 pub fn hashed_table(raw_table: impl AsRef<[u8]>) -> Box<str> {
 	let mut hasher = sha3::Sha3_256::new();
 	hasher.update(raw_table.as_ref());
@@ -527,7 +989,11 @@ pub fn hashed_table(raw_table: impl AsRef<[u8]>) -> Box<str> {
 mod tests {
 	use super::*;
 	use chrono::prelude::*;
-	use reqtsv_lib::{RecordStatus, RequirementFunctional, RequirementPriority};
+	use reqtsv_lib::{
+		IdList, LinkKind, RecordStatus, RequirementFunctional, RequirementPriority, TestOutcome,
+		VerificationMethod,
+		custom_field::{CustomFields, FieldKind},
+	};
 
 	#[test]
 	fn test_table_hash() {
@@ -545,6 +1011,10 @@ mod tests {
 				"Reqcsv Title"
 			}
 
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
 			fn get_requirement_tbl_hash(&self) -> &str {
 				"123"
 			}
@@ -560,14 +1030,38 @@ mod tests {
 			fn get_requirements(&self) -> &[Requirement] {
 				todo!()
 			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
 		}
 		let comp = Component {
 			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 3,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		let ctx = MockProject([comp]);
 		let built = build_index(&ctx);
@@ -586,19 +1080,27 @@ mod tests {
 		struct MockProject([Component; 2]);
 		let comp_a = Component {
 			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 3,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		let comp_b = Component {
 			id: 1,
+			prefix: "COMPB".into(),
+			next_seq: 3,
+			parent_id: None,
 			name: "Comp B".into(),
 			description: "Test B".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author B".into(),
+			tags: Tags::default(),
 		};
 		let components: [Component; 2] = [comp_a, comp_b];
 		impl ProjectCtx for MockProject {
@@ -606,6 +1108,10 @@ mod tests {
 				"Reqcsv Title"
 			}
 
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
 			fn get_requirement_tbl_hash(&self) -> &str {
 				"123"
 			}
@@ -621,6 +1127,26 @@ mod tests {
 			fn get_requirements(&self) -> &[Requirement] {
 				todo!()
 			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
 		}
 		let ctx = MockProject(components);
 		let built = build_components(&ctx);
@@ -639,46 +1165,68 @@ mod tests {
 		struct MockProject([Requirement; 2], [Component; 2]);
 		let req_a = Requirement {
 			id: 0,
+			key: "COMPA-0001".into(),
 			title: "Requirement A".into(),
 			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author A".into(),
 			component_id: 0,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let req_b = Requirement {
 			id: 1,
+			key: "COMPB-0001".into(),
 			title: "Requirement B".into(),
 			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author B".into(),
 			component_id: 1,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let requirements: [Requirement; 2] = [req_a, req_b];
 		let comp_a = Component {
 			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 3,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		let comp_b = Component {
 			id: 1,
+			prefix: "COMPB".into(),
+			next_seq: 3,
+			parent_id: None,
 			name: "Comp B".into(),
 			description: "Test B".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author B".into(),
+			tags: Tags::default(),
 		};
 		let components: [Component; 2] = [comp_a, comp_b];
 		impl ProjectCtx for MockProject {
@@ -686,6 +1234,10 @@ mod tests {
 				"Reqcsv Title"
 			}
 
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
 			fn get_requirement_tbl_hash(&self) -> &str {
 				"123"
 			}
@@ -701,6 +1253,26 @@ mod tests {
 			fn get_requirements(&self) -> &[Requirement] {
 				&self.0
 			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
 		}
 		let ctx = MockProject(requirements, components);
 		let built = build_all_requirements(&ctx);
@@ -719,46 +1291,68 @@ mod tests {
 		struct MockProject([Requirement; 2], [Component; 2]);
 		let req_a = Requirement {
 			id: 0,
+			key: "COMPA-0001".into(),
 			title: "Requirement A".into(),
 			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author A".into(),
 			component_id: 0,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let req_b = Requirement {
 			id: 1,
+			key: "COMPB-0001".into(),
 			title: "Requirement B".into(),
 			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author B".into(),
 			component_id: 1,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let requirements: [Requirement; 2] = [req_a, req_b];
 		let comp_a = Component {
 			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 3,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		let comp_b = Component {
 			id: 1,
+			prefix: "COMPB".into(),
+			next_seq: 3,
+			parent_id: None,
 			name: "Comp B".into(),
 			description: "Test B".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author B".into(),
+			tags: Tags::default(),
 		};
 		// this is the key part of this test!
 		let components: [Component; 2] = [comp_b, comp_a];
@@ -767,6 +1361,10 @@ mod tests {
 				"Reqcsv Title"
 			}
 
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
 			fn get_requirement_tbl_hash(&self) -> &str {
 				"123"
 			}
@@ -782,6 +1380,26 @@ mod tests {
 			fn get_requirements(&self) -> &[Requirement] {
 				&self.0
 			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
 		}
 		let ctx = MockProject(requirements, components);
 		let built = build_all_requirements(&ctx);
@@ -800,38 +1418,56 @@ mod tests {
 		struct MockProject([Requirement; 2], [Component; 1]);
 		let req_a = Requirement {
 			id: 0,
+			key: "COMPA-0001".into(),
 			title: "Requirement A".into(),
 			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author A".into(),
 			component_id: 0,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let req_b = Requirement {
 			id: 1,
+			key: "COMPB-0001".into(),
 			title: "Requirement B".into(),
 			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author B".into(),
 			component_id: 1,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let requirements: [Requirement; 2] = [req_a, req_b];
 		let comp_a = Component {
 			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 3,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		// this is the key part of this test!
 		let components: [Component; 1] = [comp_a];
@@ -840,6 +1476,10 @@ mod tests {
 				"Reqcsv Title"
 			}
 
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
 			fn get_requirement_tbl_hash(&self) -> &str {
 				"123"
 			}
@@ -855,6 +1495,26 @@ mod tests {
 			fn get_requirements(&self) -> &[Requirement] {
 				&self.0
 			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
 		}
 		let ctx = MockProject(requirements, components);
 		let built = build_all_requirements(&ctx);
@@ -870,54 +1530,79 @@ mod tests {
 
 	#[test]
 	fn test_build_a_component() {
-		struct MockProject([Requirement; 3]);
+		struct MockProject([Requirement; 3], [Component; 1]);
 		let req_a = Requirement {
 			id: 0,
+			key: "COMPA-0001".into(),
 			title: "Requirement A".into(),
 			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author A".into(),
 			component_id: 0,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let req_b = Requirement {
 			id: 1,
+			key: "COMPB-0001".into(),
 			title: "Requirement B".into(),
 			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author B".into(),
 			component_id: 1,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let req_c = Requirement {
 			id: 2,
+			key: "COMPA-0002".into(),
 			title: "Requirement C".into(),
 			requirement_text: "Thing shall do c".into(),
+			acceptance_criteria: "Show it does c".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 18, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author C".into(),
 			component_id: 0,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk C".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let requirements: [Requirement; 3] = [req_a, req_b, req_c];
 		let component = Component {
 			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 3,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		// this is the key part of this test!
 		impl ProjectCtx for MockProject {
@@ -925,6 +1610,10 @@ mod tests {
 				"Reqcsv Title"
 			}
 
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
 			fn get_requirement_tbl_hash(&self) -> &str {
 				"123"
 			}
@@ -934,16 +1623,1186 @@ mod tests {
 			}
 
 			fn get_components(&self) -> &[Component] {
-				todo!()
+				&self.1
 			}
 
 			fn get_requirements(&self) -> &[Requirement] {
 				&self.0
 			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
 		}
-		let ctx = MockProject(requirements);
-		let built = build_a_component(&ctx, &component);
-		let page = include_str!("./components/0-Comp_A.html");
+		let ctx = MockProject(requirements, [component]);
+		let built = build_a_component(&ctx, &ctx.1[0]);
+		let page = include_str!("./components/COMPA.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		)
+	}
+
+	#[test]
+	fn test_build_all_requirements_nested() {
+		struct MockProject([Requirement; 3], [Component; 2]);
+		let req_a = Requirement {
+			id: 0,
+			key: "COMPA-0001".into(),
+			title: "Requirement A".into(),
+			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author A".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		// listed before its parent on purpose
+		let req_b = Requirement {
+			id: 1,
+			key: "COMPB-0001".into(),
+			title: "Requirement B".into(),
+			requirement_text: "Sub thing shall do B".into(),
+			acceptance_criteria: "Show it does sub B".into(),
+			verification_method: VerificationMethod::Inspection,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 17, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author B".into(),
+			component_id: 1,
+			parent_id: Some(2),
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let req_c = Requirement {
+			id: 2,
+			key: "COMPA-0002".into(),
+			title: "Requirement C".into(),
+			requirement_text: "Thing shall do C".into(),
+			acceptance_criteria: "Show it does C".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 18, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author C".into(),
+			component_id: 0,
+			parent_id: Some(0),
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk C".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let requirements: [Requirement; 3] = [req_a, req_b, req_c];
+		let comp_a = Component {
+			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 3,
+			parent_id: None,
+			name: "Comp A".into(),
+			description: "Test A".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::default(),
+		};
+		let comp_b = Component {
+			id: 1,
+			prefix: "COMPB".into(),
+			next_seq: 2,
+			parent_id: None,
+			name: "Comp B".into(),
+			description: "Test B".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 17, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author B".into(),
+			tags: Tags::default(),
+		};
+		let components: [Component; 2] = [comp_a, comp_b];
+		impl ProjectCtx for MockProject {
+			fn get_project_title(&self) -> &str {
+				"Reqcsv Title"
+			}
+
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
+			fn get_requirement_tbl_hash(&self) -> &str {
+				"123"
+			}
+
+			fn get_component_tbl_hash(&self) -> &str {
+				"ABC"
+			}
+
+			fn get_components(&self) -> &[Component] {
+				&self.1
+			}
+
+			fn get_requirements(&self) -> &[Requirement] {
+				&self.0
+			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
+		}
+		let ctx = MockProject(requirements, components);
+		let built = build_all_requirements(&ctx);
+		let page = include_str!("./all-requirements-nested.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		);
+		let built = build_a_component(&ctx, &ctx.1[0]);
+		let page = include_str!("./components/COMPA-nested.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		)
+	}
+
+	#[test]
+	fn test_build_all_requirements_links() {
+		struct MockProject([Requirement; 2], [Component; 1], [Link; 3]);
+		let req_a = Requirement {
+			id: 0,
+			key: "COMPA-0001".into(),
+			title: "Requirement A".into(),
+			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author A".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let req_b = Requirement {
+			id: 1,
+			key: "COMPA-0002".into(),
+			title: "Requirement B".into(),
+			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 17, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author B".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let comp_a = Component {
+			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 3,
+			parent_id: None,
+			name: "Comp A".into(),
+			description: "Test A".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::default(),
+		};
+		let link_a = Link {
+			id: 0,
+			from_id: 1,
+			kind: LinkKind::DerivesFrom,
+			to_id: 0,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 18, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+		};
+		let link_b = Link {
+			id: 1,
+			from_id: 0,
+			kind: LinkKind::DependsOn,
+			to_id: 5,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 18, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+		};
+		// removed links aren't shown
+		let link_c = Link {
+			id: 2,
+			from_id: 0,
+			kind: LinkKind::ConflictsWith,
+			to_id: 1,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 18, 0, 0, 0).unwrap(),
+			status: RecordStatus::Deleted,
+		};
+		impl ProjectCtx for MockProject {
+			fn get_project_title(&self) -> &str {
+				"Reqcsv Title"
+			}
+
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
+			fn get_requirement_tbl_hash(&self) -> &str {
+				"123"
+			}
+
+			fn get_component_tbl_hash(&self) -> &str {
+				"ABC"
+			}
+
+			fn get_components(&self) -> &[Component] {
+				&self.1
+			}
+
+			fn get_requirements(&self) -> &[Requirement] {
+				&self.0
+			}
+
+			fn get_links(&self) -> &[Link] {
+				&self.2
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
+		}
+		let ctx = MockProject([req_a, req_b], [comp_a], [link_a, link_b, link_c]);
+		let built = build_all_requirements(&ctx);
+		let page = include_str!("./all-requirements-links.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		)
+	}
+
+	#[test]
+	fn test_build_all_requirements_justification() {
+		struct MockProject([Requirement; 2], [Component; 1], [Link; 0]);
+		// superseded requirements say why
+		let req_a = Requirement {
+			id: 0,
+			key: "COMPA-0001".into(),
+			title: "Requirement A".into(),
+			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Obsolete,
+			status_justification: "Replaced by COMPA-0002".into(),
+			author: "Author A".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let req_b = Requirement {
+			id: 1,
+			key: "COMPA-0002".into(),
+			title: "Requirement B".into(),
+			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 17, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author B".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let comp_a = Component {
+			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 3,
+			parent_id: None,
+			name: "Comp A".into(),
+			description: "Test A".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::default(),
+		};
+		impl ProjectCtx for MockProject {
+			fn get_project_title(&self) -> &str {
+				"Reqcsv Title"
+			}
+
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
+			fn get_requirement_tbl_hash(&self) -> &str {
+				"123"
+			}
+
+			fn get_component_tbl_hash(&self) -> &str {
+				"ABC"
+			}
+
+			fn get_components(&self) -> &[Component] {
+				&self.1
+			}
+
+			fn get_requirements(&self) -> &[Requirement] {
+				&self.0
+			}
+
+			fn get_links(&self) -> &[Link] {
+				&self.2
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
+		}
+		let ctx = MockProject([req_a, req_b], [comp_a], []);
+		let built = build_all_requirements(&ctx);
+		let page = include_str!("./all-requirements-justification.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		)
+	}
+
+	#[test]
+	fn test_build_tags() {
+		struct MockProject([Requirement; 2], [Component; 1]);
+		let req_a = Requirement {
+			id: 0,
+			key: "COMPA-0001".into(),
+			title: "Requirement A".into(),
+			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author A".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk A".into(),
+			tags: Tags::from(vec!["safety".to_string(), "user interface".to_string()]),
+			custom: CustomFields::default(),
+		};
+		let req_b = Requirement {
+			id: 1,
+			key: "COMPA-0002".into(),
+			title: "Requirement B".into(),
+			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 17, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author B".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let comp_a = Component {
+			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 3,
+			parent_id: None,
+			name: "Comp A".into(),
+			description: "Test A".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::from(vec!["safety".to_string()]),
+		};
+		impl ProjectCtx for MockProject {
+			fn get_project_title(&self) -> &str {
+				"Reqcsv Title"
+			}
+
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
+			fn get_requirement_tbl_hash(&self) -> &str {
+				"123"
+			}
+
+			fn get_component_tbl_hash(&self) -> &str {
+				"ABC"
+			}
+
+			fn get_components(&self) -> &[Component] {
+				&self.1
+			}
+
+			fn get_requirements(&self) -> &[Requirement] {
+				&self.0
+			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
+		}
+		let ctx = MockProject([req_a, req_b], [comp_a]);
+		let built = build_tags(&ctx);
+		let page = include_str!("./tags.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		);
+		assert_eq!(
+			"user_20interface.html",
+			DisplayTagPageName("user interface").to_string()
+		);
+		let built = build_a_tag(&ctx, "safety");
+		let page = include_str!("./tags/safety.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		)
+	}
+
+	#[test]
+	fn test_build_all_requirements_custom_fields() {
+		struct MockProject([Requirement; 1], [Component; 1], [FieldDef; 3]);
+		let req_a = Requirement {
+			id: 0,
+			key: "COMPA-0001".into(),
+			title: "Requirement A".into(),
+			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author A".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields(
+				[
+					("safety_class", "B"),
+					("customer_ref", "ACME-12"),
+					("not_in_config", "hidden"),
+				]
+				.into_iter()
+				.map(|(name, value)| (name.to_string(), value.to_string()))
+				.collect(),
+			),
+		};
+		let comp_a = Component {
+			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 2,
+			parent_id: None,
+			name: "Comp A".into(),
+			description: "Test A".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::default(),
+		};
+		let fields = [
+			FieldDef {
+				name: "customer_ref".into(),
+				label: Some("Customer Reference".into()),
+				kind: FieldKind::String,
+				required: false,
+			},
+			FieldDef {
+				name: "safety_class".into(),
+				label: None,
+				kind: FieldKind::Enum {
+					values: vec!["A".into(), "B".into()],
+				},
+				required: true,
+			},
+			FieldDef {
+				name: "due".into(),
+				label: None,
+				kind: FieldKind::Date,
+				required: false,
+			},
+		];
+		impl ProjectCtx for MockProject {
+			fn get_project_title(&self) -> &str {
+				"Reqcsv Title"
+			}
+
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
+			fn get_requirement_tbl_hash(&self) -> &str {
+				"123"
+			}
+
+			fn get_component_tbl_hash(&self) -> &str {
+				"ABC"
+			}
+
+			fn get_components(&self) -> &[Component] {
+				&self.1
+			}
+
+			fn get_requirements(&self) -> &[Requirement] {
+				&self.0
+			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&self.2
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
+		}
+		let ctx = MockProject([req_a], [comp_a], fields);
+		let built = build_all_requirements(&ctx);
+		let page = include_str!("./all-requirements-custom-fields.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		)
+	}
+
+	#[test]
+	fn test_build_all_requirements_history() {
+		struct MockProject([Requirement; 1], [Component; 1], [RequirementRevision; 2]);
+		let req_a = Requirement {
+			id: 0,
+			key: "COMPA-0001".into(),
+			title: "Requirement A".into(),
+			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author A".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 2,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let comp_a = Component {
+			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 2,
+			parent_id: None,
+			name: "Comp A".into(),
+			description: "Test A".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::default(),
+		};
+		let history = [
+			RequirementRevision {
+				requirement_id: 0,
+				version: 0,
+				title: "Requirement A".into(),
+				functional: RequirementFunctional::Functional,
+				requirement_text: "Thing shall do a".into(),
+				acceptance_criteria: String::new(),
+				verification_method: VerificationMethod::Inspection,
+				author: "Author A".into(),
+				priority: RequirementPriority::Low,
+				risks: "Risk A".into(),
+				tags: Tags::default(),
+				superseded_on: Local.with_ymd_and_hms(2025, 6, 17, 0, 0, 0).unwrap(),
+			},
+			RequirementRevision {
+				requirement_id: 0,
+				version: 1,
+				title: "Requirement A".into(),
+				functional: RequirementFunctional::Functional,
+				requirement_text: "Thing shall do A".into(),
+				acceptance_criteria: String::new(),
+				verification_method: VerificationMethod::Test,
+				author: "Author A".into(),
+				priority: RequirementPriority::Low,
+				risks: "Risk A".into(),
+				tags: Tags::default(),
+				superseded_on: Local.with_ymd_and_hms(2025, 6, 18, 0, 0, 0).unwrap(),
+			},
+		];
+		impl ProjectCtx for MockProject {
+			fn get_project_title(&self) -> &str {
+				"Reqcsv Title"
+			}
+
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
+			fn get_requirement_tbl_hash(&self) -> &str {
+				"123"
+			}
+
+			fn get_component_tbl_hash(&self) -> &str {
+				"ABC"
+			}
+
+			fn get_components(&self) -> &[Component] {
+				&self.1
+			}
+
+			fn get_requirements(&self) -> &[Requirement] {
+				&self.0
+			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&self.2
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
+		}
+		let ctx = MockProject([req_a], [comp_a], history);
+		let built = build_all_requirements(&ctx);
+		let page = include_str!("./all-requirements-history.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		)
+	}
+
+	#[test]
+	fn test_build_all_requirements_test_cases() {
+		struct MockProject(
+			[Requirement; 3],
+			[Component; 1],
+			[TestCase; 2],
+			[TestResult; 3],
+		);
+		let req_a = Requirement {
+			id: 0,
+			key: "COMPA-0001".into(),
+			title: "Requirement A".into(),
+			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author A".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let req_b = Requirement {
+			id: 1,
+			key: "COMPA-0002".into(),
+			title: "Requirement B".into(),
+			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author A".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let req_c = Requirement {
+			id: 2,
+			key: "COMPA-0003".into(),
+			title: "Requirement C".into(),
+			requirement_text: "Thing shall do C".into(),
+			acceptance_criteria: "Show it does C".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author A".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk C".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let comp_a = Component {
+			id: 0,
+			prefix: "COMPA".into(),
+			next_seq: 4,
+			parent_id: None,
+			name: "Comp A".into(),
+			description: "Test A".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::default(),
+		};
+		let test_cases = [
+			TestCase {
+				id: 0,
+				key: "TC-0001".into(),
+				title: "Bench test".into(),
+				requirement_ids: IdList::from(vec![0, 1]),
+				procedure: "Run it on the bench".into(),
+				creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+				status: RecordStatus::Accepted,
+				author: "Author A".into(),
+			},
+			TestCase {
+				id: 1,
+				key: "TC-0002".into(),
+				title: "Field test".into(),
+				requirement_ids: IdList::from(vec![1]),
+				procedure: "Run it in the field".into(),
+				creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+				status: RecordStatus::Accepted,
+				author: "Author A".into(),
+			},
+		];
+		let results = [
+			TestResult {
+				id: 0,
+				test_case_id: 0,
+				outcome: TestOutcome::Fail,
+				run_date: Local.with_ymd_and_hms(2025, 6, 17, 0, 0, 0).unwrap(),
+				evidence: "logs/bench-1.txt".into(),
+				author: "Author A".into(),
+			},
+			TestResult {
+				id: 1,
+				test_case_id: 0,
+				outcome: TestOutcome::Pass,
+				run_date: Local.with_ymd_and_hms(2025, 6, 18, 0, 0, 0).unwrap(),
+				evidence: "logs/bench-2.txt".into(),
+				author: "Author A".into(),
+			},
+			TestResult {
+				id: 2,
+				test_case_id: 1,
+				outcome: TestOutcome::Fail,
+				run_date: Local.with_ymd_and_hms(2025, 6, 18, 0, 0, 0).unwrap(),
+				evidence: String::new(),
+				author: "Author A".into(),
+			},
+		];
+		impl ProjectCtx for MockProject {
+			fn get_project_title(&self) -> &str {
+				"Reqcsv Title"
+			}
+
+			fn get_project_description(&self) -> &str {
+				""
+			}
+
+			fn get_requirement_tbl_hash(&self) -> &str {
+				"123"
+			}
+
+			fn get_component_tbl_hash(&self) -> &str {
+				"ABC"
+			}
+
+			fn get_components(&self) -> &[Component] {
+				&self.1
+			}
+
+			fn get_requirements(&self) -> &[Requirement] {
+				&self.0
+			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&self.2
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&self.3
+			}
+		}
+		let ctx = MockProject([req_a, req_b, req_c], [comp_a], test_cases, results);
+		let built = build_all_requirements(&ctx);
+		let page = include_str!("./all-requirements-test-cases.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		)
+	}
+
+	#[test]
+	fn test_build_nested_components() {
+		struct MockProject([Requirement; 3], [Component; 4]);
+		let vehicle_req = Requirement {
+			id: 0,
+			key: "VEH-0001".into(),
+			title: "Vehicle Req".into(),
+			requirement_text: "Thing shall do Vehicle Req".into(),
+			acceptance_criteria: "Show it does Vehicle Req".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author A".into(),
+			component_id: 0,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let powertrain_req = Requirement {
+			id: 1,
+			key: "PWR-0001".into(),
+			title: "Powertrain Req".into(),
+			requirement_text: "Thing shall do Powertrain Req".into(),
+			acceptance_criteria: "Show it does Powertrain Req".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author A".into(),
+			component_id: 1,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let inverter_req = Requirement {
+			id: 2,
+			key: "INV-0001".into(),
+			title: "Inverter Req".into(),
+			requirement_text: "Thing shall do Inverter Req".into(),
+			acceptance_criteria: "Show it does Inverter Req".into(),
+			verification_method: VerificationMethod::Test,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			author: "Author A".into(),
+			component_id: 2,
+			parent_id: None,
+			functional: RequirementFunctional::Functional,
+			version: 0,
+			priority: RequirementPriority::Mandated,
+			risks: "Risk".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		let vehicle = Component {
+			id: 0,
+			prefix: "VEH".into(),
+			next_seq: 2,
+			parent_id: None,
+			name: "Vehicle".into(),
+			description: "The vehicle".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::default(),
+		};
+		let powertrain = Component {
+			id: 1,
+			prefix: "PWR".into(),
+			next_seq: 2,
+			parent_id: Some(0),
+			name: "Powertrain".into(),
+			description: "The powertrain".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::default(),
+		};
+		let inverter = Component {
+			id: 2,
+			prefix: "INV".into(),
+			next_seq: 2,
+			parent_id: Some(1),
+			name: "Inverter".into(),
+			description: "The inverter".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::default(),
+		};
+		let motor = Component {
+			id: 3,
+			prefix: "MOT".into(),
+			next_seq: 2,
+			parent_id: Some(1),
+			name: "Motor".into(),
+			description: "The motor".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::default(),
+		};
+		impl ProjectCtx for MockProject {
+			fn get_project_title(&self) -> &str {
+				"Reqcsv Title"
+			}
+
+			fn get_project_description(&self) -> &str {
+				"Components nested under a vehicle"
+			}
+
+			fn get_requirement_tbl_hash(&self) -> &str {
+				"123"
+			}
+
+			fn get_component_tbl_hash(&self) -> &str {
+				"ABC"
+			}
+
+			fn get_components(&self) -> &[Component] {
+				&self.1
+			}
+
+			fn get_requirements(&self) -> &[Requirement] {
+				&self.0
+			}
+
+			fn get_links(&self) -> &[Link] {
+				&[]
+			}
+
+			fn get_fields(&self) -> &[FieldDef] {
+				&[]
+			}
+
+			fn get_history(&self) -> &[RequirementRevision] {
+				&[]
+			}
+
+			fn get_test_cases(&self) -> &[TestCase] {
+				&[]
+			}
+
+			fn get_test_results(&self) -> &[TestResult] {
+				&[]
+			}
+		}
+		let ctx = MockProject(
+			[vehicle_req, powertrain_req, inverter_req],
+			[vehicle, powertrain, inverter, motor],
+		);
+		let built = build_index(&ctx);
+		let page = include_str!("./index-nested.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		);
+		let built = build_components(&ctx);
+		let page = include_str!("./components-nested.html");
+		assert_eq!(
+			page,
+			built.as_ref(),
+			"\nexpected:\n{}\nbuilt:\n{}\n",
+			page,
+			built.as_ref()
+		);
+		let built = build_a_component(&ctx, &ctx.1[1]);
+		let page = include_str!("./components/PWR.html");
 		assert_eq!(
 			page,
 			built.as_ref(),
@@ -4,7 +4,7 @@ use std::path::PathBuf;
 use argh::FromArgs;
 use log::{LevelFilter, error, info};
 use reqtsv_html_compiler::{BuildDocsErr, ProjectCtx, UserInputs, compile_html, hashed_table};
-use reqtsv_lib::{Project, get_project_root};
+use reqtsv_lib::{Project, filter::FilterErr, get_project_root};
 use thiserror::Error;
 
 fn main() -> Result<(), Error> {
@@ -38,10 +38,29 @@ fn main() -> Result<(), Error> {
 		return Ok(());
 	}
 
-	info!("Building requirements docs at: {:?}", &reqtsv.output);
-	let project = get_project_root(reqtsv.project.as_path()).map_err(Error::ProjectRoot)?;
+	let mut project = get_project_root(reqtsv.project.as_path()).map_err(Error::ProjectRoot)?;
+	if reqtsv.exclude_deleted || project.config.exclude_deleted {
+		project.exclude_deleted();
+	}
+	if let Some(filter) = &reqtsv.filter {
+		let filter = filter.parse().map_err(Error::Filter)?;
+		project
+			.filter_requirements(&filter)
+			.map_err(Error::Filter)?;
+	}
+	// flags win over the project config
+	let inputs = UserInputs {
+		out_dir: reqtsv
+			.output
+			.unwrap_or_else(|| project.config.output_dir(&project.root)),
+		css_path: reqtsv
+			.css
+			.unwrap_or_else(|| project.config.css_path(&project.root))
+			.into_boxed_path(),
+	};
+	info!("Building requirements docs at: {:?}", &inputs.out_dir);
 	let ctx = CtxImpl::from(project);
-	compile_html(&ctx, reqtsv).map_err(Error::BuildDocs)
+	compile_html(&ctx, inputs).map_err(Error::BuildDocs)
 }
 
 struct CtxImpl {
@@ -65,6 +84,10 @@ impl ProjectCtx for CtxImpl {
 		&self.project.project_title
 	}
 
+	fn get_project_description(&self) -> &str {
+		&self.project.config.description
+	}
+
 	fn get_requirement_tbl_hash(&self) -> &str {
 		&self.requirement_tlb_hash
 	}
@@ -80,6 +103,26 @@ impl ProjectCtx for CtxImpl {
 	fn get_requirements(&self) -> &[reqtsv_lib::Requirement] {
 		&self.project.requirements
 	}
+
+	fn get_links(&self) -> &[reqtsv_lib::Link] {
+		&self.project.links
+	}
+
+	fn get_fields(&self) -> &[reqtsv_lib::custom_field::FieldDef] {
+		&self.project.config.fields
+	}
+
+	fn get_history(&self) -> &[reqtsv_lib::RequirementRevision] {
+		&self.project.history
+	}
+
+	fn get_test_cases(&self) -> &[reqtsv_lib::TestCase] {
+		&self.project.test_cases
+	}
+
+	fn get_test_results(&self) -> &[reqtsv_lib::TestResult] {
+		&self.project.test_results
+	}
 }
 
 #[derive(Error, Debug)]
@@ -88,6 +131,8 @@ enum Error {
 	BuildDocs(BuildDocsErr),
 	#[error("Failed to open project: {0:}")]
 	ProjectRoot(reqtsv_lib::GetProjectRootErr),
+	#[error("Failed to filter requirements: {0:}")]
+	Filter(FilterErr),
 }
 
 #[derive(FromArgs, Debug, PartialEq)]
@@ -100,21 +145,19 @@ struct ReqtsvHtml {
 	/// directory containing requirements project
 	project: PathBuf,
 	#[argh(option, short = 'o')]
-	/// root directory of the html output (where index.html will go)
-	output: PathBuf,
+	/// root directory of the html output (where index.html will go), defaults to the project config
+	output: Option<PathBuf>,
 	#[argh(option, short = 'c')]
-	/// css to use
-	css: PathBuf,
+	/// css to use, defaults to the project config
+	css: Option<PathBuf>,
+	#[argh(switch)]
+	/// leave deleted records out of the HTML, defaults to the project config
+	exclude_deleted: bool,
+	#[argh(option)]
+	/// only put the requirements matching a filter expression in the HTML e.g.
+	/// "priority = High and status = Accepted", see reqtsv_lib::filter
+	filter: Option<String>,
 	#[argh(switch, short = 'v')]
 	/// verbose logging
 	verbose: bool,
 }
-
-impl From<ReqtsvHtml> for UserInputs {
-	fn from(val: ReqtsvHtml) -> Self {
-		UserInputs {
-			out_dir: val.output,
-			css_path: val.css.into_boxed_path(),
-		}
-	}
-}
@@ -30,6 +30,13 @@ impl OutDir {
 		self.0.pop();
 		is_file
 	}
+
+	pub fn tags_dir_is_file(&mut self) -> bool {
+		self.0.push("tags");
+		let is_file = self.0.is_file();
+		self.0.pop();
+		is_file
+	}
 }
 
 impl AsRef<Path> for OutDir {
@@ -114,4 +121,6 @@ build_dir_file!(OutDirIndex, "index.html");
 build_dir_file!(OutDirComponents, "components.html");
 build_dir_file!(OutDirAllRequirements, "all-requirements.html");
 build_dir_file!(OutDirCSS, "styles.css");
+build_dir_file!(OutDirTags, "tags.html");
 build_dir_sub!(OutDirComponentsDir, "components");
+build_dir_sub!(OutDirTagsDir, "tags");
@@ -0,0 +1,651 @@
+//! Validates a project without stopping at the first problem, for CI.
+//!
+//! Unlike [`crate::get_project_root`] every row is read on its own so one corrupt row doesn't
+//! hide the rest. Problems are [`Severity::Error`] when reqtsv can't load or would misread the
+//! project, and [`Severity::Warning`] when it loads but something is likely a mistake.
+//!
+//! | kind | severity | |
+//! |---|---|---|
+//! | `config` | error | the project config can't be read, nothing else is checked |
+//! | `schema` | error | the schema version isn't the one this reqtsv reads |
+//! | `missing_table` | error | the component or requirement table isn't there |
+//! | `header` | error | the columns don't match the schema |
+//! | `corrupt_row` | error | the wrong number of fields or a value that doesn't parse |
+//! | `illegal_character` | error | a tab or raw new-line in a field |
+//! | `duplicate_id` | error | two rows with one ID |
+//! | `missing_component` | error | a requirement's `component_id` isn't in the component table |
+//! | `deleted_component` | warning | a live requirement in a deleted component |
+//! | `duplicate_title` | warning | two live requirements with the same title, ignoring case |
+//! | `duplicate_text` | warning | two live requirements with the same requirement text |
+//! | `orphaned_draft` | warning | a draft file, inserted already or never |
+//! | `orphaned_edit` | warning | an edit file for a missing or deleted record |
+
+use std::{
+	collections::HashMap,
+	fs,
+	path::{Path, PathBuf},
+};
+
+use csv::StringRecord;
+use serde::{Serialize, de::DeserializeOwned};
+use strum_macros::Display;
+
+use crate::{
+	COLUMN_DELIMITER, COMPONENT_DRAFT_PREFIX, COMPONENT_EDIT_PREFIX, Component, Link,
+	RECORD_DELIMITER, REQUIREMENT_DRAFT_PREFIX, REQUIREMENT_EDIT_PREFIX, RecordStatus, Requirement,
+	RequirementRevision, TEST_CASE_DRAFT_PREFIX, TEST_CASE_EDIT_PREFIX, TestCase, TestResult,
+	config::load_project_config,
+	schema::{Table, check_header, check_schema_version},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Severity {
+	Warning,
+	Error,
+}
+
+/// See the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum IssueKind {
+	Config,
+	Schema,
+	MissingTable,
+	Header,
+	CorruptRow,
+	IllegalCharacter,
+	DuplicateId,
+	MissingComponent,
+	DeletedComponent,
+	DuplicateTitle,
+	DuplicateText,
+	OrphanedDraft,
+	OrphanedEdit,
+}
+
+impl IssueKind {
+	pub fn severity(self) -> Severity {
+		match self {
+			IssueKind::DeletedComponent
+			| IssueKind::DuplicateTitle
+			| IssueKind::DuplicateText
+			| IssueKind::OrphanedDraft
+			| IssueKind::OrphanedEdit => Severity::Warning,
+			_ => Severity::Error,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Issue {
+	pub severity: Severity,
+	pub kind: IssueKind,
+	/// Relative to the project root.
+	pub file: String,
+	/// 1 is the header of a table, `None` for the whole file.
+	pub line: Option<u64>,
+	pub message: String,
+}
+
+impl std::fmt::Display for Issue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.line {
+			Some(line) => write!(f, "{}:{line}: ", self.file)?,
+			None => write!(f, "{}: ", self.file)?,
+		}
+		write!(f, "{}: {} [{}]", self.severity, self.message, self.kind)
+	}
+}
+
+/// What [`check_project`] found, in the order it was found, table by table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CheckReport {
+	pub errors: usize,
+	pub warnings: usize,
+	pub issues: Vec<Issue>,
+}
+
+impl CheckReport {
+	/// `true` when there are errors, or warnings too with `warnings_as_errors`.
+	pub fn failed(&self, warnings_as_errors: bool) -> bool {
+		self.errors > 0 || (warnings_as_errors && self.warnings > 0)
+	}
+
+	fn push(&mut self, kind: IssueKind, file: &str, line: Option<u64>, message: String) {
+		let severity = kind.severity();
+		match severity {
+			Severity::Error => self.errors += 1,
+			Severity::Warning => self.warnings += 1,
+		}
+		self.issues.push(Issue {
+			severity,
+			kind,
+			file: file.to_string(),
+			line,
+			message,
+		});
+	}
+}
+
+/// Rows that parsed and the line each starts on.
+type Rows<R> = Vec<(u64, R)>;
+
+pub fn check_project(project_root: &Path) -> CheckReport {
+	let mut report = CheckReport::default();
+	let config = match load_project_config(project_root) {
+		Ok(config) => config,
+		Err(e) => {
+			report.push(
+				IssueKind::Config,
+				crate::config::PROJECT_CONFIG_NAME,
+				None,
+				e.to_string(),
+			);
+			return report;
+		}
+	};
+	if let Err(e) = check_schema_version(&config) {
+		report.push(
+			IssueKind::Schema,
+			crate::config::PROJECT_CONFIG_NAME,
+			None,
+			e.to_string(),
+		);
+	}
+
+	let tables = &config.tables;
+	let components: Rows<Component> =
+		check_table(&mut report, project_root, Table::Component, tables);
+	let requirements: Rows<Requirement> =
+		check_table(&mut report, project_root, Table::Requirement, tables);
+	let links: Rows<Link> = check_table(&mut report, project_root, Table::Link, tables);
+	let history: Rows<RequirementRevision> =
+		check_table(&mut report, project_root, Table::History, tables);
+	let test_cases: Rows<TestCase> =
+		check_table(&mut report, project_root, Table::TestCase, tables);
+	let test_results: Rows<TestResult> =
+		check_table(&mut report, project_root, Table::TestResult, tables);
+
+	check_duplicates(&mut report, &tables.component, &components, |c| c.id);
+	check_duplicates(&mut report, &tables.requirement, &requirements, |r| r.id);
+	check_duplicates(&mut report, &tables.link, &links, |l| l.id);
+	check_duplicates(&mut report, &tables.history, &history, |rev| {
+		format!("{} version {}", rev.requirement_id, rev.version)
+	});
+	check_duplicates(&mut report, &tables.test_case, &test_cases, |tc| tc.id);
+	check_duplicates(&mut report, &tables.test_result, &test_results, |tr| tr.id);
+	check_components(&mut report, &tables.requirement, &components, &requirements);
+	check_duplicate_text(&mut report, &tables.requirement, &requirements);
+
+	let components: Vec<Component> = components.into_iter().map(|(_, c)| c).collect();
+	let requirements: Vec<Requirement> = requirements.into_iter().map(|(_, r)| r).collect();
+	let test_cases: Vec<TestCase> = test_cases.into_iter().map(|(_, tc)| tc).collect();
+	let records = [
+		(
+			COMPONENT_DRAFT_PREFIX,
+			COMPONENT_EDIT_PREFIX,
+			"name",
+			components
+				.iter()
+				.map(|c| (c.id, c.key(), c.name.as_str(), c.status))
+				.collect::<Vec<_>>(),
+		),
+		(
+			REQUIREMENT_DRAFT_PREFIX,
+			REQUIREMENT_EDIT_PREFIX,
+			"title",
+			requirements
+				.iter()
+				.map(|r| (r.id, r.key(), r.title.as_str(), r.status))
+				.collect(),
+		),
+		(
+			TEST_CASE_DRAFT_PREFIX,
+			TEST_CASE_EDIT_PREFIX,
+			"title",
+			test_cases
+				.iter()
+				.map(|tc| (tc.id, tc.key().into_owned(), tc.title.as_str(), tc.status))
+				.collect(),
+		),
+	];
+	for path in project_files(project_root) {
+		let Some(file) = path
+			.strip_prefix(project_root)
+			.ok()
+			.and_then(Path::to_str)
+			.map(str::to_string)
+		else {
+			continue;
+		};
+		let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+			continue;
+		};
+		let Some(stem) = name.strip_suffix(".toml") else {
+			continue;
+		};
+		for (draft_prefix, edit_prefix, heading, records) in records.iter() {
+			if stem.starts_with(draft_prefix) {
+				check_draft(&mut report, &path, &file, heading, records);
+			} else if let Some(id_or_key) = stem.strip_prefix(edit_prefix) {
+				let id_or_key = id_or_key.strip_prefix('-').unwrap_or(id_or_key);
+				check_edit(&mut report, &file, id_or_key, records);
+			}
+		}
+	}
+	report
+}
+
+/// Reads every row of `table` on its own, reporting the ones that don't parse.
+fn check_table<R: DeserializeOwned>(
+	report: &mut CheckReport,
+	project_root: &Path,
+	table: Table,
+	tables: &crate::config::TableNames,
+) -> Rows<R> {
+	let file = table.file_name(tables);
+	let raw = match fs::read_to_string(project_root.join(file)) {
+		Ok(raw) => raw,
+		// tables other than these are made when a session opens an older project
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+			if matches!(table, Table::Component | Table::Requirement) {
+				report.push(
+					IssueKind::MissingTable,
+					file,
+					None,
+					format!("{table} table is missing"),
+				);
+			}
+			return Vec::new();
+		}
+		Err(e) => {
+			report.push(
+				IssueKind::MissingTable,
+				file,
+				None,
+				format!("can't read table: {e}"),
+			);
+			return Vec::new();
+		}
+	};
+	check_rows(report, table, file, &raw)
+}
+
+fn check_rows<R: DeserializeOwned>(
+	report: &mut CheckReport,
+	table: Table,
+	file: &str,
+	raw: &str,
+) -> Rows<R> {
+	if let Err(e) = check_header(table, file, raw) {
+		report.push(IssueKind::Header, file, Some(1), e.to_string());
+	}
+	let mut reader = csv::ReaderBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(RECORD_DELIMITER))
+		.flexible(true)
+		.from_reader(raw.as_bytes());
+	let headers = match reader.headers() {
+		Ok(headers) => headers.clone(),
+		Err(e) => {
+			report.push(IssueKind::Header, file, Some(1), e.to_string());
+			return Vec::new();
+		}
+	};
+	let mut rows = Vec::new();
+	for res in reader.records() {
+		let row = match res {
+			Ok(row) => row,
+			Err(e) => {
+				let line = e.position().map(|p| p.line());
+				report.push(IssueKind::CorruptRow, file, line, e.to_string());
+				continue;
+			}
+		};
+		let line = row.position().map(|p| p.line());
+		if row.len() != headers.len() {
+			report.push(
+				IssueKind::CorruptRow,
+				file,
+				line,
+				format!(
+					"row has {} fields but the header has {}, a field may hold a raw new-line",
+					row.len(),
+					headers.len()
+				),
+			);
+			continue;
+		}
+		if !check_characters(report, file, line, &headers, &row) {
+			continue;
+		}
+		match row.deserialize::<R>(Some(&headers)) {
+			Ok(record) => rows.push((line.unwrap_or_default(), record)),
+			Err(e) => report.push(IssueKind::CorruptRow, file, line, e.to_string()),
+		}
+	}
+	rows
+}
+
+/// `false` when a field of `row` has a tab or raw new-line, which only get in quoted.
+fn check_characters(
+	report: &mut CheckReport,
+	file: &str,
+	line: Option<u64>,
+	headers: &StringRecord,
+	row: &StringRecord,
+) -> bool {
+	let mut clean = true;
+	for (column, field) in headers.iter().zip(row.iter()) {
+		let found = match field {
+			f if f.contains('\t') => "a tab",
+			f if f.contains(['\n', '\r']) => "a raw new-line",
+			_ => continue,
+		};
+		report.push(
+			IssueKind::IllegalCharacter,
+			file,
+			line,
+			format!("field: {column} has {found}"),
+		);
+		clean = false;
+	}
+	clean
+}
+
+fn check_duplicates<R, K: Eq + std::hash::Hash + std::fmt::Display>(
+	report: &mut CheckReport,
+	file: &str,
+	rows: &Rows<R>,
+	id: impl Fn(&R) -> K,
+) {
+	let mut seen: HashMap<K, u64> = HashMap::with_capacity(rows.len());
+	for (line, record) in rows.iter() {
+		let id = id(record);
+		match seen.get(&id) {
+			Some(first) => report.push(
+				IssueKind::DuplicateId,
+				file,
+				Some(*line),
+				format!("duplicate ID: {id}, first used on line {first}"),
+			),
+			None => {
+				seen.insert(id, *line);
+			}
+		}
+	}
+}
+
+fn check_components(
+	report: &mut CheckReport,
+	file: &str,
+	components: &Rows<Component>,
+	requirements: &Rows<Requirement>,
+) {
+	for (line, requirement) in requirements.iter() {
+		match components
+			.iter()
+			.find(|(_, c)| c.id == requirement.component_id)
+		{
+			None => report.push(
+				IssueKind::MissingComponent,
+				file,
+				Some(*line),
+				format!(
+					"requirement: {} is in component ID: {}, which doesn't exist",
+					requirement.key(),
+					requirement.component_id
+				),
+			),
+			Some((_, component))
+				if component.status == RecordStatus::Deleted
+					&& requirement.status != RecordStatus::Deleted =>
+			{
+				report.push(
+					IssueKind::DeletedComponent,
+					file,
+					Some(*line),
+					format!(
+						"requirement: {} is in deleted component: {}",
+						requirement.key(),
+						component.key()
+					),
+				)
+			}
+			Some(_) => {}
+		}
+	}
+}
+
+/// Titles ignoring case, and requirement text, shared by live requirements.
+fn check_duplicate_text(report: &mut CheckReport, file: &str, requirements: &Rows<Requirement>) {
+	let mut titles: HashMap<String, (u64, String)> = HashMap::new();
+	let mut texts: HashMap<String, (u64, String)> = HashMap::new();
+	for (line, requirement) in requirements
+		.iter()
+		.filter(|(_, r)| r.status != RecordStatus::Deleted)
+	{
+		let title = requirement.title.trim().to_lowercase();
+		let text = requirement.requirement_text.trim().to_string();
+		for (kind, seen, value, what) in [
+			(IssueKind::DuplicateTitle, &mut titles, title, "title"),
+			(
+				IssueKind::DuplicateText,
+				&mut texts,
+				text,
+				"requirement text",
+			),
+		] {
+			if value.is_empty() {
+				continue;
+			}
+			match seen.get(&value) {
+				Some((first_line, first_key)) => report.push(
+					kind,
+					file,
+					Some(*line),
+					format!(
+						"requirement: {} has the same {what} as: {first_key} on line {first_line}",
+						requirement.key()
+					),
+				),
+				None => {
+					seen.insert(value, (*line, requirement.key()));
+				}
+			}
+		}
+	}
+}
+
+/// Files in the project root and the directories in it, where the menus look for drafts and
+/// edit files.
+fn project_files(project_root: &Path) -> Vec<PathBuf> {
+	let mut files = Vec::new();
+	let Ok(entries) = fs::read_dir(project_root) else {
+		return files;
+	};
+	for entry in entries.flatten() {
+		let path = entry.path();
+		match entry.file_type() {
+			Ok(kind) if kind.is_file() => files.push(path),
+			Ok(kind) if kind.is_dir() => {
+				if let Ok(entries) = fs::read_dir(&path) {
+					files.extend(
+						entries
+							.flatten()
+							.filter(|e| e.file_type().is_ok_and(|kind| kind.is_file()))
+							.map(|e| e.path()),
+					);
+				}
+			}
+			_ => {}
+		}
+	}
+	files.sort();
+	files
+}
+
+/// (ID, key, title or name, status) of the records a draft or edit file is for.
+type FileRecords<'a> = Vec<(u64, String, &'a str, RecordStatus)>;
+
+fn check_draft(
+	report: &mut CheckReport,
+	path: &Path,
+	file: &str,
+	heading: &str,
+	records: &FileRecords,
+) {
+	let draft_heading = fs::read_to_string(path)
+		.ok()
+		.and_then(|raw| raw.parse::<toml::Table>().ok())
+		.and_then(|draft| draft.get(heading)?.as_str().map(|s| s.trim().to_string()))
+		.filter(|s| !s.is_empty());
+	let inserted = draft_heading.and_then(|draft_heading| {
+		records.iter().find(|(.., record_heading, status)| {
+			*status != RecordStatus::Deleted
+				&& record_heading.trim().eq_ignore_ascii_case(&draft_heading)
+		})
+	});
+	let message = match inserted {
+		Some((_, key, ..)) => format!("draft was inserted as: {key}, it can be deleted"),
+		None => "draft was never inserted".to_string(),
+	};
+	report.push(IssueKind::OrphanedDraft, file, None, message);
+}
+
+fn check_edit(report: &mut CheckReport, file: &str, id_or_key: &str, records: &FileRecords) {
+	let record = records
+		.iter()
+		.find(|(_, key, ..)| key.eq_ignore_ascii_case(id_or_key))
+		.or_else(|| {
+			let id = id_or_key.parse::<u64>().ok()?;
+			records.iter().find(|(record_id, ..)| *record_id == id)
+		});
+	let message = match record {
+		None => format!("edit file is for: \"{id_or_key}\", which doesn't exist"),
+		Some((_, key, _, RecordStatus::Deleted)) => {
+			format!("edit file is for: {key}, which is deleted")
+		}
+		Some(_) => return,
+	};
+	report.push(IssueKind::OrphanedEdit, file, None, message);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{COMPONENT_HEADER, REQUIREMENT_HEADER};
+
+	fn requirement_row(
+		id: u64,
+		component_id: u64,
+		title: &str,
+		text: &str,
+		status: &str,
+	) -> String {
+		format!(
+			"{id}\tPWR-000{id}\t{component_id}\t\t{title}\tFunctional\t2025-01-01T12:00:00+00:00\t{text}\t\tTest\t0\tJM\tHigh\t{status}\t\t\t\n"
+		)
+	}
+
+	#[test]
+	fn test_check_rows() {
+		let raw = format!(
+			"{COMPONENT_HEADER}\
+			 0\tPWR\t1\t\tPowertrain\t\t2025-01-01T12:00:00+00:00\tAccepted\tJM\t\n\
+			 1\tBAD\t1\t\tBad date\t\tyesterday\tAccepted\tJM\t\n\
+			 2\tSHORT\t1\n\
+			 3\tQ\t1\t\t\"tab\there\"\t\t2025-01-01T12:00:00+00:00\tAccepted\tJM\t\n\
+			 0\tDUP\t1\t\tDuplicate\t\t2025-01-01T12:00:00+00:00\tDeleted\tJM\t\n"
+		);
+		let mut report = CheckReport::default();
+		let rows: Rows<Component> =
+			check_rows(&mut report, Table::Component, "component.tsv", &raw);
+		check_duplicates(&mut report, "component.tsv", &rows, |c| c.id);
+		let found: Vec<(IssueKind, Option<u64>)> =
+			report.issues.iter().map(|i| (i.kind, i.line)).collect();
+		assert_eq!(
+			vec![
+				(IssueKind::CorruptRow, Some(3)),
+				(IssueKind::CorruptRow, Some(4)),
+				(IssueKind::IllegalCharacter, Some(5)),
+				(IssueKind::DuplicateId, Some(6)),
+			],
+			found
+		);
+		assert_eq!(4, report.errors);
+		assert_eq!(
+			vec![2, 6],
+			rows.iter().map(|(line, _)| *line).collect::<Vec<_>>()
+		);
+		assert_eq!(
+			"component.tsv:6: error: duplicate ID: 0, first used on line 2 [duplicate_id]",
+			report.issues[3].to_string()
+		);
+	}
+
+	#[test]
+	fn test_check_project() {
+		let dir = std::env::temp_dir().join(format!("reqtsv-check-test-{}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		crate::config::init_project_config(&dir).unwrap();
+		fs::write(
+			dir.join("component.tsv"),
+			format!(
+				"{COMPONENT_HEADER}\
+				 0\tPWR\t1\t\tPowertrain\t\t2025-01-01T12:00:00+00:00\tAccepted\tJM\t\n\
+				 1\tOLD\t1\t\tOld\t\t2025-01-01T12:00:00+00:00\tDeleted\tJM\t\n"
+			),
+		)
+		.unwrap();
+		fs::write(
+			dir.join("requirement.tsv"),
+			format!(
+				"{REQUIREMENT_HEADER}{}{}{}{}",
+				requirement_row(0, 0, "Brake latency", "Stop fast", "Accepted"),
+				requirement_row(1, 1, "Seats", "Heat", "Proposed"),
+				requirement_row(2, 7, "brake latency", "Stop fast", "Proposed"),
+				requirement_row(3, 7, "Gone", "Gone", "Deleted"),
+			),
+		)
+		.unwrap();
+		fs::write(
+			dir.join("requirement_draft-abc.toml"),
+			"title = \"Brake latency\"\n",
+		)
+		.unwrap();
+		fs::write(dir.join("requirement_edit-PWR-0009.toml"), "").unwrap();
+		fs::write(dir.join("requirement_edit-PWR-0000.toml"), "").unwrap();
+
+		let report = check_project(&dir);
+		let _ = fs::remove_dir_all(&dir);
+		let found: Vec<(IssueKind, &str, Option<u64>)> = report
+			.issues
+			.iter()
+			.map(|i| (i.kind, i.file.as_str(), i.line))
+			.collect();
+		assert_eq!(
+			vec![
+				(IssueKind::DeletedComponent, "requirement.tsv", Some(3)),
+				(IssueKind::MissingComponent, "requirement.tsv", Some(4)),
+				(IssueKind::MissingComponent, "requirement.tsv", Some(5)),
+				(IssueKind::DuplicateTitle, "requirement.tsv", Some(4)),
+				(IssueKind::DuplicateText, "requirement.tsv", Some(4)),
+				(IssueKind::OrphanedDraft, "requirement_draft-abc.toml", None),
+				(
+					IssueKind::OrphanedEdit,
+					"requirement_edit-PWR-0009.toml",
+					None
+				),
+			],
+			found
+		);
+		assert_eq!(2, report.errors);
+		assert!(report.failed(false));
+		assert!(report.issues[5].message.contains("inserted as: PWR-0000"));
+	}
+}
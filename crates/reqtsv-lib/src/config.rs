@@ -0,0 +1,332 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::custom_field::{FieldDef, FieldDefErr, validate_field_defs};
+use crate::{
+	COMPONENT_TABLE_NAME, HISTORY_TABLE_NAME, LINK_TABLE_NAME, MAX_KEY_PREFIX_LEN,
+	REQUIREMENT_TABLE_NAME, TEST_CASE_KEY_PREFIX, TEST_CASE_TABLE_NAME, TEST_RESULT_TABLE_NAME,
+	is_valid_key_prefix,
+	schema::{LEGACY_SCHEMA_VERSION, SCHEMA_VERSION},
+};
+
+/// Project settings in the project root, made by `init_project`.
+pub const PROJECT_CONFIG_NAME: &str = "reqtsv.toml";
+pub const DEFAULT_TITLE: &str = "Requirements";
+pub const DEFAULT_CSS_PATH: &str = "styles.css";
+/// Written to [`DEFAULT_CSS_PATH`] by `init_project` so a new project builds as is.
+pub const DEFAULT_CSS: &str = include_str!("./styles.css");
+pub const DEFAULT_OUTPUT_DIR: &str = "docs";
+
+/// Every key is optional, a project without [`PROJECT_CONFIG_NAME`] gets [`ProjectConfig::default`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProjectConfig {
+	/// Layout of the tables, see [`crate::schema`].
+	pub schema_version: u32,
+	pub title: String,
+	pub description: String,
+	/// Pre-filled as the author of new drafts.
+	pub default_author: String,
+	/// Style sheet copied in to the HTML output, relative to the project root.
+	pub css: PathBuf,
+	/// Where the HTML is built, relative to the project root.
+	pub output_dir: PathBuf,
+	/// Leave deleted records out of the HTML, archived records are never built.
+	pub exclude_deleted: bool,
+	/// Commit the component and requirement tables to git after each change, made when the
+	/// session saves.
+	pub git_auto_commit: bool,
+	pub id_prefixes: IdPrefixes,
+	pub tables: TableNames,
+	/// Extra requirement fields, see [`crate::custom_field`].
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub fields: Vec<FieldDef>,
+}
+
+impl Default for ProjectConfig {
+	fn default() -> Self {
+		Self {
+			schema_version: LEGACY_SCHEMA_VERSION,
+			title: DEFAULT_TITLE.into(),
+			description: String::new(),
+			default_author: String::new(),
+			css: PathBuf::from(DEFAULT_CSS_PATH),
+			output_dir: PathBuf::from(DEFAULT_OUTPUT_DIR),
+			exclude_deleted: false,
+			git_auto_commit: false,
+			id_prefixes: IdPrefixes::default(),
+			tables: TableNames::default(),
+			fields: Vec::new(),
+		}
+	}
+}
+
+impl ProjectConfig {
+	pub fn css_path(&self, project_root: &Path) -> PathBuf {
+		project_root.join(&self.css)
+	}
+
+	pub fn output_dir(&self, project_root: &Path) -> PathBuf {
+		project_root.join(&self.output_dir)
+	}
+}
+
+/// Key prefixes for records that don't get one from their component.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct IdPrefixes {
+	pub test_case: String,
+}
+
+impl Default for IdPrefixes {
+	fn default() -> Self {
+		Self {
+			test_case: TEST_CASE_KEY_PREFIX.into(),
+		}
+	}
+}
+
+/// File names of the tables in the project root, each must end in ".tsv".
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TableNames {
+	pub component: String,
+	pub requirement: String,
+	pub link: String,
+	pub history: String,
+	pub test_case: String,
+	pub test_result: String,
+}
+
+impl Default for TableNames {
+	fn default() -> Self {
+		Self {
+			component: COMPONENT_TABLE_NAME.into(),
+			requirement: REQUIREMENT_TABLE_NAME.into(),
+			link: LINK_TABLE_NAME.into(),
+			history: HISTORY_TABLE_NAME.into(),
+			test_case: TEST_CASE_TABLE_NAME.into(),
+			test_result: TEST_RESULT_TABLE_NAME.into(),
+		}
+	}
+}
+
+impl TableNames {
+	pub fn iter(&self) -> impl Iterator<Item = &str> {
+		[
+			&self.component,
+			&self.requirement,
+			&self.link,
+			&self.history,
+			&self.test_case,
+			&self.test_result,
+		]
+		.into_iter()
+		.map(String::as_str)
+	}
+}
+
+/// Where a table is written before it replaces `table_name` e.g. "link.new.tsv".
+pub fn new_table_name(table_name: &str) -> String {
+	format!(
+		"{}.new.tsv",
+		table_name.strip_suffix(".tsv").unwrap_or(table_name)
+	)
+}
+
+/// Where `table_name` is moved while a new table is swapped in e.g. "link.old.tsv".
+pub fn old_table_name(table_name: &str) -> String {
+	format!(
+		"{}.old.tsv",
+		table_name.strip_suffix(".tsv").unwrap_or(table_name)
+	)
+}
+
+/// Where the new version of `table_name` is kept when a save is rolled back e.g.
+/// "link.unsaved-20250527T120000.tsv", out of the way of the next save and of recovery.
+pub fn unsaved_table_name(table_name: &str, rolled_back_at: DateTime<Local>) -> String {
+	format!(
+		"{}.unsaved-{}.tsv",
+		table_name.strip_suffix(".tsv").unwrap_or(table_name),
+		rolled_back_at.format("%Y%m%dT%H%M%S")
+	)
+}
+
+/// Where purged records of `table_name` are kept e.g. "link.archive.tsv".
+pub fn archive_table_name(table_name: &str) -> String {
+	format!(
+		"{}.archive.tsv",
+		table_name.strip_suffix(".tsv").unwrap_or(table_name)
+	)
+}
+
+#[derive(Error, Debug)]
+pub enum ProjectConfigErr {
+	#[error("can't read project config: {0}")]
+	Read(#[source] std::io::Error),
+	#[error("can't write project config: {0}")]
+	Write(#[source] std::io::Error),
+	#[error("invalid project config: {0}")]
+	Parse(#[source] toml::de::Error),
+	#[error("invalid project config: {0}")]
+	Field(#[source] FieldDefErr),
+	#[error("invalid project config: table name {0:?} must be a file name ending in \".tsv\"")]
+	TableName(String),
+	#[error("invalid project config: table name {0:?} is used more than once")]
+	DuplicateTableName(String),
+	#[error(
+		"invalid project config: ID prefix {0:?} must be 1 to {MAX_KEY_PREFIX_LEN} upper case letters or digits starting with a letter"
+	)]
+	IdPrefix(String),
+}
+
+/// Loads [`PROJECT_CONFIG_NAME`] from `project_root`, or the default config if there is none.
+pub fn load_project_config(project_root: &Path) -> Result<ProjectConfig, ProjectConfigErr> {
+	let path = project_root.join(PROJECT_CONFIG_NAME);
+	if !path.exists() {
+		return Ok(ProjectConfig::default());
+	}
+	let raw = std::fs::read_to_string(&path).map_err(ProjectConfigErr::Read)?;
+	let config: ProjectConfig = toml::from_str(&raw).map_err(ProjectConfigErr::Parse)?;
+	validate_project_config(&config)?;
+	Ok(config)
+}
+
+/// Loads the project config if there is one, otherwise writes the default, titled after the
+/// project directory.
+pub fn init_project_config(project_root: &Path) -> Result<ProjectConfig, ProjectConfigErr> {
+	let path = project_root.join(PROJECT_CONFIG_NAME);
+	if path.exists() {
+		return load_project_config(project_root);
+	}
+	let mut config = ProjectConfig {
+		schema_version: SCHEMA_VERSION,
+		..ProjectConfig::default()
+	};
+	if let Some(name) = project_root.file_name().and_then(|n| n.to_str()) {
+		config.title = name.to_string();
+	}
+	let raw = toml::to_string_pretty(&config).expect("the config is always valid TOML");
+	std::fs::write(&path, raw).map_err(ProjectConfigErr::Write)?;
+	Ok(config)
+}
+
+/// Sets `schema_version` in the project config, leaving the rest of the file as it is.
+pub fn set_schema_version(project_root: &Path, version: u32) -> Result<(), ProjectConfigErr> {
+	let path = project_root.join(PROJECT_CONFIG_NAME);
+	if !path.exists() {
+		init_project_config(project_root)?;
+	}
+	let raw = std::fs::read_to_string(&path).map_err(ProjectConfigErr::Read)?;
+	let line = format!("schema_version = {version}");
+	// top level keys come before the first table
+	let top_level = raw
+		.lines()
+		.take_while(|l| !l.trim_start().starts_with('['))
+		.count();
+	let mut lines: Vec<&str> = raw.lines().collect();
+	match lines[..top_level]
+		.iter()
+		.position(|l| l.trim_start().starts_with("schema_version"))
+	{
+		Some(idx) => lines[idx] = &line,
+		None => lines.insert(0, &line),
+	}
+	let mut raw = lines.join("\n");
+	raw.push('\n');
+	std::fs::write(&path, raw).map_err(ProjectConfigErr::Write)
+}
+
+fn validate_project_config(config: &ProjectConfig) -> Result<(), ProjectConfigErr> {
+	validate_field_defs(&config.fields).map_err(ProjectConfigErr::Field)?;
+	if !is_valid_key_prefix(&config.id_prefixes.test_case) {
+		return Err(ProjectConfigErr::IdPrefix(
+			config.id_prefixes.test_case.clone(),
+		));
+	}
+	let mut seen: Vec<&str> = Vec::new();
+	for name in config.tables.iter() {
+		let stem = name.strip_suffix(".tsv").unwrap_or_default();
+		if stem.is_empty() || name.contains(['/', '\\', '\t', '\n']) {
+			return Err(ProjectConfigErr::TableName(name.to_string()));
+		}
+		if seen.contains(&name) {
+			return Err(ProjectConfigErr::DuplicateTableName(name.to_string()));
+		}
+		seen.push(name);
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_project_config() {
+		let config: ProjectConfig = toml::from_str(
+			r#"
+title = "Rover"
+default_author = "JM"
+
+[tables]
+requirement = "reqs.tsv"
+"#,
+		)
+		.unwrap();
+		assert_eq!("Rover", config.title);
+		assert_eq!(LEGACY_SCHEMA_VERSION, config.schema_version);
+		assert_eq!("JM", config.default_author);
+		assert_eq!("reqs.tsv", config.tables.requirement);
+		assert_eq!(COMPONENT_TABLE_NAME, config.tables.component);
+		assert_eq!(PathBuf::from(DEFAULT_OUTPUT_DIR), config.output_dir);
+		assert!(validate_project_config(&config).is_ok());
+		assert_eq!("reqs.new.tsv", new_table_name(&config.tables.requirement));
+		assert_eq!("reqs.old.tsv", old_table_name(&config.tables.requirement));
+		assert_eq!(
+			"reqs.archive.tsv",
+			archive_table_name(&config.tables.requirement)
+		);
+		assert!(!config.exclude_deleted);
+		assert!(!config.git_auto_commit);
+
+		let mut bad = config.clone();
+		bad.tables.link = "reqs.tsv".into();
+		assert!(validate_project_config(&bad).is_err());
+		let mut bad = config.clone();
+		bad.tables.link = "links".into();
+		assert!(validate_project_config(&bad).is_err());
+		let mut bad = config;
+		bad.id_prefixes.test_case = "tc".into();
+		assert!(validate_project_config(&bad).is_err());
+
+		let root = std::env::temp_dir().join(format!("reqtsv-config-test-{}", std::process::id()));
+		std::fs::create_dir_all(&root).unwrap();
+		std::fs::write(
+			root.join(PROJECT_CONFIG_NAME),
+			"# kept\ntitle = \"Rover\"\n\n[tables]\nlink = \"links.tsv\"\n",
+		)
+		.unwrap();
+		set_schema_version(&root, SCHEMA_VERSION).unwrap();
+		set_schema_version(&root, SCHEMA_VERSION).unwrap();
+		let raw = std::fs::read_to_string(root.join(PROJECT_CONFIG_NAME)).unwrap();
+		assert_eq!(
+			format!(
+				"schema_version = {SCHEMA_VERSION}\n# kept\ntitle = \"Rover\"\n\n[tables]\nlink = \"links.tsv\"\n"
+			),
+			raw
+		);
+		assert_eq!(
+			SCHEMA_VERSION,
+			load_project_config(&root).unwrap().schema_version
+		);
+		std::fs::remove_dir_all(&root).unwrap();
+
+		let round_trip: ProjectConfig =
+			toml::from_str(&toml::to_string_pretty(&ProjectConfig::default()).unwrap()).unwrap();
+		assert_eq!(ProjectConfig::default(), round_trip);
+	}
+}
@@ -0,0 +1,398 @@
+//! Requirement fields declared by a project in [`crate::config::PROJECT_CONFIG_NAME`].
+//! They are stored as extra columns after the built in ones in the requirement table.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{COLUMN_DELIMITER, RECORD_DELIMITER};
+
+/// Columns written for [`crate::Requirement`]. Custom fields can't reuse these names.
+pub const REQUIREMENT_COLUMNS: &[&str] = &[
+	"id",
+	"key",
+	"component_id",
+	"parent_id",
+	"title",
+	"functional",
+	"creation_date",
+	"requirement_text",
+	"acceptance_criteria",
+	"verification_method",
+	"version",
+	"author",
+	"priority",
+	"status",
+	"status_justification",
+	"risks",
+	"tags",
+];
+
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FieldKind {
+	/// One line of text.
+	String,
+	/// Text that may span several lines, stored with escaped new-lines.
+	Text,
+	/// One of `values`.
+	Enum {
+		values: Vec<String>,
+	},
+	Integer,
+	/// A calendar date written as [`DATE_FORMAT`].
+	Date,
+}
+
+impl std::fmt::Display for FieldKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			FieldKind::String => write!(f, "one line of text"),
+			FieldKind::Text => write!(f, "text"),
+			FieldKind::Enum { values } => {
+				write!(f, "one of: ")?;
+				let mut values = values.iter().peekable();
+				while let Some(value) = values.next() {
+					write!(f, "\"{value}\"")?;
+					if values.peek().is_some() {
+						write!(f, ", ")?;
+					}
+				}
+				Ok(())
+			}
+			FieldKind::Integer => write!(f, "a whole number"),
+			FieldKind::Date => write!(f, "a date e.g. \"2025-06-16\""),
+		}
+	}
+}
+
+/// One `[[fields]]` entry in the project config, e.g.
+/// `{ name = "safety_class", kind = "enum", values = ["A", "B"], required = true }`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FieldDef {
+	/// Column and draft file key.
+	pub name: String,
+	/// Shown in place of `name` in the HTML output.
+	#[serde(default)]
+	pub label: Option<String>,
+	#[serde(flatten)]
+	pub kind: FieldKind,
+	#[serde(default)]
+	pub required: bool,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum FieldDefErr {
+	#[error("custom field name: {0:?} must be lower case ASCII letters, digits, or '_'")]
+	BadName(String),
+	#[error("custom field name: {0:?} is a built in requirement field")]
+	Reserved(String),
+	#[error("custom field name: {0:?} is declared more than once")]
+	Duplicate(String),
+	#[error("enum field: {0:?} has no values")]
+	NoValues(String),
+	#[error("enum field: {field:?} has an empty value or one with tabs or new-lines: {value:?}")]
+	BadValue { field: String, value: String },
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum FieldValueErr {
+	#[error("{field} is required")]
+	Missing { field: String },
+	#[error("{field} expects {expected}, found: {found}")]
+	WrongType {
+		field: String,
+		expected: String,
+		found: String,
+	},
+	#[error("{field} contains one or more tab or new line characters")]
+	BadChar { field: String },
+}
+
+impl FieldDef {
+	pub fn label(&self) -> &str {
+		self.label.as_deref().unwrap_or(&self.name)
+	}
+
+	/// Checks a value from a draft or edit file and returns it as it's stored in the table.
+	/// An empty string means the field is unset.
+	pub fn normalize(&self, value: Option<&toml::Value>) -> Result<String, FieldValueErr> {
+		let wrong_type = |found: &toml::Value| FieldValueErr::WrongType {
+			field: self.name.clone(),
+			expected: self.kind.to_string(),
+			found: found.to_string(),
+		};
+		let stored = match (value, &self.kind) {
+			(None, _) => String::new(),
+			(Some(toml::Value::String(s)), _) if s.trim().is_empty() => String::new(),
+			(Some(toml::Value::String(s)), FieldKind::String) => {
+				if s.contains(['\t', '\n', '\r']) {
+					return Err(FieldValueErr::BadChar {
+						field: self.name.clone(),
+					});
+				}
+				s.clone()
+			}
+			(Some(toml::Value::String(s)), FieldKind::Text) => {
+				if s.contains('\t') {
+					return Err(FieldValueErr::BadChar {
+						field: self.name.clone(),
+					});
+				}
+				s.replace("\r\n", "\\n").replace('\n', "\\n")
+			}
+			(Some(v @ toml::Value::String(s)), FieldKind::Enum { values }) => {
+				if !values.contains(s) {
+					return Err(wrong_type(v));
+				}
+				s.clone()
+			}
+			(Some(toml::Value::Integer(i)), FieldKind::Integer) => i.to_string(),
+			(Some(v @ toml::Value::String(s)), FieldKind::Integer) => s
+				.trim()
+				.parse::<i64>()
+				.map_err(|_| wrong_type(v))?
+				.to_string(),
+			(Some(v @ toml::Value::String(s)), FieldKind::Date) => {
+				NaiveDate::parse_from_str(s.trim(), DATE_FORMAT)
+					.map_err(|_| wrong_type(v))?
+					.format(DATE_FORMAT)
+					.to_string()
+			}
+			(Some(v @ toml::Value::Datetime(dt)), FieldKind::Date) => match (dt.date, dt.time) {
+				(Some(date), None) => date.to_string(),
+				_ => return Err(wrong_type(v)),
+			},
+			(Some(v), _) => return Err(wrong_type(v)),
+		};
+		if self.required && stored.is_empty() {
+			return Err(FieldValueErr::Missing {
+				field: self.name.clone(),
+			});
+		}
+		Ok(stored)
+	}
+}
+
+pub fn validate_field_defs(defs: &[FieldDef]) -> Result<(), FieldDefErr> {
+	for (idx, def) in defs.iter().enumerate() {
+		if def.name.is_empty()
+			|| !def
+				.name
+				.chars()
+				.all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_')
+		{
+			return Err(FieldDefErr::BadName(def.name.clone()));
+		}
+		if REQUIREMENT_COLUMNS.contains(&def.name.as_str()) {
+			return Err(FieldDefErr::Reserved(def.name.clone()));
+		}
+		if defs[..idx].iter().any(|d| d.name == def.name) {
+			return Err(FieldDefErr::Duplicate(def.name.clone()));
+		}
+		if let FieldKind::Enum { values } = &def.kind {
+			if values.is_empty() {
+				return Err(FieldDefErr::NoValues(def.name.clone()));
+			}
+			if let Some(bad) = values
+				.iter()
+				.find(|v| v.trim().is_empty() || v.contains(['\t', '\n', '\r']))
+			{
+				return Err(FieldDefErr::BadValue {
+					field: def.name.clone(),
+					value: bad.clone(),
+				});
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Custom field values of one record by field name, as stored in the table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CustomFields(pub BTreeMap<String, String>);
+
+impl CustomFields {
+	/// The stored value, empty if unset.
+	pub fn get(&self, name: &str) -> &str {
+		self.0.get(name).map(String::as_str).unwrap_or("")
+	}
+}
+
+/// Reads every column of `raw_table` that isn't in `builtin`, one [`CustomFields`] per record.
+/// Columns of fields no longer in the config are kept so saving doesn't lose them.
+pub fn read_custom_columns(
+	raw_table: &str,
+	builtin: &[&str],
+) -> Result<Vec<CustomFields>, csv::Error> {
+	let mut tsv_reader = csv::ReaderBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(RECORD_DELIMITER))
+		.from_reader(raw_table.as_bytes());
+	let columns: Vec<(usize, String)> = tsv_reader
+		.headers()?
+		.iter()
+		.enumerate()
+		.filter(|(_, name)| !builtin.contains(name))
+		.map(|(idx, name)| (idx, name.to_string()))
+		.collect();
+
+	tsv_reader
+		.records()
+		.map(|res| {
+			res.map(|record| {
+				CustomFields(
+					columns
+						.iter()
+						.filter_map(|(idx, name)| {
+							record
+								.get(*idx)
+								.filter(|v| !v.is_empty())
+								.map(|v| (name.clone(), v.to_string()))
+						})
+						.collect(),
+				)
+			})
+		})
+		.collect()
+}
+
+/// Adds a column per field in `defs`, then any other fields found in `rows`,
+/// to a table already serialized with headers.
+pub fn append_custom_columns(
+	table: &[u8],
+	defs: &[FieldDef],
+	rows: &[&CustomFields],
+) -> Result<Vec<u8>, csv::Error> {
+	let mut columns: Vec<&str> = defs.iter().map(|d| d.name.as_str()).collect();
+	for name in rows.iter().flat_map(|r| r.0.keys()) {
+		if !columns.contains(&name.as_str()) {
+			columns.push(name);
+		}
+	}
+	if columns.is_empty() {
+		return Ok(table.to_vec());
+	}
+
+	let mut tsv_reader = csv::ReaderBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(RECORD_DELIMITER))
+		.has_headers(false)
+		.from_reader(table);
+	let mut wtr = csv::WriterBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(RECORD_DELIMITER))
+		.from_writer(Vec::with_capacity(table.len()));
+
+	let mut records = tsv_reader.records();
+	if let Some(header) = records.next() {
+		let mut header = header?;
+		header.extend(columns.iter());
+		wtr.write_record(&header)?;
+	}
+	for (record, custom) in records.zip(rows) {
+		let mut record = record?;
+		record.extend(columns.iter().map(|c| custom.get(c)));
+		wtr.write_record(&record)?;
+	}
+	wtr.into_inner()
+		.map_err(|e| csv::Error::from(e.into_error()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn field(name: &str, kind: FieldKind, required: bool) -> FieldDef {
+		FieldDef {
+			name: name.into(),
+			label: None,
+			kind,
+			required,
+		}
+	}
+
+	#[test]
+	fn test_normalize() {
+		let safety = field(
+			"safety_class",
+			FieldKind::Enum {
+				values: vec!["A".into(), "B".into()],
+			},
+			true,
+		);
+		assert_eq!(Ok("A".into()), safety.normalize(Some(&"A".into())));
+		assert!(safety.normalize(Some(&"C".into())).is_err());
+		assert!(safety.normalize(None).is_err());
+
+		let count = field("count", FieldKind::Integer, false);
+		assert_eq!(Ok("12".into()), count.normalize(Some(&12.into())));
+		assert_eq!(Ok("12".into()), count.normalize(Some(&" 12".into())));
+		assert_eq!(Ok(String::new()), count.normalize(None));
+		assert!(count.normalize(Some(&"twelve".into())).is_err());
+
+		let due = field("due", FieldKind::Date, false);
+		assert_eq!(
+			Ok("2025-06-16".into()),
+			due.normalize(Some(&"2025-06-16".into()))
+		);
+		let date: toml::Value =
+			toml::from_str::<toml::Table>("d = 2025-06-16").unwrap()["d"].clone();
+		assert_eq!(Ok("2025-06-16".into()), due.normalize(Some(&date)));
+		assert!(due.normalize(Some(&"16/06/2025".into())).is_err());
+
+		let notes = field("notes", FieldKind::Text, false);
+		assert_eq!(Ok("a\\nb".into()), notes.normalize(Some(&"a\nb".into())));
+		let customer = field("customer", FieldKind::String, false);
+		assert!(customer.normalize(Some(&"a\nb".into())).is_err());
+	}
+
+	#[test]
+	fn test_validate_field_defs() {
+		assert_eq!(
+			Err(FieldDefErr::Reserved("title".into())),
+			validate_field_defs(&[field("title", FieldKind::String, false)])
+		);
+		assert_eq!(
+			Err(FieldDefErr::BadName("Customer Ref".into())),
+			validate_field_defs(&[field("Customer Ref", FieldKind::String, false)])
+		);
+		assert_eq!(
+			Err(FieldDefErr::Duplicate("a".into())),
+			validate_field_defs(&[
+				field("a", FieldKind::String, false),
+				field("a", FieldKind::Date, false)
+			])
+		);
+		assert_eq!(
+			Err(FieldDefErr::NoValues("e".into())),
+			validate_field_defs(&[field("e", FieldKind::Enum { values: vec![] }, false)])
+		);
+	}
+
+	#[test]
+	fn test_custom_columns_round_trip() {
+		let table = b"id\ttitle\n0\tA\n1\tB\n";
+		let defs = [
+			field("customer", FieldKind::String, false),
+			field("count", FieldKind::Integer, false),
+		];
+		let a = CustomFields(BTreeMap::from([("customer".into(), "ACME".into())]));
+		let b = CustomFields(BTreeMap::from([
+			("count".into(), "3".into()),
+			("dropped".into(), "kept".into()),
+		]));
+		let written = append_custom_columns(table, &defs, &[&a, &b]).unwrap();
+		assert_eq!(
+			"id\ttitle\tcustomer\tcount\tdropped\n0\tA\tACME\t\t\n1\tB\t\t3\tkept\n",
+			String::from_utf8(written.clone()).unwrap()
+		);
+		let read =
+			read_custom_columns(std::str::from_utf8(&written).unwrap(), &["id", "title"]).unwrap();
+		assert_eq!(vec![a, b], read);
+	}
+}
@@ -0,0 +1,756 @@
+//! A small expression language to pick requirements or components, e.g.
+//!
+//! ```text
+//! priority in (High, Mandated) and functional = NonFunctional and status = Accepted
+//!     and component_name = Powertrain and creation_date > 2025-06-01 and author = JM
+//! ```
+//!
+//! Fields are the ones of the [JSON schema](crate::json), custom fields of requirements are
+//! `custom.<name>`. A comparison is `field op value` where op is one of:
+//!
+//! | op | |
+//! |---|---|
+//! | `=` `!=` | equal, text ignores case |
+//! | `<` `<=` `>` `>=` | numbers, dates (`YYYY-MM-DD`), and text |
+//! | `~` | text contains the value, ignoring case |
+//! | `=~` | text matches the regex |
+//! | `in (a, b)` | equal to any of the values |
+//!
+//! Comparisons join with `and`, `or`, and `not`, and group with parentheses. `and` binds
+//! tighter than `or`. Values with spaces or symbols are quoted e.g. `title ~ "brake latency"`.
+//! A tag field matches when any of its tags does. A missing value, such as the `parent_id` of
+//! a top level requirement, only equals `null`.
+
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+	Project,
+	custom_field::DATE_FORMAT,
+	json::{ComponentJson, RequirementJson},
+};
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum FilterErr {
+	#[error("filter syntax error at character {at}: {msg}")]
+	Syntax { at: usize, msg: String },
+	#[error("bad regex: {0}")]
+	Regex(String),
+	#[error("unknown field: {0:?}")]
+	UnknownField(String),
+	#[error("field: {field} is {kind}, can't compare it with: {value:?}")]
+	BadValue {
+		field: String,
+		kind: &'static str,
+		value: String,
+	},
+	#[error("field: {field} is {kind}, it can't be compared with: {op}")]
+	BadOp {
+		field: String,
+		kind: &'static str,
+		op: &'static str,
+	},
+}
+
+/// A field of a record as the filter sees it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValue<'a> {
+	Integer(i64),
+	Text(Cow<'a, str>),
+	Date(NaiveDate),
+	Tags(Vec<&'a str>),
+	/// An optional field that isn't set.
+	Null,
+}
+
+impl FieldValue<'_> {
+	fn kind(&self) -> &'static str {
+		match self {
+			FieldValue::Integer(_) => "an integer",
+			FieldValue::Text(_) => "text",
+			FieldValue::Date(_) => "a date",
+			FieldValue::Tags(_) => "a list of tags",
+			FieldValue::Null => "not set",
+		}
+	}
+}
+
+/// A record that can be filtered.
+pub trait Filterable {
+	/// `None` for a field the record doesn't have.
+	fn field(&self, name: &str) -> Option<FieldValue<'_>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+	Eq,
+	Ne,
+	Lt,
+	Le,
+	Gt,
+	Ge,
+	Contains,
+}
+
+impl Op {
+	fn as_str(self) -> &'static str {
+		match self {
+			Op::Eq => "=",
+			Op::Ne => "!=",
+			Op::Lt => "<",
+			Op::Le => "<=",
+			Op::Gt => ">",
+			Op::Ge => ">=",
+			Op::Contains => "~",
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+	And(Box<Expr>, Box<Expr>),
+	Or(Box<Expr>, Box<Expr>),
+	Not(Box<Expr>),
+	Compare {
+		field: String,
+		op: Op,
+		value: String,
+	},
+	Regex {
+		field: String,
+		regex: Regex,
+	},
+	In {
+		field: String,
+		values: Vec<String>,
+	},
+}
+
+/// A parsed filter expression, see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct Filter {
+	expr: Expr,
+}
+
+impl FromStr for Filter {
+	type Err = FilterErr;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let tokens = tokenize(s)?;
+		let mut parser = Parser {
+			tokens,
+			pos: 0,
+			end: s.chars().count(),
+		};
+		let expr = parser.or()?;
+		match parser.tokens.get(parser.pos) {
+			None => Ok(Self { expr }),
+			Some((at, token)) => Err(FilterErr::Syntax {
+				at: *at,
+				msg: format!("expected \"and\", \"or\", or the end, found: {token}"),
+			}),
+		}
+	}
+}
+
+impl Filter {
+	/// Errors on a field the record doesn't have or a value of the wrong kind.
+	pub fn matches(&self, record: &impl Filterable) -> Result<bool, FilterErr> {
+		eval(&self.expr, record)
+	}
+}
+
+/// The records matching `filter`, all of them when there is none.
+pub fn select<R: Filterable>(
+	records: Vec<R>,
+	filter: Option<&Filter>,
+) -> Result<Vec<R>, FilterErr> {
+	let Some(filter) = filter else {
+		return Ok(records);
+	};
+	let mut selected = Vec::with_capacity(records.len());
+	for record in records {
+		if filter.matches(&record)? {
+			selected.push(record);
+		}
+	}
+	Ok(selected)
+}
+
+impl Project {
+	/// Drops the requirements not matching `filter`, and their links and history, so they
+	/// aren't built. The raw tables are left as they are.
+	pub fn filter_requirements(&mut self, filter: &Filter) -> Result<(), FilterErr> {
+		let keep: Vec<u64> = select(self.requirements_json(), Some(filter))?
+			.iter()
+			.map(|r| r.id)
+			.collect();
+		self.requirements.retain(|r| keep.contains(&r.id));
+		self.links
+			.retain(|l| keep.contains(&l.from_id) && keep.contains(&l.to_id));
+		self.history
+			.retain(|rev| keep.contains(&rev.requirement_id));
+		Ok(())
+	}
+}
+
+fn eval(expr: &Expr, record: &impl Filterable) -> Result<bool, FilterErr> {
+	let field = |name: &str| {
+		record
+			.field(name)
+			.ok_or_else(|| FilterErr::UnknownField(name.to_string()))
+	};
+	match expr {
+		Expr::And(lhs, rhs) => Ok(eval(lhs, record)? && eval(rhs, record)?),
+		Expr::Or(lhs, rhs) => Ok(eval(lhs, record)? || eval(rhs, record)?),
+		Expr::Not(inner) => Ok(!eval(inner, record)?),
+		Expr::Compare {
+			field: name,
+			op,
+			value,
+		} => compare(name, &field(name)?, *op, value),
+		Expr::In {
+			field: name,
+			values,
+		} => {
+			let found = field(name)?;
+			for value in values {
+				if compare(name, &found, Op::Eq, value)? {
+					return Ok(true);
+				}
+			}
+			Ok(false)
+		}
+		Expr::Regex { field: name, regex } => match field(name)? {
+			FieldValue::Integer(i) => Ok(regex.is_match(&i.to_string())),
+			FieldValue::Text(text) => Ok(regex.is_match(&text)),
+			FieldValue::Date(date) => Ok(regex.is_match(&date.format(DATE_FORMAT).to_string())),
+			FieldValue::Tags(tags) => Ok(tags.iter().any(|tag| regex.is_match(tag))),
+			FieldValue::Null => Ok(false),
+		},
+	}
+}
+
+fn compare(name: &str, found: &FieldValue, op: Op, value: &str) -> Result<bool, FilterErr> {
+	let bad_value = || FilterErr::BadValue {
+		field: name.to_string(),
+		kind: found.kind(),
+		value: value.to_string(),
+	};
+	let ordered = |ordering: std::cmp::Ordering| -> Option<bool> {
+		match op {
+			Op::Eq => Some(ordering.is_eq()),
+			Op::Ne => Some(ordering.is_ne()),
+			Op::Lt => Some(ordering.is_lt()),
+			Op::Le => Some(ordering.is_le()),
+			Op::Gt => Some(ordering.is_gt()),
+			Op::Ge => Some(ordering.is_ge()),
+			Op::Contains => None,
+		}
+	};
+	let matched = match found {
+		FieldValue::Integer(i) => match op {
+			Op::Contains => Some(i.to_string().contains(value)),
+			_ => ordered(i.cmp(&value.parse::<i64>().map_err(|_| bad_value())?)),
+		},
+		FieldValue::Date(date) => match op {
+			Op::Contains => Some(date.format(DATE_FORMAT).to_string().contains(value)),
+			_ => ordered(
+				date.cmp(&NaiveDate::parse_from_str(value, DATE_FORMAT).map_err(|_| bad_value())?),
+			),
+		},
+		FieldValue::Text(text) => match op {
+			Op::Eq => Some(text.eq_ignore_ascii_case(value)),
+			Op::Ne => Some(!text.eq_ignore_ascii_case(value)),
+			Op::Contains => Some(contains_ignore_case(text, value)),
+			_ => ordered(text.as_ref().cmp(value)),
+		},
+		FieldValue::Tags(tags) => match op {
+			Op::Eq => Some(tags.iter().any(|tag| tag.eq_ignore_ascii_case(value))),
+			Op::Ne => Some(!tags.iter().any(|tag| tag.eq_ignore_ascii_case(value))),
+			Op::Contains => Some(tags.iter().any(|tag| contains_ignore_case(tag, value))),
+			_ => None,
+		},
+		FieldValue::Null => {
+			let is_null = value.eq_ignore_ascii_case("null");
+			match op {
+				Op::Eq => Some(is_null),
+				Op::Ne => Some(!is_null),
+				_ => Some(false),
+			}
+		}
+	};
+	matched.ok_or_else(|| FilterErr::BadOp {
+		field: name.to_string(),
+		kind: found.kind(),
+		op: op.as_str(),
+	})
+}
+
+fn contains_ignore_case(text: &str, value: &str) -> bool {
+	text.to_lowercase().contains(&value.to_lowercase())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+	/// A field name, keyword, or unquoted value.
+	Word(String),
+	Quoted(String),
+	Op(&'static str),
+	Open,
+	Close,
+	Comma,
+}
+
+impl std::fmt::Display for Token {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Token::Word(word) => write!(f, "{word}"),
+			Token::Quoted(text) => write!(f, "{text:?}"),
+			Token::Op(op) => write!(f, "{op}"),
+			Token::Open => write!(f, "("),
+			Token::Close => write!(f, ")"),
+			Token::Comma => write!(f, ","),
+		}
+	}
+}
+
+/// Tokens and the character they start at.
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>, FilterErr> {
+	// longest first so "<=" isn't read as "<"
+	const OPS: [&str; 8] = ["=~", "!=", "<=", ">=", "=", "<", ">", "~"];
+	let chars: Vec<char> = s.chars().collect();
+	let mut tokens = Vec::new();
+	let mut i = 0;
+	while i < chars.len() {
+		let ch = chars[i];
+		if ch.is_whitespace() {
+			i += 1;
+			continue;
+		}
+		let start = i;
+		let token = match ch {
+			'(' => Token::Open,
+			')' => Token::Close,
+			',' => Token::Comma,
+			'"' => {
+				let mut text = String::new();
+				i += 1;
+				loop {
+					match chars.get(i) {
+						None => {
+							return Err(FilterErr::Syntax {
+								at: start,
+								msg: "unterminated quote".into(),
+							});
+						}
+						Some('"') => break,
+						Some('\\') if chars.get(i + 1).is_some() => {
+							text.push(chars[i + 1]);
+							i += 2;
+						}
+						Some(ch) => {
+							text.push(*ch);
+							i += 1;
+						}
+					}
+				}
+				Token::Quoted(text)
+			}
+			_ => {
+				let rest: String = chars[i..].iter().take(2).collect();
+				match OPS.iter().find(|op| rest.starts_with(**op)) {
+					Some(op) => {
+						i += op.chars().count() - 1;
+						Token::Op(op)
+					}
+					None => {
+						let len = chars[i..]
+							.iter()
+							.take_while(|ch| is_word_char(**ch))
+							.count();
+						if len == 0 {
+							return Err(FilterErr::Syntax {
+								at: start,
+								msg: format!("unexpected character: {ch:?}"),
+							});
+						}
+						i += len - 1;
+						Token::Word(chars[start..start + len].iter().collect())
+					}
+				}
+			}
+		};
+		tokens.push((start, token));
+		i += 1;
+	}
+	Ok(tokens)
+}
+
+fn is_word_char(ch: char) -> bool {
+	!ch.is_whitespace() && !matches!(ch, '(' | ')' | ',' | '"' | '=' | '!' | '<' | '>' | '~')
+}
+
+struct Parser {
+	tokens: Vec<(usize, Token)>,
+	pos: usize,
+	/// Where the input ends, for errors about missing tokens.
+	end: usize,
+}
+
+impl Parser {
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.pos).map(|(_, token)| token)
+	}
+
+	fn next(&mut self, expected: &str) -> Result<(usize, Token), FilterErr> {
+		let next = self.tokens.get(self.pos).cloned();
+		self.pos += 1;
+		next.ok_or_else(|| FilterErr::Syntax {
+			at: self.end,
+			msg: format!("expected {expected}, found the end"),
+		})
+	}
+
+	fn is_keyword(&self, keyword: &str) -> bool {
+		matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+	}
+
+	fn or(&mut self) -> Result<Expr, FilterErr> {
+		let mut expr = self.and()?;
+		while self.is_keyword("or") {
+			self.pos += 1;
+			expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+		}
+		Ok(expr)
+	}
+
+	fn and(&mut self) -> Result<Expr, FilterErr> {
+		let mut expr = self.unary()?;
+		while self.is_keyword("and") {
+			self.pos += 1;
+			expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+		}
+		Ok(expr)
+	}
+
+	fn unary(&mut self) -> Result<Expr, FilterErr> {
+		if self.is_keyword("not") {
+			self.pos += 1;
+			return Ok(Expr::Not(Box::new(self.unary()?)));
+		}
+		if self.peek() == Some(&Token::Open) {
+			self.pos += 1;
+			let expr = self.or()?;
+			return match self.next("\")\"")? {
+				(_, Token::Close) => Ok(expr),
+				(at, token) => Err(FilterErr::Syntax {
+					at,
+					msg: format!("expected \")\", found: {token}"),
+				}),
+			};
+		}
+		self.comparison()
+	}
+
+	fn comparison(&mut self) -> Result<Expr, FilterErr> {
+		let field = match self.next("a field name")? {
+			(_, Token::Word(word)) => word,
+			(at, token) => {
+				return Err(FilterErr::Syntax {
+					at,
+					msg: format!("expected a field name, found: {token}"),
+				});
+			}
+		};
+		if self.is_keyword("in") {
+			self.pos += 1;
+			return self.in_list(field);
+		}
+		let (at, op) = match self.next("an operator")? {
+			(at, Token::Op(op)) => (at, op),
+			(at, token) => {
+				return Err(FilterErr::Syntax {
+					at,
+					msg: format!("expected an operator after: {field}, found: {token}"),
+				});
+			}
+		};
+		let value = self.value()?;
+		let op = match op {
+			"=~" => {
+				let regex = Regex::new(&value).map_err(|e| FilterErr::Regex(e.to_string()))?;
+				return Ok(Expr::Regex { field, regex });
+			}
+			"=" => Op::Eq,
+			"!=" => Op::Ne,
+			"<" => Op::Lt,
+			"<=" => Op::Le,
+			">" => Op::Gt,
+			">=" => Op::Ge,
+			"~" => Op::Contains,
+			op => {
+				return Err(FilterErr::Syntax {
+					at,
+					msg: format!("unknown operator: {op}"),
+				});
+			}
+		};
+		Ok(Expr::Compare { field, op, value })
+	}
+
+	fn in_list(&mut self, field: String) -> Result<Expr, FilterErr> {
+		match self.next("\"(\"")? {
+			(_, Token::Open) => {}
+			(at, token) => {
+				return Err(FilterErr::Syntax {
+					at,
+					msg: format!("expected \"(\" after in, found: {token}"),
+				});
+			}
+		}
+		let mut values = vec![self.value()?];
+		loop {
+			match self.next("\",\" or \")\"")? {
+				(_, Token::Comma) => values.push(self.value()?),
+				(_, Token::Close) => return Ok(Expr::In { field, values }),
+				(at, token) => {
+					return Err(FilterErr::Syntax {
+						at,
+						msg: format!("expected \",\" or \")\", found: {token}"),
+					});
+				}
+			}
+		}
+	}
+
+	fn value(&mut self) -> Result<String, FilterErr> {
+		match self.next("a value")? {
+			(_, Token::Word(value)) | (_, Token::Quoted(value)) => Ok(value),
+			(at, token) => Err(FilterErr::Syntax {
+				at,
+				msg: format!("expected a value, found: {token}"),
+			}),
+		}
+	}
+}
+
+/// The name an enum is stored and printed in JSON with e.g. "NonFunctional".
+fn serde_name(value: &impl Serialize) -> FieldValue<'static> {
+	match serde_json::to_value(value) {
+		Ok(serde_json::Value::String(name)) => FieldValue::Text(Cow::Owned(name)),
+		_ => FieldValue::Null,
+	}
+}
+
+fn text(text: &str) -> FieldValue<'_> {
+	FieldValue::Text(Cow::Borrowed(text))
+}
+
+fn optional_id(id: Option<u64>) -> FieldValue<'static> {
+	id.map(|id| FieldValue::Integer(id as i64))
+		.unwrap_or(FieldValue::Null)
+}
+
+fn optional_text(value: Option<&str>) -> FieldValue<'_> {
+	value.map(text).unwrap_or(FieldValue::Null)
+}
+
+impl Filterable for ComponentJson<'_> {
+	fn field(&self, name: &str) -> Option<FieldValue<'_>> {
+		Some(match name {
+			"id" => FieldValue::Integer(self.id as i64),
+			"key" => text(&self.key),
+			"prefix" => text(self.prefix),
+			"parent_id" => optional_id(self.parent_id),
+			"name" => text(self.name),
+			"path" => text(&self.path),
+			"description" => text(&self.description),
+			"creation_date" => FieldValue::Date(self.creation_date.date_naive()),
+			"status" => serde_name(&self.status),
+			"author" => text(self.author),
+			"tags" => FieldValue::Tags(self.tags.clone()),
+			_ => return None,
+		})
+	}
+}
+
+impl Filterable for RequirementJson<'_> {
+	fn field(&self, name: &str) -> Option<FieldValue<'_>> {
+		if let Some(custom) = name.strip_prefix("custom.") {
+			return Some(optional_text(self.custom.get(custom).map(String::as_str)));
+		}
+		Some(match name {
+			"id" => FieldValue::Integer(self.id as i64),
+			"key" => text(&self.key),
+			"component_id" => FieldValue::Integer(self.component_id as i64),
+			"component_key" => optional_text(self.component_key.as_deref()),
+			"component_name" => optional_text(self.component_name),
+			"component_path" => optional_text(self.component_path.as_deref()),
+			"parent_id" => optional_id(self.parent_id),
+			"parent_key" => optional_text(self.parent_key.as_deref()),
+			"title" => text(self.title),
+			"functional" => serde_name(&self.functional),
+			"creation_date" => FieldValue::Date(self.creation_date.date_naive()),
+			"requirement_text" => text(&self.requirement_text),
+			"acceptance_criteria" => text(&self.acceptance_criteria),
+			"verification_method" => serde_name(&self.verification_method),
+			"version" => FieldValue::Integer(self.version as i64),
+			"author" => text(self.author),
+			"priority" => serde_name(&self.priority),
+			"status" => serde_name(&self.status),
+			"status_justification" => text(self.status_justification),
+			"risks" => text(&self.risks),
+			"tags" => FieldValue::Tags(self.tags.clone()),
+			_ => return None,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		Component, RecordStatus, Requirement, RequirementFunctional, RequirementPriority, Tags,
+		VerificationMethod,
+	};
+	use chrono::{Local, TimeZone};
+
+	fn component() -> Component {
+		Component {
+			id: 0,
+			prefix: "PWR".into(),
+			next_seq: 3,
+			parent_id: None,
+			name: "Powertrain".into(),
+			description: String::new(),
+			creation_date: Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "JM".into(),
+			tags: Tags::default(),
+		}
+	}
+
+	fn requirement(
+		id: u64,
+		priority: RequirementPriority,
+		created: (i32, u32, u32),
+	) -> Requirement {
+		Requirement {
+			id,
+			key: format!("PWR-000{id}"),
+			component_id: 0,
+			parent_id: None,
+			title: format!("Brake latency {id}"),
+			functional: RequirementFunctional::NonFunctional,
+			creation_date: Local
+				.with_ymd_and_hms(created.0, created.1, created.2, 12, 0, 0)
+				.unwrap(),
+			requirement_text: "Stop within 20 ms".into(),
+			acceptance_criteria: String::new(),
+			verification_method: VerificationMethod::Test,
+			version: 0,
+			author: "JM".into(),
+			priority,
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			risks: String::new(),
+			tags: Tags(vec!["safety".into()]),
+			custom: Default::default(),
+		}
+	}
+
+	fn matching(filter: &str, requirements: &[Requirement]) -> Result<Vec<u64>, FilterErr> {
+		let components = [component()];
+		let json: Vec<RequirementJson> = requirements
+			.iter()
+			.map(|r| RequirementJson::new(r, &components, requirements, &[]))
+			.collect();
+		let filter: Filter = filter.parse()?;
+		Ok(select(json, Some(&filter))?.iter().map(|r| r.id).collect())
+	}
+
+	#[test]
+	fn test_filter() {
+		let requirements = [
+			requirement(1, RequirementPriority::High, (2025, 7, 1)),
+			requirement(2, RequirementPriority::Low, (2025, 7, 1)),
+			requirement(3, RequirementPriority::Mandated, (2025, 5, 1)),
+		];
+		assert_eq!(
+			Ok(vec![1]),
+			matching(
+				"priority in (High, Mandated) and functional = NonFunctional and status = Accepted \
+				 and component_name = Powertrain and creation_date > 2025-06-01 and author = jm",
+				&requirements
+			)
+		);
+		assert_eq!(
+			Ok(vec![1, 3]),
+			matching("priority = high or priority = \"Mandated\"", &requirements)
+		);
+		assert_eq!(
+			Ok(vec![2]),
+			matching("not (priority = High or id >= 3)", &requirements)
+		);
+		// and binds tighter than or
+		assert_eq!(
+			Ok(vec![1, 3]),
+			matching("id = 1 or id = 3 and title ~ LATENCY", &requirements)
+		);
+		assert_eq!(
+			Ok(vec![2]),
+			matching("title =~ \"latency [2]$\"", &requirements)
+		);
+		assert_eq!(
+			Ok(vec![1, 2, 3]),
+			matching("tags = SAFETY and parent_id = null", &requirements)
+		);
+		assert_eq!(Ok(vec![]), matching("custom.effort != null", &requirements));
+	}
+
+	#[test]
+	fn test_filter_errors() {
+		let requirements = [requirement(1, RequirementPriority::High, (2025, 7, 1))];
+		assert_eq!(
+			Err(FilterErr::UnknownField("colour".into())),
+			matching("colour = red", &requirements)
+		);
+		assert!(matches!(
+			matching("creation_date > June", &requirements),
+			Err(FilterErr::BadValue { .. })
+		));
+		assert!(matches!(
+			matching("tags > a", &requirements),
+			Err(FilterErr::BadOp { .. })
+		));
+		assert_eq!(
+			Err(FilterErr::Syntax {
+				at: 7,
+				msg: "expected a value, found the end".into()
+			}),
+			"title =".parse::<Filter>().map(|_| ())
+		);
+		assert!(matches!(
+			"(id = 1".parse::<Filter>(),
+			Err(FilterErr::Syntax { .. })
+		));
+		assert!(matches!(
+			"id = 1 id = 2".parse::<Filter>(),
+			Err(FilterErr::Syntax { at: 7, .. })
+		));
+		assert!(matches!(
+			"title ~ \"open".parse::<Filter>(),
+			Err(FilterErr::Syntax { at: 8, .. })
+		));
+		assert!(matches!(
+			"title =~ \"(\"".parse::<Filter>(),
+			Err(FilterErr::Regex(_))
+		));
+	}
+}
@@ -0,0 +1,348 @@
+//! JSON and NDJSON views of components and requirements, for scripts and dashboards that
+//! shouldn't have to know the TSV escaping rules.
+//!
+//! # Schema
+//!
+//! Version [`JSON_SCHEMA_VERSION`]. Fields are only ever added, a field is never renamed,
+//! removed, or given another type without bumping the version. Enum fields hold the name stored
+//! in the tables e.g. `"NonFunctional"`, dates are RFC 3339, and multi-line text holds real
+//! new-lines.
+//!
+//! A component:
+//!
+//! | field | type | |
+//! |---|---|---|
+//! | `id` | integer | |
+//! | `key` | string | the prefix, or the ID for components without one |
+//! | `prefix` | string | empty if not declared |
+//! | `parent_id` | integer or null | |
+//! | `name` | string | |
+//! | `path` | string | names from the root component e.g. `"Vehicle / Powertrain"` |
+//! | `description` | string | multi-line |
+//! | `creation_date` | string | |
+//! | `status` | string | |
+//! | `author` | string | |
+//! | `tags` | array of strings | |
+//!
+//! A requirement:
+//!
+//! | field | type | |
+//! |---|---|---|
+//! | `id` | integer | |
+//! | `key` | string | e.g. `"PWR-0012"`, or the ID for requirements without one |
+//! | `component_id` | integer | |
+//! | `component_key` | string or null | null when the component is missing |
+//! | `component_name` | string or null | |
+//! | `component_path` | string or null | |
+//! | `parent_id` | integer or null | |
+//! | `parent_key` | string or null | |
+//! | `title` | string | |
+//! | `functional` | string | |
+//! | `creation_date` | string | |
+//! | `requirement_text` | string | multi-line |
+//! | `acceptance_criteria` | string | multi-line |
+//! | `verification_method` | string | |
+//! | `version` | integer | |
+//! | `author` | string | |
+//! | `priority` | string | |
+//! | `status` | string | |
+//! | `status_justification` | string | |
+//! | `risks` | string | multi-line |
+//! | `tags` | array of strings | |
+//! | `custom` | object of strings | set custom fields by name, text fields are multi-line |
+//!
+//! [`JsonFormat::Json`] writes a list as an array and one record as an object,
+//! [`JsonFormat::Ndjson`] writes one object per line.
+
+use std::{collections::BTreeMap, io::Write};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+
+use crate::{
+	Component, Project, RecordStatus, Requirement, RequirementFunctional, RequirementPriority,
+	VerificationMethod, component_path,
+	custom_field::{FieldDef, FieldKind},
+};
+
+/// See the [module docs](self).
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum JsonFormat {
+	/// Pretty printed.
+	Json,
+	/// One compact object per line.
+	Ndjson,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ComponentJson<'a> {
+	pub id: u64,
+	pub key: String,
+	pub prefix: &'a str,
+	pub parent_id: Option<u64>,
+	pub name: &'a str,
+	pub path: String,
+	pub description: String,
+	pub creation_date: DateTime<Local>,
+	pub status: RecordStatus,
+	pub author: &'a str,
+	pub tags: Vec<&'a str>,
+}
+
+impl<'a> ComponentJson<'a> {
+	pub fn new(component: &'a Component, components: &[Component]) -> Self {
+		Self {
+			id: component.id,
+			key: component.key(),
+			prefix: &component.prefix,
+			parent_id: component.parent_id,
+			name: &component.name,
+			path: component_path(component.id, components),
+			description: decode_text(&component.description),
+			creation_date: component.creation_date,
+			status: component.status,
+			author: &component.author,
+			tags: component.tags.iter().collect(),
+		}
+	}
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RequirementJson<'a> {
+	pub id: u64,
+	pub key: String,
+	pub component_id: u64,
+	pub component_key: Option<String>,
+	pub component_name: Option<&'a str>,
+	pub component_path: Option<String>,
+	pub parent_id: Option<u64>,
+	pub parent_key: Option<String>,
+	pub title: &'a str,
+	pub functional: RequirementFunctional,
+	pub creation_date: DateTime<Local>,
+	pub requirement_text: String,
+	pub acceptance_criteria: String,
+	pub verification_method: VerificationMethod,
+	pub version: usize,
+	pub author: &'a str,
+	pub priority: RequirementPriority,
+	pub status: RecordStatus,
+	pub status_justification: &'a str,
+	pub risks: String,
+	pub tags: Vec<&'a str>,
+	pub custom: BTreeMap<&'a str, String>,
+}
+
+impl<'a> RequirementJson<'a> {
+	/// `fields` tells which custom fields are multi-line.
+	pub fn new(
+		requirement: &'a Requirement,
+		components: &'a [Component],
+		requirements: &[Requirement],
+		fields: &[FieldDef],
+	) -> Self {
+		let component = components.iter().find(|c| c.id == requirement.component_id);
+		let parent_key = requirement
+			.parent_id
+			.and_then(|id| requirements.iter().find(|r| r.id == id))
+			.map(Requirement::key);
+		let custom = requirement
+			.custom
+			.0
+			.iter()
+			.map(|(name, value)| {
+				let is_text = fields
+					.iter()
+					.any(|def| def.name == *name && def.kind == FieldKind::Text);
+				let value = match is_text {
+					true => decode_text(value),
+					false => value.clone(),
+				};
+				(name.as_str(), value)
+			})
+			.collect();
+		Self {
+			id: requirement.id,
+			key: requirement.key(),
+			component_id: requirement.component_id,
+			component_key: component.map(Component::key),
+			component_name: component.map(|c| c.name.as_str()),
+			component_path: component.map(|c| component_path(c.id, components)),
+			parent_id: requirement.parent_id,
+			parent_key,
+			title: &requirement.title,
+			functional: requirement.functional,
+			creation_date: requirement.creation_date,
+			requirement_text: decode_text(&requirement.requirement_text),
+			acceptance_criteria: decode_text(&requirement.acceptance_criteria),
+			verification_method: requirement.verification_method,
+			version: requirement.version,
+			author: &requirement.author,
+			priority: requirement.priority,
+			status: requirement.status,
+			status_justification: &requirement.status_justification,
+			risks: decode_text(&requirement.risks),
+			tags: requirement.tags.iter().collect(),
+			custom,
+		}
+	}
+}
+
+impl Project {
+	pub fn components_json(&self) -> Vec<ComponentJson<'_>> {
+		self.components
+			.iter()
+			.map(|c| ComponentJson::new(c, &self.components))
+			.collect()
+	}
+
+	pub fn requirements_json(&self) -> Vec<RequirementJson<'_>> {
+		self.requirements
+			.iter()
+			.map(|r| {
+				RequirementJson::new(r, &self.components, &self.requirements, &self.config.fields)
+			})
+			.collect()
+	}
+}
+
+/// Undoes the escaping of new-lines in the tables, "\n" as two characters.
+pub fn decode_text(text: &str) -> String {
+	text.replace("\\n", "\n")
+}
+
+/// Writes `records` as an array, or a line each for NDJSON.
+pub fn write_list<S: Serialize>(
+	mut out: impl Write,
+	format: JsonFormat,
+	records: &[S],
+) -> Result<(), serde_json::Error> {
+	match format {
+		JsonFormat::Json => {
+			serde_json::to_writer_pretty(&mut out, records)?;
+			writeln!(out).map_err(serde_json::Error::io)
+		}
+		JsonFormat::Ndjson => records
+			.iter()
+			.try_for_each(|record| write_record(&mut out, format, record)),
+	}
+}
+
+/// Writes one record followed by a new-line.
+pub fn write_record<S: Serialize>(
+	mut out: impl Write,
+	format: JsonFormat,
+	record: &S,
+) -> Result<(), serde_json::Error> {
+	match format {
+		JsonFormat::Json => serde_json::to_writer_pretty(&mut out, record)?,
+		JsonFormat::Ndjson => serde_json::to_writer(&mut out, record)?,
+	}
+	writeln!(out).map_err(serde_json::Error::io)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Tags, custom_field::CustomFields};
+	use chrono::TimeZone;
+
+	fn component(id: u64, parent_id: Option<u64>, name: &str) -> Component {
+		Component {
+			id,
+			prefix: name.to_uppercase(),
+			next_seq: 2,
+			parent_id,
+			name: name.into(),
+			description: "first\\nsecond".into(),
+			creation_date: Local.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "ab".into(),
+			tags: Tags(vec!["ui".into()]),
+		}
+	}
+
+	#[test]
+	fn test_requirement_json() {
+		let components = vec![component(0, None, "veh"), component(1, Some(0), "nav")];
+		let requirement = Requirement {
+			id: 3,
+			key: "NAV-0001".into(),
+			component_id: 1,
+			parent_id: Some(9),
+			title: "Route".into(),
+			functional: RequirementFunctional::NonFunctional,
+			creation_date: Local.with_ymd_and_hms(2024, 5, 2, 12, 0, 0).unwrap(),
+			requirement_text: "line one\\nline two".into(),
+			acceptance_criteria: String::new(),
+			verification_method: VerificationMethod::Analysis,
+			version: 2,
+			author: "ab".into(),
+			priority: RequirementPriority::High,
+			status: RecordStatus::Approved,
+			status_justification: "reviewed".into(),
+			risks: "none".into(),
+			tags: Tags::default(),
+			custom: CustomFields(BTreeMap::from([
+				("notes".into(), "a\\nb".into()),
+				("code".into(), "x\\ny".into()),
+			])),
+		};
+		let fields: Vec<FieldDef> = toml::from_str::<BTreeMap<String, Vec<FieldDef>>>(
+			"fields = [{ name = \"notes\", kind = \"text\" }, { name = \"code\", kind = \"string\" }]",
+		)
+		.unwrap()
+		.remove("fields")
+		.unwrap();
+
+		let json = RequirementJson::new(&requirement, &components, &[], &fields);
+		assert_eq!(Some("NAV".to_string()), json.component_key);
+		assert_eq!(Some("nav"), json.component_name);
+		assert_eq!(Some("veh / nav".to_string()), json.component_path);
+		// a missing parent isn't an error
+		assert_eq!(None, json.parent_key);
+		assert_eq!("line one\nline two", json.requirement_text);
+		assert_eq!("a\nb", json.custom["notes"]);
+		assert_eq!("x\\ny", json.custom["code"]);
+
+		let mut out = Vec::new();
+		write_record(&mut out, JsonFormat::Ndjson, &json).unwrap();
+		let line = String::from_utf8(out).unwrap();
+		assert!(line.ends_with("}\n"));
+		assert_eq!(1, line.lines().count());
+		let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+		assert_eq!("NonFunctional", value["functional"]);
+		assert_eq!("Approved", value["status"]);
+		assert_eq!(3, value["id"]);
+		assert_eq!(serde_json::Value::Null, value["parent_key"]);
+	}
+
+	#[test]
+	fn test_write_list() {
+		let components = vec![component(0, None, "veh"), component(1, Some(0), "nav")];
+		let json: Vec<ComponentJson> = components
+			.iter()
+			.map(|c| ComponentJson::new(c, &components))
+			.collect();
+		assert_eq!("first\nsecond", json[0].description);
+
+		let mut out = Vec::new();
+		write_list(&mut out, JsonFormat::Ndjson, &json).unwrap();
+		let out = String::from_utf8(out).unwrap();
+		assert_eq!(2, out.lines().count());
+		let value: serde_json::Value = serde_json::from_str(out.lines().nth(1).unwrap()).unwrap();
+		assert_eq!("NAV", value["key"]);
+		assert_eq!("veh / nav", value["path"]);
+		assert_eq!(serde_json::json!(["ui"]), value["tags"]);
+
+		let mut out = Vec::new();
+		write_list(&mut out, JsonFormat::Json, &json).unwrap();
+		let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+		assert_eq!(2, value.as_array().unwrap().len());
+		assert_eq!(0, value[0]["id"]);
+	}
+}
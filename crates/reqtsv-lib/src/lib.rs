@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
@@ -14,10 +15,26 @@ use strum_macros::EnumIter;
 use strum_macros::EnumString;
 use thiserror::Error;
 
+pub mod check;
+pub mod config;
+pub mod custom_field;
+pub mod filter;
+pub mod json;
+pub mod lock;
+pub mod merge;
+pub mod recovery;
+pub mod schema;
+pub mod search;
+
+use config::{ProjectConfig, ProjectConfigErr, load_project_config};
+use custom_field::{CustomFields, REQUIREMENT_COLUMNS, read_custom_columns};
+use schema::{SchemaErr, Table, check_header, check_schema_version};
+
 pub const COLUMN_DELIMITER: u8 = b'\t';
 pub const RECORD_DELIMITER: u8 = b'\n';
-pub const COMPONENT_HEADER: &'static str = "id\tname\tdescription\tcreation_date\tstatus\tauthor\n";
-pub const REQUIREMENT_HEADER: &'static str = "id\tcomponent_id\tfunctional\tcreation_date\trequirement\tversion\tauthor\tpriority\tstatus\tstatus_justification\trisks\n";
+pub const COMPONENT_HEADER: &'static str =
+	"id\tprefix\tnext_seq\tparent_id\tname\tdescription\tcreation_date\tstatus\tauthor\ttags\n";
+pub const REQUIREMENT_HEADER: &'static str = "id\tkey\tcomponent_id\tparent_id\ttitle\tfunctional\tcreation_date\trequirement_text\tacceptance_criteria\tverification_method\tversion\tauthor\tpriority\tstatus\tstatus_justification\trisks\ttags\n";
 
 pub const COMPONENT_TABLE_NAME: &'static str = "component.tsv";
 pub const COMPONENT_OLD_TABLE_NAME: &'static str = "components.old.tsv";
@@ -25,27 +42,126 @@ pub const COMPONENT_NEW_TABLE_NAME: &'static str = "components.new.tsv";
 pub const COMPONENT_DRAFT_PREFIX: &'static str = "component_draft";
 pub const COMPONENT_EDIT_PREFIX: &'static str = "component_edit";
 
+pub const LINK_HEADER: &str = "id\tfrom_id\tkind\tto_id\tcreation_date\tstatus\n";
+
 pub const REQUIREMENT_TABLE_NAME: &'static str = "requirement.tsv";
 pub const REQUIREMENT_OLD_TABLE_NAME: &'static str = "requiremnt.old.tsv";
 pub const REQUIREMENT_NEW_TABLE_NAME: &'static str = "requirement.new.tsv";
 pub const REQUIREMENT_DRAFT_PREFIX: &'static str = "requirement_draft";
 pub const REQUIREMENT_EDIT_PREFIX: &'static str = "requirement_edit";
 
+pub const LINK_TABLE_NAME: &str = "link.tsv";
+pub const LINK_OLD_TABLE_NAME: &str = "link.old.tsv";
+pub const LINK_NEW_TABLE_NAME: &str = "link.new.tsv";
+
+pub const HISTORY_HEADER: &str = "requirement_id\tversion\ttitle\tfunctional\trequirement_text\tacceptance_criteria\tverification_method\tauthor\tpriority\trisks\ttags\tsuperseded_on\n";
+pub const HISTORY_TABLE_NAME: &str = "history.tsv";
+pub const HISTORY_OLD_TABLE_NAME: &str = "history.old.tsv";
+pub const HISTORY_NEW_TABLE_NAME: &str = "history.new.tsv";
+
+pub const TEST_CASE_HEADER: &str =
+	"id\tkey\ttitle\trequirement_ids\tprocedure\tcreation_date\tstatus\tauthor\n";
+pub const TEST_CASE_TABLE_NAME: &str = "test_case.tsv";
+pub const TEST_CASE_OLD_TABLE_NAME: &str = "test_case.old.tsv";
+pub const TEST_CASE_NEW_TABLE_NAME: &str = "test_case.new.tsv";
+pub const TEST_CASE_DRAFT_PREFIX: &str = "test_case_draft";
+pub const TEST_CASE_EDIT_PREFIX: &str = "test_case_edit";
+/// The default test case key prefix, keys are the prefix and the ID plus one e.g. "TC-0001" for ID 0.
+pub const TEST_CASE_KEY_PREFIX: &str = "TC";
+
+pub const TEST_RESULT_HEADER: &str = "id\ttest_case_id\toutcome\trun_date\tevidence\tauthor\n";
+pub const TEST_RESULT_TABLE_NAME: &str = "test_result.tsv";
+pub const TEST_RESULT_OLD_TABLE_NAME: &str = "test_result.old.tsv";
+pub const TEST_RESULT_NEW_TABLE_NAME: &str = "test_result.new.tsv";
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Hash, Display)]
 pub enum RecordStatus {
 	Draft,
 	Accepted,
 	Deleted,
+	Proposed,
+	Approved,
+	Implemented,
+	Verified,
+	Rejected,
+	Obsolete,
+}
+
+/// Requirement lifecycle as (from, allowed next statuses). Anything not listed is refused.
+/// `Accepted` is what requirements were inserted as before the lifecycle existed.
+pub const REQUIREMENT_TRANSITIONS: &[(RecordStatus, &[RecordStatus])] = &[
+	(
+		RecordStatus::Accepted,
+		&[
+			RecordStatus::Proposed,
+			RecordStatus::Approved,
+			RecordStatus::Implemented,
+			RecordStatus::Verified,
+			RecordStatus::Rejected,
+			RecordStatus::Obsolete,
+		],
+	),
+	(
+		RecordStatus::Proposed,
+		&[RecordStatus::Approved, RecordStatus::Rejected],
+	),
+	(
+		RecordStatus::Approved,
+		&[
+			RecordStatus::Implemented,
+			RecordStatus::Proposed,
+			RecordStatus::Obsolete,
+		],
+	),
+	(
+		RecordStatus::Implemented,
+		&[
+			RecordStatus::Verified,
+			RecordStatus::Approved,
+			RecordStatus::Obsolete,
+		],
+	),
+	(
+		RecordStatus::Verified,
+		&[RecordStatus::Implemented, RecordStatus::Obsolete],
+	),
+	(RecordStatus::Rejected, &[RecordStatus::Proposed]),
+];
+
+impl RecordStatus {
+	/// Statuses a requirement in this status may move to.
+	pub fn requirement_transitions(self) -> &'static [RecordStatus] {
+		REQUIREMENT_TRANSITIONS
+			.iter()
+			.find(|(from, _)| *from == self)
+			.map(|(_, to)| *to)
+			.unwrap_or(&[])
+	}
+
+	pub fn can_transition_to(self, to: RecordStatus) -> bool {
+		self.requirement_transitions().contains(&to)
+	}
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Component {
 	pub id: u64,
+	/// Declared once on insert, e.g. "PWR", and used for every requirement key.
+	#[serde(default)]
+	pub prefix: String,
+	/// Next requirement sequence number. Only ever incremented so keys are never reused.
+	#[serde(default)]
+	pub next_seq: u64,
+	/// The component this one is a subsystem of.
+	#[serde(default)]
+	pub parent_id: Option<u64>,
 	pub name: String,
 	pub description: String,
 	pub creation_date: DateTime<Local>,
 	pub status: RecordStatus,
 	pub author: String,
+	#[serde(default)]
+	pub tags: Tags,
 }
 
 #[derive(
@@ -58,6 +174,27 @@ pub enum RequirementFunctional {
 	NonFunctional,
 }
 
+#[derive(
+	Debug,
+	Default,
+	Serialize,
+	Deserialize,
+	Copy,
+	Clone,
+	PartialEq,
+	Eq,
+	EnumString,
+	Display,
+	EnumIter,
+)]
+pub enum VerificationMethod {
+	#[default]
+	Test,
+	Analysis,
+	Inspection,
+	Demonstration,
+}
+
 #[derive(
 	Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Hash, Display, EnumIter,
 )]
@@ -71,16 +208,408 @@ pub enum RequirementPriority {
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Requirement {
 	pub id: u64,
+	/// Durable human-readable key e.g. "PWR-0012". Empty for records made before keys existed.
+	#[serde(default)]
+	pub key: String,
 	pub component_id: u64,
+	/// The requirement this one was decomposed from.
+	#[serde(default)]
+	pub parent_id: Option<u64>,
 	pub title: String,
 	pub functional: RequirementFunctional,
 	pub creation_date: DateTime<Local>,
 	pub requirement_text: String,
+	/// How QA shows the requirement is met.
+	#[serde(default)]
+	pub acceptance_criteria: String,
+	#[serde(default)]
+	pub verification_method: VerificationMethod,
 	pub version: usize,
 	pub author: String,
 	pub priority: RequirementPriority,
 	pub status: RecordStatus,
+	/// Why the requirement was moved to its current status.
+	#[serde(default)]
+	pub status_justification: String,
+	pub risks: String,
+	#[serde(default)]
+	pub tags: Tags,
+	/// Read from and written to the columns after the built in ones, not by serde.
+	#[serde(skip)]
+	pub custom: CustomFields,
+}
+
+pub const TAG_SEPARATOR: char = ',';
+
+/// Free-form labels e.g. "safety" or "v2", stored in one column separated by [`TAG_SEPARATOR`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Tags(pub Vec<String>);
+
+impl Tags {
+	pub fn iter(&self) -> impl Iterator<Item = &str> {
+		self.0.iter().map(String::as_str)
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	pub fn contains(&self, tag: &str) -> bool {
+		self.0.iter().any(|t| t == tag)
+	}
+}
+
+/// Tags can't be empty or hold tabs, new-lines, or the [`TAG_SEPARATOR`].
+pub fn is_valid_tag(tag: &str) -> bool {
+	!tag.trim().is_empty() && !tag.contains(['\t', '\n', '\r', TAG_SEPARATOR])
+}
+
+impl From<Vec<String>> for Tags {
+	fn from(tags: Vec<String>) -> Self {
+		let mut deduped: Vec<String> = Vec::with_capacity(tags.len());
+		for tag in tags.into_iter().map(|t| t.trim().to_string()) {
+			if !tag.is_empty() && !deduped.contains(&tag) {
+				deduped.push(tag);
+			}
+		}
+		Self(deduped)
+	}
+}
+
+impl std::fmt::Display for Tags {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let mut tags = self.0.iter().peekable();
+		while let Some(tag) = tags.next() {
+			write!(f, "{tag}")?;
+			if tags.peek().is_some() {
+				write!(f, "{TAG_SEPARATOR}")?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl Serialize for Tags {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for Tags {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let joined = String::deserialize(deserializer)?;
+		Ok(Tags::from(
+			joined
+				.split(TAG_SEPARATOR)
+				.map(String::from)
+				.collect::<Vec<String>>(),
+		))
+	}
+}
+
+/// How the `from` requirement of a [`Link`] relates to the `to` requirement.
+#[derive(
+	Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash, EnumString, Display, EnumIter,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LinkKind {
+	DerivesFrom,
+	Refines,
+	ConflictsWith,
+	DependsOn,
+}
+
+/// A typed traceability link between two requirements, stored in [`LINK_TABLE_NAME`].
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Link {
+	pub id: u64,
+	pub from_id: u64,
+	pub kind: LinkKind,
+	pub to_id: u64,
+	pub creation_date: DateTime<Local>,
+	pub status: RecordStatus,
+}
+
+/// A requirement as it was before an edit replaced it, stored in [`HISTORY_TABLE_NAME`].
+/// Rows are only ever appended, (requirement_id, version) is unique.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RequirementRevision {
+	pub requirement_id: u64,
+	pub version: usize,
+	pub title: String,
+	pub functional: RequirementFunctional,
+	pub requirement_text: String,
+	pub acceptance_criteria: String,
+	pub verification_method: VerificationMethod,
+	pub author: String,
+	pub priority: RequirementPriority,
 	pub risks: String,
+	pub tags: Tags,
+	/// When the edit that replaced this version was applied.
+	pub superseded_on: DateTime<Local>,
+}
+
+/// Record IDs stored in one column separated by [`TAG_SEPARATOR`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IdList(pub Vec<u64>);
+
+impl IdList {
+	pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+		self.0.iter().copied()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	pub fn contains(&self, id: u64) -> bool {
+		self.0.contains(&id)
+	}
+}
+
+impl From<Vec<u64>> for IdList {
+	fn from(ids: Vec<u64>) -> Self {
+		let mut deduped: Vec<u64> = Vec::with_capacity(ids.len());
+		for id in ids {
+			if !deduped.contains(&id) {
+				deduped.push(id);
+			}
+		}
+		Self(deduped)
+	}
+}
+
+impl std::fmt::Display for IdList {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let mut ids = self.0.iter().peekable();
+		while let Some(id) = ids.next() {
+			write!(f, "{id}")?;
+			if ids.peek().is_some() {
+				write!(f, "{TAG_SEPARATOR}")?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl Serialize for IdList {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for IdList {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let joined = String::deserialize(deserializer)?;
+		joined
+			.split(TAG_SEPARATOR)
+			.map(str::trim)
+			.filter(|id| !id.is_empty())
+			.map(str::parse::<u64>)
+			.collect::<Result<Vec<u64>, _>>()
+			.map(IdList::from)
+			.map_err(serde::de::Error::custom)
+	}
+}
+
+/// A procedure that verifies one or more requirements, stored in [`TEST_CASE_TABLE_NAME`].
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct TestCase {
+	pub id: u64,
+	/// Empty for test cases made before keys were stored, see [`TestCase::key`].
+	#[serde(default)]
+	pub key: String,
+	pub title: String,
+	pub requirement_ids: IdList,
+	pub procedure: String,
+	pub creation_date: DateTime<Local>,
+	pub status: RecordStatus,
+	pub author: String,
+}
+
+impl TestCase {
+	pub fn key(&self) -> Cow<'_, str> {
+		if self.key.is_empty() {
+			Cow::Owned(format_test_case_key(self.id))
+		} else {
+			Cow::Borrowed(&self.key)
+		}
+	}
+
+	pub fn is_active(&self) -> bool {
+		!matches!(self.status, RecordStatus::Deleted)
+	}
+}
+
+/// e.g. "TC-0001" for ID 0, for test cases without a stored key.
+pub fn format_test_case_key(id: u64) -> String {
+	format_requirement_key(TEST_CASE_KEY_PREFIX, id + 1)
+}
+
+#[derive(
+	Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash, EnumString, Display, EnumIter,
+)]
+pub enum TestOutcome {
+	Pass,
+	Fail,
+}
+
+/// One run of a [`TestCase`], stored in [`TEST_RESULT_TABLE_NAME`]. Results are only ever appended.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct TestResult {
+	pub id: u64,
+	pub test_case_id: u64,
+	pub outcome: TestOutcome,
+	pub run_date: DateTime<Local>,
+	/// Where to find logs, reports, or other proof of the run.
+	pub evidence: String,
+	pub author: String,
+}
+
+/// The most recent run of `test_case_id`, ties go to the result recorded last.
+pub fn latest_result(test_case_id: u64, results: &[TestResult]) -> Option<&TestResult> {
+	results
+		.iter()
+		.filter(|r| r.test_case_id == test_case_id)
+		.max_by_key(|r| (r.run_date, r.id))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Display)]
+pub enum VerificationStatus {
+	#[strum(serialize = "Not Covered")]
+	NotCovered,
+	#[strum(serialize = "Not Run")]
+	NotRun,
+	#[strum(serialize = "Failed")]
+	Failed,
+	#[strum(serialize = "Passed")]
+	Passed,
+}
+
+/// Passed only when every active test case covering the requirement passed its latest run,
+/// any latest run failing is Failed, and any test case never run is Not Run.
+pub fn verification_status(
+	requirement_id: u64,
+	test_cases: &[TestCase],
+	results: &[TestResult],
+) -> VerificationStatus {
+	let mut status = VerificationStatus::NotCovered;
+	for test_case in test_cases
+		.iter()
+		.filter(|tc| tc.is_active() && tc.requirement_ids.contains(requirement_id))
+	{
+		match latest_result(test_case.id, results).map(|r| r.outcome) {
+			Some(TestOutcome::Fail) => return VerificationStatus::Failed,
+			None => status = VerificationStatus::NotRun,
+			Some(TestOutcome::Pass) if status == VerificationStatus::NotCovered => {
+				status = VerificationStatus::Passed
+			}
+			Some(TestOutcome::Pass) => {}
+		}
+	}
+	status
+}
+
+impl Link {
+	pub fn is_active(&self) -> bool {
+		!matches!(self.status, RecordStatus::Deleted)
+	}
+}
+
+pub const MAX_KEY_PREFIX_LEN: usize = 8;
+
+/// Formats a requirement key e.g. "PWR-0012".
+pub fn format_requirement_key(prefix: &str, seq: u64) -> String {
+	format!("{prefix}-{seq:04}")
+}
+
+/// A prefix is 1 to [`MAX_KEY_PREFIX_LEN`] upper case ASCII letters or digits, starting with a letter.
+pub fn is_valid_key_prefix(prefix: &str) -> bool {
+	(1..=MAX_KEY_PREFIX_LEN).contains(&prefix.len())
+		&& prefix.starts_with(|ch: char| ch.is_ascii_uppercase())
+		&& prefix
+			.chars()
+			.all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit())
+}
+
+/// Would making `new_parent` the parent of `child` create a cycle?
+/// `parent_of` looks up the current parent of a record by ID.
+pub fn creates_cycle(child: u64, new_parent: u64, parent_of: impl Fn(u64) -> Option<u64>) -> bool {
+	let mut current = Some(new_parent);
+	// a hand edited table could already hold a cycle, treat it the same
+	let mut visited = std::collections::HashSet::new();
+	while let Some(id) = current {
+		if id == child || !visited.insert(id) {
+			return true;
+		}
+		current = parent_of(id);
+	}
+	false
+}
+
+pub const COMPONENT_PATH_SEPARATOR: &str = " / ";
+
+/// `id` then its parent, grandparent, and so on up to the root.
+/// Stops before repeating an ID if a hand edited table holds a cycle.
+pub fn ancestry(id: u64, parent_of: impl Fn(u64) -> Option<u64>) -> Vec<u64> {
+	let mut chain = vec![id];
+	let mut current = parent_of(id);
+	while let Some(id) = current {
+		if chain.contains(&id) {
+			break;
+		}
+		chain.push(id);
+		current = parent_of(id);
+	}
+	chain
+}
+
+/// Component names from the root down to `id` e.g. "Vehicle / Powertrain / Inverter".
+pub fn component_path(id: u64, components: &[Component]) -> String {
+	component_path_by(id, |id| {
+		components
+			.iter()
+			.find(|c| c.id == id)
+			.map(|c| (c.name.as_str(), c.parent_id))
+	})
+}
+
+/// [`component_path`] for components held some other way, `lookup` gives the name and parent
+/// ID of a component.
+pub fn component_path_by<'a>(
+	id: u64,
+	lookup: impl Fn(u64) -> Option<(&'a str, Option<u64>)>,
+) -> String {
+	let chain = ancestry(id, |id| lookup(id).and_then(|(_, parent_id)| parent_id));
+	let names: Vec<&str> = chain
+		.iter()
+		.rev()
+		.filter_map(|id| lookup(*id).map(|(name, _)| name))
+		.collect();
+	names.join(COMPONENT_PATH_SEPARATOR)
+}
+
+impl Component {
+	/// The prefix if declared, otherwise the numeric ID.
+	pub fn key(&self) -> String {
+		if self.prefix.is_empty() {
+			self.id.to_string()
+		} else {
+			self.prefix.clone()
+		}
+	}
+}
+
+impl Requirement {
+	/// The key if allocated, otherwise the numeric ID.
+	pub fn key(&self) -> String {
+		if self.key.is_empty() {
+			self.id.to_string()
+		} else {
+			self.key.clone()
+		}
+	}
 }
 
 #[derive(Error, Debug)]
@@ -135,6 +664,31 @@ pub enum GetProjectRootErr {
 	BadRequirementRecord(#[source] csv::Error),
 	#[error("corrupt Component record: {0:}")]
 	BadComponentRecord(#[source] csv::Error),
+
+	#[error("can't load Links table: {0:}")]
+	LoadLinks(#[source] LoadTableError),
+	#[error("corrupt Link record: {0:}")]
+	BadLinkRecord(#[source] csv::Error),
+
+	#[error("can't load History table: {0:}")]
+	LoadHistory(#[source] LoadTableError),
+	#[error("corrupt History record: {0:}")]
+	BadHistoryRecord(#[source] csv::Error),
+
+	#[error("can't load Test Case table: {0:}")]
+	LoadTestCases(#[source] LoadTableError),
+	#[error("corrupt Test Case record: {0:}")]
+	BadTestCaseRecord(#[source] csv::Error),
+
+	#[error("can't load Test Result table: {0:}")]
+	LoadTestResults(#[source] LoadTableError),
+	#[error("corrupt Test Result record: {0:}")]
+	BadTestResultRecord(#[source] csv::Error),
+
+	#[error("{0:}")]
+	Config(#[source] ProjectConfigErr),
+	#[error("{0:}")]
+	Schema(#[source] SchemaErr),
 }
 
 #[derive(Debug)]
@@ -146,19 +700,63 @@ pub struct Project {
 	pub requirements: Vec<Requirement>,
 	pub raw_requirements: Box<str>,
 	pub requirement_file: File,
+	pub links: Vec<Link>,
+	pub raw_links: Box<str>,
+	/// `None` for projects made before the link table existed.
+	pub link_file: Option<File>,
+	pub history: Vec<RequirementRevision>,
+	pub raw_history: Box<str>,
+	/// `None` for projects made before the history table existed.
+	pub history_file: Option<File>,
+	pub test_cases: Vec<TestCase>,
+	pub raw_test_cases: Box<str>,
+	/// `None` for projects made before the test case table existed.
+	pub test_case_file: Option<File>,
+	pub test_results: Vec<TestResult>,
+	pub raw_test_results: Box<str>,
+	/// `None` for projects made before the test result table existed.
+	pub test_result_file: Option<File>,
 	pub project_title: Box<str>,
+	pub config: ProjectConfig,
+}
+
+impl Project {
+	/// Drops deleted records, and the links, history and test results of them, so they aren't
+	/// built. The raw tables are left as they are.
+	pub fn exclude_deleted(&mut self) {
+		let live = |status: RecordStatus| status != RecordStatus::Deleted;
+		self.components.retain(|c| live(c.status));
+		self.requirements.retain(|r| live(r.status));
+		let requirement_ids: Vec<u64> = self.requirements.iter().map(|r| r.id).collect();
+		self.links.retain(|l| {
+			live(l.status)
+				&& requirement_ids.contains(&l.from_id)
+				&& requirement_ids.contains(&l.to_id)
+		});
+		self.history
+			.retain(|rev| requirement_ids.contains(&rev.requirement_id));
+		self.test_cases.retain(|tc| live(tc.status));
+		let test_case_ids: Vec<u64> = self.test_cases.iter().map(|tc| tc.id).collect();
+		self.test_results
+			.retain(|tr| test_case_ids.contains(&tr.test_case_id));
+	}
 }
 
 pub fn get_project_root(maybe_root: impl AsRef<Path>) -> Result<Project, GetProjectRootErr> {
+	let config = load_project_config(maybe_root.as_ref()).map_err(GetProjectRootErr::Config)?;
+	check_schema_version(&config).map_err(GetProjectRootErr::Schema)?;
+
 	//serialize and verify both tables
 	info!("Loading component table...");
 	let component_tbl_path = maybe_root
 		.as_ref()
-		.join(COMPONENT_TABLE_NAME)
+		.join(&config.tables.component)
 		.into_boxed_path();
 
 	let (component_file, raw_components) = load_table(component_tbl_path, true)
 		.map_err(|lte| GetProjectRootErr::LoadComponents(lte))?;
+	check_header(Table::Component, &config.tables.component, &raw_components)
+		.map_err(GetProjectRootErr::Schema)?;
 
 	let mut tsv_reader = csv::ReaderBuilder::new()
 		.delimiter(COLUMN_DELIMITER)
@@ -183,11 +781,17 @@ pub fn get_project_root(maybe_root: impl AsRef<Path>) -> Result<Project, GetProj
 	info!("Loading requirement table...");
 	let requirement_tbl_path = maybe_root
 		.as_ref()
-		.join(REQUIREMENT_TABLE_NAME)
+		.join(&config.tables.requirement)
 		.into_boxed_path();
 
 	let (requirement_file, raw_requirements) = load_table(requirement_tbl_path, true)
 		.map_err(|lte| GetProjectRootErr::LoadRequirements(lte))?;
+	check_header(
+		Table::Requirement,
+		&config.tables.requirement,
+		&raw_requirements,
+	)
+	.map_err(GetProjectRootErr::Schema)?;
 
 	let mut tsv_reader = csv::ReaderBuilder::new()
 		.delimiter(COLUMN_DELIMITER)
@@ -205,10 +809,143 @@ pub fn get_project_root(maybe_root: impl AsRef<Path>) -> Result<Project, GetProj
 		}
 	}
 
+	let custom = read_custom_columns(&raw_requirements, REQUIREMENT_COLUMNS)
+		.map_err(GetProjectRootErr::BadRequirementRecord)?;
+	for (requirement, custom) in requirements.iter_mut().zip(custom) {
+		requirement.custom = custom;
+	}
+
 	if log_enabled!(Level::Debug) {
 		debug!("found {} requirement records", requirements.len())
 	}
 
+	info!("Loading link table...");
+	let link_tbl_path = maybe_root
+		.as_ref()
+		.join(&config.tables.link)
+		.into_boxed_path();
+
+	let (link_file, raw_links) = if link_tbl_path.exists() {
+		let (file, raw) = load_table(link_tbl_path, true).map_err(GetProjectRootErr::LoadLinks)?;
+		(Some(file), raw)
+	} else {
+		debug!("no link table, using an empty one");
+		(None, Box::from(""))
+	};
+	check_header(Table::Link, &config.tables.link, &raw_links)
+		.map_err(GetProjectRootErr::Schema)?;
+
+	let mut tsv_reader = csv::ReaderBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(b'\n'))
+		.from_reader(raw_links.as_bytes());
+
+	let links: Vec<Link> = tsv_reader
+		.deserialize::<Link>()
+		.collect::<Result<_, _>>()
+		.map_err(GetProjectRootErr::BadLinkRecord)?;
+
+	if log_enabled!(Level::Debug) {
+		debug!("found {} link records", links.len())
+	}
+
+	info!("Loading history table...");
+	let history_tbl_path = maybe_root
+		.as_ref()
+		.join(&config.tables.history)
+		.into_boxed_path();
+
+	let (history_file, raw_history) = if history_tbl_path.exists() {
+		let (file, raw) =
+			load_table(history_tbl_path, true).map_err(GetProjectRootErr::LoadHistory)?;
+		(Some(file), raw)
+	} else {
+		debug!("no history table, using an empty one");
+		(None, Box::from(""))
+	};
+	check_header(Table::History, &config.tables.history, &raw_history)
+		.map_err(GetProjectRootErr::Schema)?;
+
+	let mut tsv_reader = csv::ReaderBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(b'\n'))
+		.from_reader(raw_history.as_bytes());
+
+	let history: Vec<RequirementRevision> = tsv_reader
+		.deserialize::<RequirementRevision>()
+		.collect::<Result<_, _>>()
+		.map_err(GetProjectRootErr::BadHistoryRecord)?;
+
+	if log_enabled!(Level::Debug) {
+		debug!("found {} history records", history.len())
+	}
+
+	info!("Loading test case table...");
+	let test_case_tbl_path = maybe_root
+		.as_ref()
+		.join(&config.tables.test_case)
+		.into_boxed_path();
+
+	let (test_case_file, raw_test_cases) = if test_case_tbl_path.exists() {
+		let (file, raw) =
+			load_table(test_case_tbl_path, true).map_err(GetProjectRootErr::LoadTestCases)?;
+		(Some(file), raw)
+	} else {
+		debug!("no test case table, using an empty one");
+		(None, Box::from(""))
+	};
+	check_header(Table::TestCase, &config.tables.test_case, &raw_test_cases)
+		.map_err(GetProjectRootErr::Schema)?;
+
+	let mut tsv_reader = csv::ReaderBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(b'\n'))
+		.from_reader(raw_test_cases.as_bytes());
+
+	let test_cases: Vec<TestCase> = tsv_reader
+		.deserialize::<TestCase>()
+		.collect::<Result<_, _>>()
+		.map_err(GetProjectRootErr::BadTestCaseRecord)?;
+
+	if log_enabled!(Level::Debug) {
+		debug!("found {} test case records", test_cases.len())
+	}
+
+	info!("Loading test result table...");
+	let test_result_tbl_path = maybe_root
+		.as_ref()
+		.join(&config.tables.test_result)
+		.into_boxed_path();
+
+	let (test_result_file, raw_test_results) = if test_result_tbl_path.exists() {
+		let (file, raw) =
+			load_table(test_result_tbl_path, true).map_err(GetProjectRootErr::LoadTestResults)?;
+		(Some(file), raw)
+	} else {
+		debug!("no test result table, using an empty one");
+		(None, Box::from(""))
+	};
+	check_header(
+		Table::TestResult,
+		&config.tables.test_result,
+		&raw_test_results,
+	)
+	.map_err(GetProjectRootErr::Schema)?;
+
+	let mut tsv_reader = csv::ReaderBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(b'\n'))
+		.from_reader(raw_test_results.as_bytes());
+
+	let test_results: Vec<TestResult> = tsv_reader
+		.deserialize::<TestResult>()
+		.collect::<Result<_, _>>()
+		.map_err(GetProjectRootErr::BadTestResultRecord)?;
+
+	if log_enabled!(Level::Debug) {
+		debug!("found {} test result records", test_results.len())
+	}
+
 	let root: Box<Path> = Box::from(maybe_root.as_ref());
 
 	info!("Loaded project @: {:?}", &root);
@@ -220,8 +957,20 @@ pub fn get_project_root(maybe_root: impl AsRef<Path>) -> Result<Project, GetProj
 		requirement_file,
 		raw_components,
 		raw_requirements,
-		// TODO: Need reqtsv.toml
-		project_title: format!("TODO Placeholder Title").into_boxed_str(),
+		links,
+		raw_links,
+		link_file,
+		history,
+		raw_history,
+		history_file,
+		test_cases,
+		raw_test_cases,
+		test_case_file,
+		test_results,
+		raw_test_results,
+		test_result_file,
+		project_title: config.title.clone().into_boxed_str(),
+		config,
 	})
 }
 
@@ -268,4 +1017,162 @@ fn load_table(
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+	use super::*;
+	use chrono::TimeZone;
+
+	#[test]
+	fn test_requirement_transitions() {
+		assert!(RecordStatus::Proposed.can_transition_to(RecordStatus::Approved));
+		assert!(RecordStatus::Implemented.can_transition_to(RecordStatus::Verified));
+		assert!(RecordStatus::Rejected.can_transition_to(RecordStatus::Proposed));
+		assert!(!RecordStatus::Proposed.can_transition_to(RecordStatus::Verified));
+		assert!(!RecordStatus::Proposed.can_transition_to(RecordStatus::Proposed));
+		assert!(!RecordStatus::Verified.can_transition_to(RecordStatus::Deleted));
+		assert!(RecordStatus::Obsolete.requirement_transitions().is_empty());
+		assert!(RecordStatus::Deleted.requirement_transitions().is_empty());
+	}
+
+	#[test]
+	fn test_ancestry() {
+		let parents = [(1, 0), (2, 1), (3, 2)];
+		let parent_of = |id: u64| parents.iter().find(|(c, _)| *c == id).map(|(_, p)| *p);
+		assert_eq!(vec![3, 2, 1, 0], ancestry(3, parent_of));
+		assert_eq!(vec![0], ancestry(0, parent_of));
+
+		// a cycle stops at the first repeat
+		let parents = [(0, 1), (1, 0)];
+		let parent_of = |id: u64| parents.iter().find(|(c, _)| *c == id).map(|(_, p)| *p);
+		assert_eq!(vec![0, 1], ancestry(0, parent_of));
+	}
+
+	#[test]
+	fn test_component_path() {
+		let component = |id: u64, name: &str, parent_id: Option<u64>| Component {
+			id,
+			prefix: String::new(),
+			next_seq: 0,
+			parent_id,
+			name: name.into(),
+			description: String::new(),
+			creation_date: Local::now(),
+			status: RecordStatus::Accepted,
+			author: String::new(),
+			tags: Tags::default(),
+		};
+		let components = [
+			component(0, "Vehicle", None),
+			component(1, "Powertrain", Some(0)),
+			component(2, "Inverter", Some(1)),
+		];
+		assert_eq!(
+			"Vehicle / Powertrain / Inverter",
+			component_path(2, &components)
+		);
+		assert_eq!("Vehicle", component_path(0, &components));
+		// a missing parent ends the path
+		assert_eq!("Inverter", component_path(2, &components[2..]));
+	}
+
+	#[test]
+	fn test_format_requirement_key() {
+		assert_eq!("PWR-0012", format_requirement_key("PWR", 12));
+		assert_eq!("PWR-12345", format_requirement_key("PWR", 12345));
+	}
+
+	#[test]
+	fn test_creates_cycle() {
+		// 0 <- 1 <- 2, 3 has no parent
+		let parents = [None, Some(0), Some(1), None];
+		let parent_of = |id: u64| parents.get(id as usize).copied().flatten();
+		assert!(creates_cycle(0, 2, parent_of));
+		assert!(creates_cycle(1, 1, parent_of));
+		assert!(!creates_cycle(3, 2, parent_of));
+		assert!(!creates_cycle(2, 0, parent_of));
+	}
+
+	#[test]
+	fn test_tags() {
+		let tags = Tags::from(vec![
+			" safety".to_string(),
+			"ui".to_string(),
+			"".to_string(),
+			"safety".to_string(),
+		]);
+		assert_eq!("safety,ui", tags.to_string());
+		assert!(is_valid_tag("v2"));
+		assert!(!is_valid_tag("a,b"));
+		assert!(!is_valid_tag("a\tb"));
+		assert!(!is_valid_tag(" "));
+	}
+
+	#[test]
+	fn test_key_prefix() {
+		assert!(is_valid_key_prefix("PWR"));
+		assert!(is_valid_key_prefix("UI2"));
+		assert!(!is_valid_key_prefix(""));
+		assert!(!is_valid_key_prefix("2UI"));
+		assert!(!is_valid_key_prefix("pwr"));
+		assert!(!is_valid_key_prefix("PW-R"));
+		assert!(!is_valid_key_prefix("ABCDEFGHI"));
+	}
+	#[test]
+	fn test_verification_status() {
+		let date = |day| Local.with_ymd_and_hms(2025, 6, day, 0, 0, 0).unwrap();
+		let test_case = |id, requirement_ids: Vec<u64>| TestCase {
+			id,
+			key: String::new(),
+			title: format!("Test {id}"),
+			requirement_ids: IdList::from(requirement_ids),
+			procedure: String::new(),
+			creation_date: date(1),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+		};
+		let result = |id, test_case_id, outcome, day| TestResult {
+			id,
+			test_case_id,
+			outcome,
+			run_date: date(day),
+			evidence: String::new(),
+			author: "Author A".into(),
+		};
+		let test_cases = [
+			test_case(0, vec![0, 1]),
+			test_case(1, vec![1]),
+			test_case(2, vec![2]),
+		];
+		let results = [
+			result(0, 0, TestOutcome::Fail, 2),
+			result(1, 0, TestOutcome::Pass, 3),
+			result(2, 1, TestOutcome::Pass, 4),
+			result(3, 1, TestOutcome::Fail, 3),
+		];
+		assert_eq!(
+			VerificationStatus::Passed,
+			verification_status(0, &test_cases, &results)
+		);
+		assert_eq!(
+			VerificationStatus::Passed,
+			verification_status(1, &test_cases, &results)
+		);
+		assert_eq!(
+			VerificationStatus::NotRun,
+			verification_status(2, &test_cases, &results)
+		);
+		assert_eq!(
+			VerificationStatus::NotCovered,
+			verification_status(3, &test_cases, &results)
+		);
+		let results = [
+			result(0, 0, TestOutcome::Pass, 2),
+			result(1, 1, TestOutcome::Fail, 2),
+		];
+		assert_eq!(
+			VerificationStatus::Failed,
+			verification_status(1, &test_cases, &results)
+		);
+		assert_eq!("0,1", test_cases[0].requirement_ids.to_string());
+		assert_eq!("TC-0003", test_cases[2].key());
+	}
+}
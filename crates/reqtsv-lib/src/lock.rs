@@ -0,0 +1,293 @@
+use std::{
+	fs::OpenOptions,
+	io::{ErrorKind, Write},
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+use chrono::{DateTime, Local};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Held in the project root while a session can write to the tables.
+pub const PROJECT_LOCK_NAME: &str = "reqtsv.lock";
+
+/// How long an empty or unparsable lock is taken to be one another session is still writing.
+pub const UNREADABLE_LOCK_GRACE: Duration = Duration::from_secs(10);
+
+/// Who holds the lock, written to [`PROJECT_LOCK_NAME`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LockInfo {
+	pub pid: u32,
+	pub host: String,
+	pub user: String,
+	pub started: DateTime<Local>,
+}
+
+impl LockInfo {
+	pub fn current() -> Self {
+		Self {
+			pid: std::process::id(),
+			host: host_name(),
+			user: user_name(),
+			started: Local::now(),
+		}
+	}
+
+	/// Only a lock from this host with a process that is gone is known to be stale, a lock from
+	/// another host is assumed to be live.
+	pub fn is_stale(&self) -> bool {
+		self.host == host_name() && !process_is_alive(self.pid)
+	}
+}
+
+impl std::fmt::Display for LockInfo {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{}@{} (PID: {}) since {}",
+			self.user, self.host, self.pid, self.started
+		)
+	}
+}
+
+#[derive(Error, Debug)]
+pub enum LockErr {
+	#[error("project is locked by {0}")]
+	Held(LockInfo),
+	#[error(
+		"project is being locked by another session, the lock file at: {0:?} can't be read yet"
+	)]
+	Busy(PathBuf),
+	#[error("can't create lock file at: {0:?} due to: {1}")]
+	Create(PathBuf, #[source] std::io::Error),
+	#[error("can't read lock file at: {0:?} due to: {1}")]
+	Read(PathBuf, #[source] std::io::Error),
+	#[error("can't remove stale lock file at: {0:?} due to: {1}")]
+	RemoveStale(PathBuf, #[source] std::io::Error),
+}
+
+/// The lock on a project, the lock file is removed on drop.
+#[derive(Debug)]
+pub struct ProjectLock {
+	path: PathBuf,
+}
+
+impl ProjectLock {
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+}
+
+impl Drop for ProjectLock {
+	fn drop(&mut self) {
+		if let Err(e) = std::fs::remove_file(&self.path) {
+			warn!("can't remove lock file at: {:?} due to: {e}", &self.path);
+		}
+	}
+}
+
+/// Takes the lock on `project_root`, clearing a stale lock first.
+pub fn acquire_lock(project_root: &Path) -> Result<ProjectLock, LockErr> {
+	let path = project_root.join(PROJECT_LOCK_NAME);
+	if path.exists() {
+		let held = read_lock(&path)?;
+		match &held {
+			Some(held) if !held.is_stale() => return Err(LockErr::Held(held.clone())),
+			Some(held) => warn!("clearing stale lock held by {held}"),
+			// another session may be between creating and writing it
+			None if !grace_elapsed(&path)? => return Err(LockErr::Busy(path)),
+			None => warn!("clearing unreadable lock file at: {:?}", &path),
+		}
+		// another session may have cleared the stale lock and taken its own since it was read
+		match read_lock(&path) {
+			Ok(again) if again == held => match std::fs::remove_file(&path) {
+				Err(e) if e.kind() != ErrorKind::NotFound => {
+					return Err(LockErr::RemoveStale(path, e));
+				}
+				_ => {}
+			},
+			Ok(Some(again)) => return Err(LockErr::Held(again)),
+			Ok(None) => return Err(LockErr::Busy(path)),
+			Err(_) if !path.exists() => {}
+			Err(e) => return Err(e),
+		}
+	}
+
+	let info = LockInfo::current();
+	let raw = toml::to_string(&info).expect("lock info is always valid TOML");
+	// written in full beside the lock then linked into place, so the lock is never seen half
+	// written and a lock taken by another session in the meantime is never replaced
+	let tmp_path = project_root.join(format!("{PROJECT_LOCK_NAME}.{}.tmp", info.pid));
+	write_new_lock(&tmp_path, &raw, false)?;
+	let linked = std::fs::hard_link(&tmp_path, &path);
+	if let Err(e) = std::fs::remove_file(&tmp_path) {
+		warn!("can't remove {:?} due to: {e}", &tmp_path);
+	}
+	match linked {
+		Ok(()) => {}
+		Err(e) if e.kind() == ErrorKind::AlreadyExists => return Err(lock_taken(&path, e)),
+		// file systems without hard links, create_new still refuses an existing lock
+		Err(e) => {
+			debug!("can't link lock file into place due to: {e}");
+			write_new_lock(&path, &raw, true)?;
+		}
+	}
+	debug!("locked project with: {info}");
+	Ok(ProjectLock { path })
+}
+
+fn write_new_lock(path: &Path, raw: &str, create_new: bool) -> Result<(), LockErr> {
+	let mut file = OpenOptions::new()
+		.write(true)
+		.truncate(!create_new)
+		.create(!create_new)
+		.create_new(create_new)
+		.open(path)
+		.map_err(|e| match e.kind() {
+			ErrorKind::AlreadyExists => lock_taken(path, e),
+			_ => LockErr::Create(path.into(), e),
+		})?;
+	file.write_all(raw.as_bytes())
+		.and_then(|_| file.sync_all())
+		.map_err(|e| LockErr::Create(path.into(), e))
+}
+
+/// The error for a lock another session took first.
+fn lock_taken(path: &Path, e: std::io::Error) -> LockErr {
+	match read_lock(path) {
+		Ok(Some(held)) => LockErr::Held(held),
+		Ok(None) => LockErr::Busy(path.into()),
+		Err(_) => LockErr::Create(path.into(), e),
+	}
+}
+
+/// An unreadable lock older than [`UNREADABLE_LOCK_GRACE`] is left over from a dead session.
+fn grace_elapsed(path: &Path) -> Result<bool, LockErr> {
+	let modified = std::fs::metadata(path)
+		.and_then(|m| m.modified())
+		.map_err(|e| LockErr::Read(path.into(), e))?;
+	Ok(modified
+		.elapsed()
+		.map(|age| age >= UNREADABLE_LOCK_GRACE)
+		.unwrap_or(false))
+}
+
+/// `None` when the lock file can't be parsed e.g. a session died while writing it.
+pub fn read_lock(path: &Path) -> Result<Option<LockInfo>, LockErr> {
+	let raw = std::fs::read_to_string(path).map_err(|e| LockErr::Read(path.into(), e))?;
+	Ok(toml::from_str(&raw).ok())
+}
+
+fn host_name() -> String {
+	std::env::var("HOSTNAME")
+		.ok()
+		.or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+		.or_else(|| std::env::var("COMPUTERNAME").ok())
+		.map(|h| h.trim().to_string())
+		.filter(|h| !h.is_empty())
+		.unwrap_or_else(|| "unknown".into())
+}
+
+fn user_name() -> String {
+	std::env::var("USER")
+		.or_else(|_| std::env::var("USERNAME"))
+		.unwrap_or_else(|_| "unknown".into())
+}
+
+#[cfg(target_os = "linux")]
+fn process_is_alive(pid: u32) -> bool {
+	Path::new("/proc").join(pid.to_string()).exists()
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_is_alive(pid: u32) -> bool {
+	std::process::Command::new("kill")
+		.arg("-0")
+		.arg(pid.to_string())
+		.stderr(std::process::Stdio::null())
+		.status()
+		.map(|s| s.success())
+		.unwrap_or(true)
+}
+
+/// No portable way to tell, so never treat the lock as stale.
+#[cfg(not(unix))]
+fn process_is_alive(_pid: u32) -> bool {
+	true
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_acquire_lock() {
+		let root = std::env::temp_dir().join(format!("reqtsv-lock-test-{}", std::process::id()));
+		std::fs::create_dir_all(&root).unwrap();
+		let lock_path = root.join(PROJECT_LOCK_NAME);
+
+		let lock = acquire_lock(&root).unwrap();
+		assert!(lock_path.exists());
+		assert!(matches!(acquire_lock(&root), Err(LockErr::Held(_))));
+		drop(lock);
+		assert!(!lock_path.exists());
+
+		// a dead process on this host
+		let stale = LockInfo {
+			pid: u32::MAX,
+			..LockInfo::current()
+		};
+		std::fs::write(&lock_path, toml::to_string(&stale).unwrap()).unwrap();
+		let lock = acquire_lock(&root).unwrap();
+		assert_eq!(
+			Some(std::process::id()),
+			read_lock(&lock_path).unwrap().map(|l| l.pid)
+		);
+		drop(lock);
+
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn test_acquire_unreadable_lock() {
+		let root = std::env::temp_dir().join(format!(
+			"reqtsv-lock-unreadable-test-{}",
+			std::process::id()
+		));
+		std::fs::create_dir_all(&root).unwrap();
+		let lock_path = root.join(PROJECT_LOCK_NAME);
+
+		// created but not written yet by another session
+		std::fs::write(&lock_path, "").unwrap();
+		assert!(matches!(acquire_lock(&root), Err(LockErr::Busy(_))));
+		assert_eq!("", std::fs::read_to_string(&lock_path).unwrap());
+
+		// left behind by a session that died before writing it
+		let old = std::time::SystemTime::now() - UNREADABLE_LOCK_GRACE * 2;
+		std::fs::File::options()
+			.write(true)
+			.open(&lock_path)
+			.unwrap()
+			.set_modified(old)
+			.unwrap();
+		let lock = acquire_lock(&root).unwrap();
+		assert_eq!(
+			Some(std::process::id()),
+			read_lock(&lock_path).unwrap().map(|l| l.pid)
+		);
+		drop(lock);
+
+		// only the lock is left while held
+		let lock = acquire_lock(&root).unwrap();
+		let files: Vec<_> = std::fs::read_dir(&root)
+			.unwrap()
+			.map(|entry| entry.unwrap().file_name())
+			.collect();
+		assert_eq!(vec![std::ffi::OsString::from(PROJECT_LOCK_NAME)], files);
+		drop(lock);
+
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+}
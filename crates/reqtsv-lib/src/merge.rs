@@ -0,0 +1,475 @@
+//! Three-way merge of a table by record, so two branches that each change a table don't conflict
+//! on whole lines. Used as a git merge driver, in `.gitattributes`:
+//!
+//! ```text
+//! *.tsv merge=reqtsv
+//! id-map-*.tsv merge=text
+//! ```
+//!
+//! The id-map files re-numbering writes have no "id" column, so they're merged as text. And in
+//! `.git/config`:
+//!
+//! ```text
+//! [merge "reqtsv"]
+//!     name = reqtsv record merge
+//!     driver = reqtsv merge-driver %O %A %B
+//! ```
+//!
+//! Records are matched by their "id" column, or by "requirement_id" and "version" in the history
+//! table. A record both sides added with the same ID is given the next free ID on their side, and
+//! their "parent_id"s that pointed at it are moved with it. References from other tables aren't
+//! rewritten, so the driver fails when a record was renumbered. A field both sides changed to
+//! different values is written as "<<<<<<< ours ||||||| base ======= theirs >>>>>>>", and a "key"
+//! both sides handed out is reported.
+
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+use crate::{COLUMN_DELIMITER, RECORD_DELIMITER};
+
+pub const CONFLICT_START: &str = "<<<<<<< ";
+pub const CONFLICT_BASE: &str = " ||||||| ";
+pub const CONFLICT_SEPARATOR: &str = " ======= ";
+pub const CONFLICT_END: &str = " >>>>>>>";
+
+/// Key columns of the history table, which has no ID.
+const REVISION_KEY: [&str; 2] = ["requirement_id", "version"];
+const ID_COLUMN: &str = "id";
+const PARENT_ID_COLUMN: &str = "parent_id";
+/// Durable keys e.g. "NAV-0004", unique in a table.
+const KEY_COLUMN: &str = "key";
+
+#[derive(Error, Debug)]
+pub enum MergeErr {
+	#[error("can't read {0} table due to: {1}")]
+	Read(Side, #[source] csv::Error),
+	#[error("{0} table has no \"id\" column")]
+	NoKey(Side),
+	#[error("{side} table has a record with a bad {column:?}: {value:?}")]
+	BadKey {
+		side: Side,
+		column: &'static str,
+		value: String,
+	},
+	#[error("the columns of our and their tables differ, migrate both to the same schema")]
+	Header,
+	#[error("can't write merged table due to: {0}")]
+	Write(#[source] csv::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum Side {
+	#[strum(serialize = "base")]
+	Base,
+	#[strum(serialize = "our")]
+	Ours,
+	#[strum(serialize = "their")]
+	Theirs,
+}
+
+/// What needs a person to look at it after a merge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+	/// Marked in the merged table.
+	Field { key: String, column: String },
+	/// The changed record is kept.
+	ChangedAndRemoved { key: String, removed_by: Side },
+	/// More than one record has the durable key `key`, each side handed it out.
+	DuplicateKey { key: String, ids: Vec<String> },
+}
+
+impl std::fmt::Display for Conflict {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Conflict::Field { key, column } => {
+				write!(f, "record: {key} column: {column} changed on both sides")
+			}
+			Conflict::ChangedAndRemoved { key, removed_by } => write!(
+				f,
+				"record: {key} was changed on one side and removed on {removed_by} side"
+			),
+			Conflict::DuplicateKey { key, ids } => {
+				write!(f, "key: {key} is used by records: {}", ids.join(", "))
+			}
+		}
+	}
+}
+
+/// A record added on their side moved to a free ID. Their "parent_id"s in the same table are
+/// rewritten but other tables aren't, so links, history and test cases that refer to `old_id` on
+/// their side need updating by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Renumbered {
+	pub old_id: u64,
+	pub new_id: u64,
+}
+
+#[derive(Debug)]
+pub struct Merged {
+	pub table: Vec<u8>,
+	pub conflicts: Vec<Conflict>,
+	pub renumbered: Vec<Renumbered>,
+}
+
+struct ParsedTable {
+	header: Vec<String>,
+	/// By key, the record's fields are in the order of `header`.
+	records: BTreeMap<Vec<u64>, Vec<String>>,
+}
+
+impl ParsedTable {
+	/// The value of `column` in `record`, `None` if the table doesn't have the column.
+	fn field<'r>(&self, record: &'r [String], column: &str) -> Option<&'r str> {
+		self.header
+			.iter()
+			.position(|c| c == column)
+			.and_then(|i| record.get(i))
+			.map(String::as_str)
+	}
+}
+
+fn key_columns(header: &[String]) -> Option<Vec<&'static str>> {
+	if header.iter().any(|c| c == ID_COLUMN) {
+		Some(vec![ID_COLUMN])
+	} else if REVISION_KEY.iter().all(|k| header.iter().any(|c| c == k)) {
+		Some(REVISION_KEY.to_vec())
+	} else {
+		None
+	}
+}
+
+/// An empty table, e.g. the base of a table added on both sides, has no records.
+fn parse(raw: &str, side: Side) -> Result<ParsedTable, MergeErr> {
+	let mut tsv_reader = csv::ReaderBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(RECORD_DELIMITER))
+		.from_reader(raw.as_bytes());
+	let header: Vec<String> = tsv_reader
+		.headers()
+		.map_err(|e| MergeErr::Read(side, e))?
+		.iter()
+		.map(String::from)
+		.collect();
+	let mut records = BTreeMap::new();
+	if raw.trim().is_empty() {
+		return Ok(ParsedTable { header, records });
+	}
+	let key_columns = key_columns(&header).ok_or(MergeErr::NoKey(side))?;
+	let key_indexes: Vec<(&'static str, usize)> = key_columns
+		.iter()
+		.map(|k| (*k, header.iter().position(|c| c == k).expect("found above")))
+		.collect();
+	for record in tsv_reader.records() {
+		let record = record.map_err(|e| MergeErr::Read(side, e))?;
+		let mut key = Vec::with_capacity(key_indexes.len());
+		for (column, i) in key_indexes.iter() {
+			let value = record.get(*i).unwrap_or_default();
+			key.push(value.parse::<u64>().map_err(|_| MergeErr::BadKey {
+				side,
+				column,
+				value: value.to_string(),
+			})?);
+		}
+		records.insert(key, record.iter().map(String::from).collect());
+	}
+	Ok(ParsedTable { header, records })
+}
+
+fn fmt_key(key: &[u64]) -> String {
+	key.iter()
+		.map(u64::to_string)
+		.collect::<Vec<String>>()
+		.join(" v")
+}
+
+/// Merges the fields of a record both sides have, `base` is `None` when both sides added it.
+fn merge_record(
+	key: &[u64],
+	header: &[String],
+	base: Option<(&ParsedTable, &[String])>,
+	ours: &[String],
+	theirs: &[String],
+	conflicts: &mut Vec<Conflict>,
+) -> Vec<String> {
+	header
+		.iter()
+		.zip(ours.iter().zip(theirs))
+		.map(|(column, (o, t))| {
+			let b = base.and_then(|(table, record)| table.field(record, column));
+			if o == t || b == Some(t.as_str()) {
+				o.clone()
+			} else if b == Some(o.as_str()) {
+				t.clone()
+			} else {
+				conflicts.push(Conflict::Field {
+					key: fmt_key(key),
+					column: column.clone(),
+				});
+				format!(
+					"{CONFLICT_START}{o}{CONFLICT_BASE}{}{CONFLICT_SEPARATOR}{t}{CONFLICT_END}",
+					b.unwrap_or_default()
+				)
+			}
+		})
+		.collect()
+}
+
+/// Merges the raw tables `ours` and `theirs` that both came from `base`, the merged table is
+/// written with the columns of `ours`.
+pub fn merge_tables(base: &str, ours: &str, theirs: &str) -> Result<Merged, MergeErr> {
+	let base = parse(base, Side::Base)?;
+	let ours = parse(ours, Side::Ours)?;
+	let mut theirs = parse(theirs, Side::Theirs)?;
+	if ours.header != theirs.header {
+		return Err(MergeErr::Header);
+	}
+	let header = ours.header.clone();
+	let id_index = header.iter().position(|c| c == ID_COLUMN);
+
+	// both added a different record at the same ID, theirs moves past every ID in use
+	let mut renumbered = Vec::new();
+	if let Some(id_index) = id_index {
+		let first_free_id = [&base, &ours, &theirs]
+			.iter()
+			.flat_map(|t| t.records.keys())
+			.map(|key| key[0] + 1)
+			.max()
+			.unwrap_or(0);
+		let colliding: Vec<Vec<u64>> = theirs
+			.records
+			.iter()
+			.filter(|(key, record)| {
+				!base.records.contains_key(*key)
+					&& ours.records.get(*key).is_some_and(|o| o != *record)
+			})
+			.map(|(key, _)| key.clone())
+			.collect();
+		for (key, new_id) in colliding.into_iter().zip(first_free_id..) {
+			let mut record = theirs.records.remove(&key).expect("found above");
+			record[id_index] = new_id.to_string();
+			theirs.records.insert(vec![new_id], record);
+			renumbered.push(Renumbered {
+				old_id: key[0],
+				new_id,
+			});
+		}
+	}
+	// their records only know the old ID of their record
+	if let Some(parent_index) = header.iter().position(|c| c == PARENT_ID_COLUMN) {
+		for record in theirs.records.values_mut() {
+			if let Some(moved) = renumbered
+				.iter()
+				.find(|r| record[parent_index] == r.old_id.to_string())
+			{
+				record[parent_index] = moved.new_id.to_string();
+			}
+		}
+	}
+
+	let mut conflicts = Vec::new();
+	let mut merged: BTreeMap<&Vec<u64>, Vec<String>> = BTreeMap::new();
+	for key in ours.records.keys().chain(theirs.records.keys()) {
+		if merged.contains_key(key) {
+			continue;
+		}
+		let b = base.records.get(key);
+		let record = match (ours.records.get(key), theirs.records.get(key)) {
+			(Some(o), Some(t)) => merge_record(
+				key,
+				&header,
+				b.map(|b| (&base, b.as_slice())),
+				o,
+				t,
+				&mut conflicts,
+			),
+			// the other side removed it
+			(Some(kept), None) | (None, Some(kept)) if b.is_some() => {
+				let removed_by = match ours.records.contains_key(key) {
+					true => Side::Theirs,
+					false => Side::Ours,
+				};
+				let unchanged = header
+					.iter()
+					.zip(kept)
+					.all(|(column, value)| base.field(b.unwrap(), column) == Some(value));
+				if unchanged {
+					continue;
+				}
+				conflicts.push(Conflict::ChangedAndRemoved {
+					key: fmt_key(key),
+					removed_by,
+				});
+				kept.clone()
+			}
+			(Some(added), None) | (None, Some(added)) => added.clone(),
+			(None, None) => unreachable!("key is from one of the sides"),
+		};
+		merged.insert(key, record);
+	}
+
+	// IDs can be renumbered but keys are cited outside the tables
+	if let Some(key_index) = header.iter().position(|c| c == KEY_COLUMN) {
+		let mut by_key: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+		for (key, record) in merged.iter() {
+			if !record[key_index].is_empty() {
+				by_key
+					.entry(record[key_index].as_str())
+					.or_default()
+					.push(fmt_key(key));
+			}
+		}
+		conflicts.extend(
+			by_key
+				.into_iter()
+				.filter(|(_, ids)| ids.len() > 1)
+				.map(|(key, ids)| Conflict::DuplicateKey {
+					key: key.to_string(),
+					ids,
+				}),
+		);
+	}
+
+	let mut wtr = csv::WriterBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(RECORD_DELIMITER))
+		.from_writer(Vec::new());
+	wtr.write_record(&header).map_err(MergeErr::Write)?;
+	for record in merged.values() {
+		wtr.write_record(record).map_err(MergeErr::Write)?;
+	}
+	let table = wtr
+		.into_inner()
+		.map_err(|e| MergeErr::Write(csv::Error::from(e.into_error())))?;
+	Ok(Merged {
+		table,
+		conflicts,
+		renumbered,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const HEADER: &str = "id\ttitle\tstatus\n";
+
+	fn table(rows: &[&str]) -> String {
+		let mut raw = HEADER.to_string();
+		for row in rows {
+			raw.push_str(row);
+			raw.push('\n');
+		}
+		raw
+	}
+
+	#[test]
+	fn test_merge_tables() {
+		let base = table(&["0\tA\tDraft", "1\tB\tDraft", "2\tC\tDraft"]);
+		// both add ID 3, ours accepts 0 and renames 1, theirs renames 1 too and removes 2
+		let ours = table(&[
+			"0\tA\tAccepted",
+			"1\tB1\tDraft",
+			"2\tC\tDraft",
+			"3\tD\tDraft",
+		]);
+		let theirs = table(&["0\tA\tDraft", "1\tB2\tDraft", "3\tE\tDraft"]);
+
+		let merged = merge_tables(&base, &ours, &theirs).unwrap();
+		assert_eq!(
+			table(&[
+				"0\tA\tAccepted",
+				"1\t<<<<<<< B1 ||||||| B ======= B2 >>>>>>>\tDraft",
+				"3\tD\tDraft",
+				"4\tE\tDraft",
+			]),
+			String::from_utf8(merged.table).unwrap()
+		);
+		assert_eq!(
+			vec![Conflict::Field {
+				key: "1".into(),
+				column: "title".into()
+			}],
+			merged.conflicts
+		);
+		assert_eq!(
+			vec![Renumbered {
+				old_id: 3,
+				new_id: 4
+			}],
+			merged.renumbered
+		);
+
+		// the same record added on both sides is kept once
+		let merged = merge_tables("", &table(&["0\tA\tDraft"]), &table(&["0\tA\tDraft"])).unwrap();
+		assert_eq!(
+			table(&["0\tA\tDraft"]),
+			String::from_utf8(merged.table).unwrap()
+		);
+		assert!(merged.renumbered.is_empty());
+
+		// changed on one side and removed on the other is kept and reported
+		let changed = table(&["0\tA\tDraft", "1\tB\tDraft", "2\tC\tAccepted"]);
+		let removed = table(&["0\tA\tDraft", "1\tB\tDraft"]);
+		let merged = merge_tables(&base, &removed, &changed).unwrap();
+		assert_eq!(changed, String::from_utf8(merged.table).unwrap());
+		assert_eq!(
+			vec![Conflict::ChangedAndRemoved {
+				key: "2".into(),
+				removed_by: Side::Ours,
+			}],
+			merged.conflicts
+		);
+	}
+
+	#[test]
+	fn test_merge_keys_and_parents() {
+		let header = "id\tkey\tparent_id\ttitle\n";
+		let base = format!("{header}0\tNAV-0001\t\tA\n");
+		// both add a child of 0 at ID 1 with the same key, theirs adds a child of their 1 too
+		let ours = format!("{base}1\tNAV-0002\t0\tB\n");
+		let theirs = format!("{base}1\tNAV-0002\t0\tC\n2\tNAV-0003\t1\tD\n");
+		let merged = merge_tables(&base, &ours, &theirs).unwrap();
+		assert_eq!(
+			format!("{base}1\tNAV-0002\t0\tB\n2\tNAV-0003\t3\tD\n3\tNAV-0002\t0\tC\n"),
+			String::from_utf8(merged.table).unwrap()
+		);
+		assert_eq!(
+			vec![Renumbered {
+				old_id: 1,
+				new_id: 3
+			}],
+			merged.renumbered
+		);
+		assert_eq!(
+			vec![Conflict::DuplicateKey {
+				key: "NAV-0002".into(),
+				ids: vec!["1".into(), "3".into()],
+			}],
+			merged.conflicts
+		);
+		assert_eq!(
+			"key: NAV-0002 is used by records: 1, 3",
+			merged.conflicts[0].to_string()
+		);
+	}
+
+	#[test]
+	fn test_merge_history() {
+		let header = "requirement_id\tversion\ttitle\n";
+		let base = format!("{header}0\t1\tA\n");
+		let ours = format!("{header}0\t1\tA\n0\t2\tB\n");
+		let theirs = format!("{header}0\t1\tA\n1\t1\tC\n");
+		let merged = merge_tables(&base, &ours, &theirs).unwrap();
+		assert_eq!(
+			format!("{header}0\t1\tA\n0\t2\tB\n1\t1\tC\n"),
+			String::from_utf8(merged.table).unwrap()
+		);
+		assert!(merged.conflicts.is_empty());
+
+		assert!(matches!(
+			merge_tables(&base, &ours, "id\ttitle\n"),
+			Err(MergeErr::Header)
+		));
+	}
+}
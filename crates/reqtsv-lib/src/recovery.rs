@@ -0,0 +1,608 @@
+//! Finishing or rolling back a table save interrupted by a crash.
+//!
+//! A table "x.tsv" is saved by writing "x.new.tsv" during the session then at exit:
+//! `mv x.tsv x.old.tsv`, `mv x.new.tsv x.tsv` and `rm x.old.tsv`. [`commit_tables`] does this
+//! for every changed table under a [`JOURNAL_NAME`] so they are all replaced or none are.
+//! [`recover_journal`] finishes or rolls back a commit a crash interrupted, and
+//! [`recover_table`] cleans up after a table saved without a journal.
+
+use std::{
+	fs::File,
+	io::{ErrorKind, Write},
+	path::{Path, PathBuf},
+};
+
+use chrono::Local;
+use log::{debug, error, warn};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::{new_table_name, old_table_name, unsaved_table_name};
+
+/// Lists the tables being swapped in, exists only while a commit is in progress.
+pub const JOURNAL_NAME: &str = "reqtsv.journal";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum TableVersion {
+	#[strum(to_string = "new")]
+	New,
+	#[strum(to_string = "current")]
+	Current,
+	#[strum(to_string = "old")]
+	Old,
+}
+
+impl TableVersion {
+	/// The order versions are tried in by [`recover_table`]. Without a journal "x.new.tsv" was
+	/// never committed so the current table wins over it.
+	pub const ALL: [TableVersion; 3] =
+		[TableVersion::Current, TableVersion::New, TableVersion::Old];
+
+	pub fn file_name(&self, table_name: &str) -> String {
+		match self {
+			TableVersion::New => new_table_name(table_name),
+			TableVersion::Current => table_name.to_string(),
+			TableVersion::Old => old_table_name(table_name),
+		}
+	}
+}
+
+/// What [`recover_table`] did to a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recovery {
+	pub table_name: String,
+	pub kept: TableVersion,
+	pub discarded: Vec<TableVersion>,
+}
+
+impl std::fmt::Display for Recovery {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}: kept the {} version", self.table_name, self.kept)?;
+		for (i, version) in self.discarded.iter().enumerate() {
+			let sep = if i == 0 { ", discarded the" } else { " and" };
+			write!(f, "{sep} {version}")?;
+		}
+		if !self.discarded.is_empty() {
+			write!(f, " version")?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Error, Debug)]
+pub enum RecoveryErr {
+	#[error("can't read table at: {0:?} due to: {1}")]
+	Read(PathBuf, #[source] std::io::Error),
+	#[error("can't move {from:?} to {to:?} due to: {source}")]
+	Rename {
+		from: PathBuf,
+		to: PathBuf,
+		#[source]
+		source: std::io::Error,
+	},
+	#[error("can't delete {0:?} due to: {1}")]
+	Remove(PathBuf, #[source] std::io::Error),
+	#[error("no valid version of table: {0}, found: {1:?}")]
+	NoValidVersion(String, Vec<TableVersion>),
+	#[error("can't write journal at: {0:?} due to: {1}")]
+	WriteJournal(PathBuf, #[source] std::io::Error),
+	#[error("can't read journal at: {0:?} due to: {1}")]
+	ReadJournal(PathBuf, #[source] std::io::Error),
+	/// The commit was rolled back, the new tables were moved to `kept` to retry with, see
+	/// [`unsaved_table_name`].
+	#[error(
+		"can't save tables due to: {source}, the old tables were restored and the new ones kept at: {kept:?}"
+	)]
+	RolledBack {
+		kept: Vec<PathBuf>,
+		#[source]
+		source: Box<RecoveryErr>,
+	},
+}
+
+/// A table written in full starts with `header` and ends with a record delimiter.
+pub fn table_is_complete(raw_table: &str, header: &str) -> bool {
+	let header = header.trim_end_matches('\n');
+	raw_table
+		.lines()
+		.next()
+		.is_some_and(|line| line.starts_with(header))
+		&& raw_table.ends_with('\n')
+}
+
+/// Returns `None` when only the current version of `table_name` exists i.e. the last save
+/// finished. Otherwise the first version in [`TableVersion::ALL`] passing `is_valid` becomes the
+/// current table and the others are deleted. Call after [`recover_journal`].
+pub fn recover_table(
+	project_root: &Path,
+	table_name: &str,
+	is_valid: impl Fn(&str) -> bool,
+) -> Result<Option<Recovery>, RecoveryErr> {
+	let found: Vec<(TableVersion, PathBuf)> = TableVersion::ALL
+		.into_iter()
+		.map(|v| (v, project_root.join(v.file_name(table_name))))
+		.filter(|(_, path)| path.exists())
+		.collect();
+	if found.iter().all(|(v, _)| *v == TableVersion::Current) {
+		return Ok(None);
+	}
+
+	let mut kept = None;
+	for (version, path) in found.iter() {
+		let raw = std::fs::read(path).map_err(|e| RecoveryErr::Read(path.clone(), e))?;
+		// a torn write may not even be UTF-8
+		if String::from_utf8(raw).is_ok_and(|raw| is_valid(&raw)) {
+			kept = Some((*version, path));
+			break;
+		}
+		debug!("{table_name}: {version} version is not valid");
+	}
+	let Some((kept, kept_path)) = kept else {
+		return Err(RecoveryErr::NoValidVersion(
+			table_name.to_string(),
+			found.into_iter().map(|(v, _)| v).collect(),
+		));
+	};
+
+	// replace the current table first so there is always a valid one on disk
+	let current_path = project_root.join(table_name);
+	if kept != TableVersion::Current {
+		rename(kept_path, &current_path)?;
+	}
+	let mut discarded = Vec::new();
+	for (version, path) in found.iter().filter(|(v, _)| *v != kept) {
+		if *version != TableVersion::Current {
+			remove(path)?;
+		}
+		discarded.push(*version);
+	}
+	Ok(Some(Recovery {
+		table_name: table_name.to_string(),
+		kept,
+		discarded,
+	}))
+}
+
+/// Written to [`JOURNAL_NAME`] before any table is swapped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Journal {
+	pub tables: Vec<String>,
+}
+
+/// Whether [`recover_journal`] finished an interrupted commit or undid it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum JournalOutcome {
+	#[strum(to_string = "finished")]
+	Replayed,
+	#[strum(to_string = "rolled back")]
+	RolledBack,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalRecovery {
+	pub outcome: JournalOutcome,
+	pub tables: Vec<String>,
+}
+
+impl std::fmt::Display for JournalRecovery {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} save of: {}", self.outcome, self.tables.join(", "))
+	}
+}
+
+/// Replaces each of `tables` with its "x.new.tsv", all of them or none. On error the tables
+/// are rolled back, if that fails too the journal is left for [`recover_journal`].
+pub fn commit_tables(project_root: &Path, tables: &[&str]) -> Result<(), RecoveryErr> {
+	if tables.is_empty() {
+		return Ok(());
+	}
+	let journal_path = project_root.join(JOURNAL_NAME);
+	let journal = Journal {
+		tables: tables.iter().map(|t| t.to_string()).collect(),
+	};
+	let raw = toml::to_string(&journal).expect("the journal is always valid TOML");
+	write_journal(project_root, &journal_path, &raw)
+		.map_err(|e| RecoveryErr::WriteJournal(journal_path.clone(), e))?;
+
+	for table_name in tables {
+		if let Err(e) = swap_table(project_root, table_name) {
+			return match restore_old(project_root, &journal.tables) {
+				Ok(kept) => {
+					remove(&journal_path)?;
+					Err(RecoveryErr::RolledBack {
+						kept,
+						source: Box::new(e),
+					})
+				}
+				Err(rbe) => {
+					error!("can't roll back commit, run reqtsv again to recover: {rbe}");
+					Err(e)
+				}
+			};
+		}
+	}
+	// committed, the old tables are only needed to roll back
+	finish(project_root, &journal.tables)?;
+	remove(&journal_path)
+}
+
+/// Writes the journal in full to a temporary file then moves it in to place, so a journal that
+/// exists was written whole before any table was swapped.
+fn write_journal(project_root: &Path, journal_path: &Path, raw: &str) -> std::io::Result<()> {
+	// a journal that wasn't recovered is never overwritten
+	if journal_path.exists() {
+		return Err(std::io::Error::new(
+			ErrorKind::AlreadyExists,
+			"the last commit wasn't recovered",
+		));
+	}
+	let tmp_path = project_root.join(format!("{JOURNAL_NAME}.tmp"));
+	let mut file = File::create(&tmp_path)?;
+	file.write_all(raw.as_bytes())?;
+	file.sync_all()?;
+	std::fs::rename(&tmp_path, journal_path)?;
+	// so the rename survives a crash, not every platform can sync a directory
+	if let Ok(dir) = File::open(project_root) {
+		let _ = dir.sync_all();
+	}
+	Ok(())
+}
+
+/// Returns `None` when there is no [`JOURNAL_NAME`] i.e. the last commit finished, or when it
+/// can't be parsed i.e. the crash was before it was in place and no table was swapped. Otherwise
+/// the commit is finished if every new table passes `is_valid(table_name, raw_table)`, or rolled
+/// back if any doesn't.
+pub fn recover_journal(
+	project_root: &Path,
+	is_valid: impl Fn(&str, &str) -> bool,
+) -> Result<Option<JournalRecovery>, RecoveryErr> {
+	let journal_path = project_root.join(JOURNAL_NAME);
+	if !journal_path.exists() {
+		return Ok(None);
+	}
+	let raw = std::fs::read_to_string(&journal_path)
+		.map_err(|e| RecoveryErr::ReadJournal(journal_path.clone(), e))?;
+	let journal: Journal = match toml::from_str(&raw) {
+		Ok(journal) => journal,
+		Err(e) => {
+			// journals made before they were written to a temporary file first can be torn
+			warn!("removing torn journal: {journal_path:?} due to: {e}");
+			remove(&journal_path)?;
+			return Ok(None);
+		}
+	};
+
+	let mut replay = true;
+	for table_name in journal.tables.iter() {
+		// not swapped in yet, or already swapped in
+		let new_path = [TableVersion::New, TableVersion::Current]
+			.into_iter()
+			.map(|v| project_root.join(v.file_name(table_name)))
+			.find(|path| path.exists());
+		let valid = match new_path {
+			Some(path) => {
+				let raw = std::fs::read(&path).map_err(|e| RecoveryErr::Read(path, e))?;
+				String::from_utf8(raw).is_ok_and(|raw| is_valid(table_name, &raw))
+			}
+			None => false,
+		};
+		if !valid {
+			debug!("{table_name}: new version is not valid, rolling back");
+			replay = false;
+			break;
+		}
+	}
+
+	let outcome = if replay {
+		for table_name in journal.tables.iter() {
+			let new = project_root.join(TableVersion::New.file_name(table_name));
+			if new.exists() {
+				swap_table(project_root, table_name)?;
+			}
+		}
+		finish(project_root, &journal.tables)?;
+		JournalOutcome::Replayed
+	} else {
+		roll_back(project_root, &journal.tables)?;
+		JournalOutcome::RolledBack
+	};
+	remove(&journal_path)?;
+	Ok(Some(JournalRecovery {
+		outcome,
+		tables: journal.tables,
+	}))
+}
+
+/// `mv x.tsv x.old.tsv` then `mv x.new.tsv x.tsv`, skipping a step done before a crash.
+fn swap_table(project_root: &Path, table_name: &str) -> Result<(), RecoveryErr> {
+	let current = project_root.join(table_name);
+	let new = project_root.join(TableVersion::New.file_name(table_name));
+	let old = project_root.join(TableVersion::Old.file_name(table_name));
+	if current.exists() && !old.exists() {
+		rename(&current, &old)?;
+	}
+	rename(&new, &current)
+}
+
+/// Deletes the old tables once every new table is in place.
+fn finish(project_root: &Path, tables: &[String]) -> Result<(), RecoveryErr> {
+	for table_name in tables {
+		let old = project_root.join(TableVersion::Old.file_name(table_name));
+		if old.exists() {
+			remove(&old)?;
+		}
+	}
+	Ok(())
+}
+
+/// Puts every old table back and deletes the new ones.
+fn roll_back(project_root: &Path, tables: &[String]) -> Result<(), RecoveryErr> {
+	for table_name in tables {
+		let current = project_root.join(table_name);
+		let new = project_root.join(TableVersion::New.file_name(table_name));
+		let old = project_root.join(TableVersion::Old.file_name(table_name));
+		if old.exists() {
+			rename(&old, &current)?;
+		}
+		if new.exists() {
+			remove(&new)?;
+		}
+	}
+	Ok(())
+}
+
+/// Puts every old table back like [`roll_back`] but keeps the new ones, see
+/// [`unsaved_table_name`]. Returns where the new tables were moved.
+fn restore_old(project_root: &Path, tables: &[String]) -> Result<Vec<PathBuf>, RecoveryErr> {
+	let rolled_back_at = Local::now();
+	let mut kept = Vec::new();
+	for table_name in tables {
+		let current = project_root.join(table_name);
+		let new = project_root.join(TableVersion::New.file_name(table_name));
+		let old = project_root.join(TableVersion::Old.file_name(table_name));
+		if old.exists() {
+			if current.exists() && !new.exists() {
+				rename(&current, &new)?;
+			}
+			rename(&old, &current)?;
+		}
+		if new.exists() {
+			// recover_table would discard "x.new.tsv" next to a current table
+			let unsaved = project_root.join(unsaved_table_name(table_name, rolled_back_at));
+			rename(&new, &unsaved)?;
+			kept.push(unsaved);
+		}
+	}
+	Ok(kept)
+}
+
+fn rename(from: &Path, to: &Path) -> Result<(), RecoveryErr> {
+	std::fs::rename(from, to).map_err(|e| RecoveryErr::Rename {
+		from: from.into(),
+		to: to.into(),
+		source: e,
+	})
+}
+
+fn remove(path: &Path) -> Result<(), RecoveryErr> {
+	std::fs::remove_file(path).map_err(|e| RecoveryErr::Remove(path.into(), e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::LINK_HEADER;
+
+	const TABLE: &str = "link.tsv";
+	const GOOD_OLD: &str = "id\tfrom_id\tkind\tto_id\tcreation_date\tstatus\n";
+	const GOOD_NEW: &str = "id\tfrom_id\tkind\tto_id\tcreation_date\tstatus\n0\t0\tRefines\t1\t2025-05-27T12:00:00-04:00\tActive\n";
+	const TORN: &str = "id\tfrom_id\tkind\tto_id\tcreation_date\tstatus\n0\t0\tRefi";
+
+	fn is_valid(raw: &str) -> bool {
+		table_is_complete(raw, LINK_HEADER)
+	}
+
+	fn setup(name: &str, files: &[(TableVersion, &str)]) -> PathBuf {
+		let root = std::env::temp_dir().join(format!(
+			"reqtsv-recovery-test-{}-{name}",
+			std::process::id()
+		));
+		if root.exists() {
+			std::fs::remove_dir_all(&root).unwrap();
+		}
+		std::fs::create_dir_all(&root).unwrap();
+		for (version, content) in files {
+			std::fs::write(root.join(version.file_name(TABLE)), content).unwrap();
+		}
+		root
+	}
+
+	/// Recovers the table and checks only the current version is left, with `expected` content.
+	fn check(root: &Path, expected: &str) -> Option<Recovery> {
+		let recovery = recover_table(root, TABLE, is_valid).unwrap();
+		assert_eq!(expected, std::fs::read_to_string(root.join(TABLE)).unwrap());
+		assert!(!root.join(new_table_name(TABLE)).exists());
+		assert!(!root.join(old_table_name(TABLE)).exists());
+		std::fs::remove_dir_all(root).unwrap();
+		recovery
+	}
+
+	#[test]
+	fn test_recover_table() {
+		use TableVersion::*;
+
+		// save finished
+		let root = setup("done", &[(Current, GOOD_NEW)]);
+		assert_eq!(None, check(&root, GOOD_NEW));
+
+		// crashed while writing x.new.tsv
+		let root = setup("torn-new", &[(Current, GOOD_OLD), (New, TORN)]);
+		let recovery = check(&root, GOOD_OLD).unwrap();
+		assert_eq!((Current, vec![New]), (recovery.kept, recovery.discarded));
+
+		// crashed before committing
+		let root = setup("before-swap", &[(Current, GOOD_OLD), (New, GOOD_NEW)]);
+		let recovery = check(&root, GOOD_OLD).unwrap();
+		assert_eq!((Current, vec![New]), (recovery.kept, recovery.discarded));
+
+		// crashed before mv x.new.tsv x.tsv
+		let root = setup("mid-swap", &[(Old, GOOD_OLD), (New, GOOD_NEW)]);
+		let recovery = check(&root, GOOD_NEW).unwrap();
+		assert_eq!((New, vec![Old]), (recovery.kept, recovery.discarded));
+
+		// crashed before mv x.new.tsv x.tsv with a torn x.new.tsv
+		let root = setup("mid-swap-torn", &[(Old, GOOD_OLD), (New, TORN)]);
+		let recovery = check(&root, GOOD_OLD).unwrap();
+		assert_eq!((Old, vec![New]), (recovery.kept, recovery.discarded));
+
+		// crashed before rm x.old.tsv
+		let root = setup("after-swap", &[(Current, GOOD_NEW), (Old, GOOD_OLD)]);
+		let recovery = check(&root, GOOD_NEW).unwrap();
+		assert_eq!(
+			(Current, vec![Old]),
+			(recovery.kept, recovery.discarded.clone())
+		);
+		assert_eq!(
+			"link.tsv: kept the current version, discarded the old version",
+			recovery.to_string()
+		);
+
+		// nothing to recover from, the files are left alone
+		let root = setup("no-valid", &[(Old, TORN), (New, TORN)]);
+		assert!(matches!(
+			recover_table(&root, TABLE, is_valid),
+			Err(RecoveryErr::NoValidVersion(_, _))
+		));
+		assert!(root.join(new_table_name(TABLE)).exists());
+		std::fs::remove_dir_all(root).unwrap();
+	}
+
+	const OTHER: &str = "component.tsv";
+
+	fn read(root: &Path, version: TableVersion, table_name: &str) -> Option<String> {
+		std::fs::read_to_string(root.join(version.file_name(table_name))).ok()
+	}
+
+	fn write_journal(root: &Path) {
+		let journal = Journal {
+			tables: vec![TABLE.into(), OTHER.into()],
+		};
+		std::fs::write(root.join(JOURNAL_NAME), toml::to_string(&journal).unwrap()).unwrap();
+	}
+
+	#[test]
+	fn test_commit_tables() {
+		use TableVersion::*;
+		let is_valid = |_: &str, raw: &str| is_valid(raw);
+
+		// both tables replaced
+		let root = setup("commit", &[(Current, GOOD_OLD), (New, GOOD_NEW)]);
+		std::fs::write(root.join(OTHER), GOOD_OLD).unwrap();
+		std::fs::write(root.join(New.file_name(OTHER)), GOOD_NEW).unwrap();
+		commit_tables(&root, &[TABLE, OTHER]).unwrap();
+		for table_name in [TABLE, OTHER] {
+			assert_eq!(Some(GOOD_NEW), read(&root, Current, table_name).as_deref());
+			assert_eq!(None, read(&root, New, table_name));
+			assert_eq!(None, read(&root, Old, table_name));
+		}
+		assert!(!root.join(JOURNAL_NAME).exists());
+		assert_eq!(None, recover_journal(&root, is_valid).unwrap());
+		std::fs::remove_dir_all(&root).unwrap();
+
+		// the second swap fails, the first is rolled back and its new table kept
+		let root = setup("commit-fail", &[(Current, GOOD_OLD), (New, GOOD_NEW)]);
+		std::fs::write(root.join(OTHER), GOOD_OLD).unwrap();
+		let Err(RecoveryErr::RolledBack { kept, .. }) = commit_tables(&root, &[TABLE, OTHER])
+		else {
+			panic!("commit should be rolled back");
+		};
+		assert_eq!(1, kept.len());
+		assert!(kept[0].to_string_lossy().contains("link.unsaved-"));
+		assert_eq!(GOOD_NEW, std::fs::read_to_string(&kept[0]).unwrap());
+		for table_name in [TABLE, OTHER] {
+			assert_eq!(Some(GOOD_OLD), read(&root, Current, table_name).as_deref());
+			assert_eq!(None, read(&root, New, table_name));
+			assert_eq!(None, read(&root, Old, table_name));
+		}
+		assert!(!root.join(JOURNAL_NAME).exists());
+		std::fs::remove_dir_all(&root).unwrap();
+
+		// crashed while writing a journal, before anything was swapped
+		let root = setup("journal-torn", &[(Current, GOOD_OLD), (New, GOOD_NEW)]);
+		std::fs::write(root.join(JOURNAL_NAME), "tables = [\"li").unwrap();
+		assert_eq!(None, recover_journal(&root, is_valid).unwrap());
+		assert!(!root.join(JOURNAL_NAME).exists());
+		let recovery = recover_table(&root, TABLE, |raw| is_valid(TABLE, raw)).unwrap();
+		assert_eq!(Some(Current), recovery.map(|r| r.kept));
+		assert_eq!(Some(GOOD_OLD), read(&root, Current, TABLE).as_deref());
+		std::fs::remove_dir_all(&root).unwrap();
+
+		// a journal that wasn't recovered isn't overwritten
+		let root = setup("journal-left", &[(Current, GOOD_OLD), (New, GOOD_NEW)]);
+		write_journal(&root);
+		assert!(matches!(
+			commit_tables(&root, &[TABLE]),
+			Err(RecoveryErr::WriteJournal(_, _))
+		));
+		assert_eq!(Some(GOOD_OLD), read(&root, Current, TABLE).as_deref());
+		std::fs::remove_dir_all(&root).unwrap();
+
+		// crashed after the journal was written
+		let root = setup("journal-written", &[(Current, GOOD_OLD), (New, GOOD_NEW)]);
+		std::fs::write(root.join(OTHER), GOOD_OLD).unwrap();
+		std::fs::write(root.join(New.file_name(OTHER)), GOOD_NEW).unwrap();
+		write_journal(&root);
+		let recovery = recover_journal(&root, is_valid).unwrap().unwrap();
+		assert_eq!(JournalOutcome::Replayed, recovery.outcome);
+		assert_eq!(
+			"finished save of: link.tsv, component.tsv",
+			recovery.to_string()
+		);
+		for table_name in [TABLE, OTHER] {
+			assert_eq!(Some(GOOD_NEW), read(&root, Current, table_name).as_deref());
+		}
+		std::fs::remove_dir_all(&root).unwrap();
+
+		// crashed with the first table swapped in and the second half way
+		let mid_commit = |name: &str, other_new: &str| {
+			let root = setup(name, &[(Current, GOOD_NEW), (Old, GOOD_OLD)]);
+			std::fs::write(root.join(Old.file_name(OTHER)), GOOD_OLD).unwrap();
+			std::fs::write(root.join(New.file_name(OTHER)), other_new).unwrap();
+			write_journal(&root);
+			root
+		};
+		let root = mid_commit("mid-commit", GOOD_NEW);
+		let recovery = recover_journal(&root, is_valid).unwrap().unwrap();
+		assert_eq!(JournalOutcome::Replayed, recovery.outcome);
+		for table_name in [TABLE, OTHER] {
+			assert_eq!(Some(GOOD_NEW), read(&root, Current, table_name).as_deref());
+			assert_eq!(None, read(&root, New, table_name));
+			assert_eq!(None, read(&root, Old, table_name));
+		}
+		assert!(!root.join(JOURNAL_NAME).exists());
+		std::fs::remove_dir_all(&root).unwrap();
+
+		// as above with a torn new table, so neither table is replaced
+		let root = mid_commit("mid-commit-torn", TORN);
+		let recovery = recover_journal(&root, is_valid).unwrap().unwrap();
+		assert_eq!(JournalOutcome::RolledBack, recovery.outcome);
+		for table_name in [TABLE, OTHER] {
+			assert_eq!(Some(GOOD_OLD), read(&root, Current, table_name).as_deref());
+			assert_eq!(None, read(&root, New, table_name));
+			assert_eq!(None, read(&root, Old, table_name));
+		}
+		assert!(!root.join(JOURNAL_NAME).exists());
+		std::fs::remove_dir_all(&root).unwrap();
+
+		// crashed while deleting the old tables
+		let root = setup("deleting-old", &[(Current, GOOD_NEW)]);
+		std::fs::write(root.join(OTHER), GOOD_NEW).unwrap();
+		std::fs::write(root.join(Old.file_name(OTHER)), GOOD_OLD).unwrap();
+		write_journal(&root);
+		let recovery = recover_journal(&root, is_valid).unwrap().unwrap();
+		assert_eq!(JournalOutcome::Replayed, recovery.outcome);
+		assert_eq!(Some(GOOD_NEW), read(&root, Current, OTHER).as_deref());
+		assert_eq!(None, read(&root, Old, OTHER));
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+}
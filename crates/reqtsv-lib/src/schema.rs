@@ -0,0 +1,497 @@
+//! The layout of the tables, versioned by `schema_version` in
+//! [`crate::config::PROJECT_CONFIG_NAME`].
+//!
+//! Version 1 is every project made before the schema was versioned. Tables made by
+//! `init_project` then had a requirement header that didn't match what was written on save.
+//! Version 2 renames the "requirement" column to "requirement_text", adds any missing columns
+//! and tables, and writes every header in full.
+
+use std::{
+	fs::OpenOptions,
+	io::Write,
+	path::{Path, PathBuf},
+};
+
+use chrono::Local;
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use crate::{
+	COLUMN_DELIMITER, COMPONENT_HEADER, Component, HISTORY_HEADER, LINK_HEADER, Link,
+	RECORD_DELIMITER, REQUIREMENT_HEADER, Requirement, RequirementRevision, TEST_CASE_HEADER,
+	TEST_RESULT_HEADER, TestCase, TestResult,
+	config::{
+		PROJECT_CONFIG_NAME, ProjectConfig, ProjectConfigErr, TableNames, load_project_config,
+		new_table_name, set_schema_version,
+	},
+	custom_field::{
+		CustomFields, FieldDef, REQUIREMENT_COLUMNS, append_custom_columns, read_custom_columns,
+	},
+	recovery::{RecoveryErr, commit_tables},
+};
+
+pub const SCHEMA_VERSION: u32 = 2;
+/// Projects without `schema_version` in their config.
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum Table {
+	Component,
+	Requirement,
+	Link,
+	History,
+	TestCase,
+	TestResult,
+}
+
+impl Table {
+	pub const ALL: [Table; 6] = [
+		Table::Component,
+		Table::Requirement,
+		Table::Link,
+		Table::History,
+		Table::TestCase,
+		Table::TestResult,
+	];
+
+	pub fn header(&self) -> &'static str {
+		match self {
+			Table::Component => COMPONENT_HEADER,
+			Table::Requirement => REQUIREMENT_HEADER,
+			Table::Link => LINK_HEADER,
+			Table::History => HISTORY_HEADER,
+			Table::TestCase => TEST_CASE_HEADER,
+			Table::TestResult => TEST_RESULT_HEADER,
+		}
+	}
+
+	pub fn file_name<'a>(&self, tables: &'a TableNames) -> &'a str {
+		match self {
+			Table::Component => &tables.component,
+			Table::Requirement => &tables.requirement,
+			Table::Link => &tables.link,
+			Table::History => &tables.history,
+			Table::TestCase => &tables.test_case,
+			Table::TestResult => &tables.test_result,
+		}
+	}
+
+	/// Custom fields are extra columns of the requirement table.
+	fn allows_extra_columns(&self) -> bool {
+		matches!(self, Table::Requirement)
+	}
+
+	/// (old name, new name) of columns renamed since [`LEGACY_SCHEMA_VERSION`].
+	fn renamed_columns(&self) -> &'static [(&'static str, &'static str)] {
+		match self {
+			Table::Requirement => &[("requirement", "requirement_text")],
+			_ => &[],
+		}
+	}
+}
+
+#[derive(Error, Debug)]
+pub enum SchemaErr {
+	#[error(
+		"project schema version {0} is newer than this version of reqtsv supports ({SCHEMA_VERSION}), please update reqtsv"
+	)]
+	TooNew(u32),
+	#[error(
+		"project schema version {0} is older than {SCHEMA_VERSION}, run `reqtsv --migrate <project>` to upgrade it"
+	)]
+	TooOld(u32),
+	#[error(
+		"{table} columns don't match schema version {SCHEMA_VERSION}, missing: {missing:?}, unexpected: {unexpected:?}"
+	)]
+	Header {
+		table: String,
+		missing: Vec<String>,
+		unexpected: Vec<String>,
+	},
+	#[error("{0}")]
+	Config(#[source] ProjectConfigErr),
+	#[error("can't back up {0:?} due to: {1}")]
+	Backup(PathBuf, #[source] std::io::Error),
+	#[error("can't read table at: {0:?} due to: {1}")]
+	Read(PathBuf, #[source] std::io::Error),
+	#[error("can't migrate table: {0} due to: {1}")]
+	Migrate(String, #[source] csv::Error),
+	#[error("can't write table at: {0:?} due to: {1}")]
+	Write(PathBuf, #[source] std::io::Error),
+	#[error("can't save migrated tables: {0}")]
+	Commit(#[source] RecoveryErr),
+}
+
+pub fn check_schema_version(config: &ProjectConfig) -> Result<(), SchemaErr> {
+	match config.schema_version {
+		v if v > SCHEMA_VERSION => Err(SchemaErr::TooNew(v)),
+		v if v < SCHEMA_VERSION => Err(SchemaErr::TooOld(v)),
+		_ => Ok(()),
+	}
+}
+
+/// Checks `raw_table` has every column of `table`, and no others unless they are custom fields.
+/// An empty file is a table without records.
+pub fn check_header(table: Table, table_name: &str, raw_table: &str) -> Result<(), SchemaErr> {
+	if raw_table.is_empty() {
+		return Ok(());
+	}
+	let found: Vec<&str> = raw_table
+		.lines()
+		.next()
+		.unwrap_or_default()
+		.split(COLUMN_DELIMITER as char)
+		.collect();
+	let expected: Vec<&str> = columns(table.header()).collect();
+	let missing: Vec<String> = expected
+		.iter()
+		.filter(|c| !found.contains(c))
+		.map(|c| c.to_string())
+		.collect();
+	let unexpected: Vec<String> = found
+		.iter()
+		.filter(|c| !table.allows_extra_columns() && !expected.contains(c))
+		.map(|c| c.to_string())
+		.collect();
+	if missing.is_empty() && unexpected.is_empty() {
+		Ok(())
+	} else {
+		Err(SchemaErr::Header {
+			table: table_name.to_string(),
+			missing,
+			unexpected,
+		})
+	}
+}
+
+fn columns(header: &str) -> impl Iterator<Item = &str> {
+	header
+		.trim_end_matches(RECORD_DELIMITER as char)
+		.split(COLUMN_DELIMITER as char)
+}
+
+/// What [`migrate_project`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+	pub from: u32,
+	pub to: u32,
+	/// Holds a copy of the config and every table as they were, `None` if nothing was done.
+	pub backup: Option<PathBuf>,
+	pub migrated: Vec<String>,
+	pub created: Vec<String>,
+}
+
+/// Upgrades every table in `project_root` to [`SCHEMA_VERSION`], after copying them to a backup
+/// directory. The tables are replaced all-or-nothing, see [`commit_tables`].
+/// Only call while holding the project lock.
+pub fn migrate_project(project_root: &Path) -> Result<Migration, SchemaErr> {
+	let config = load_project_config(project_root).map_err(SchemaErr::Config)?;
+	let from = config.schema_version;
+	if from > SCHEMA_VERSION {
+		return Err(SchemaErr::TooNew(from));
+	}
+	let mut migration = Migration {
+		from,
+		to: SCHEMA_VERSION,
+		backup: None,
+		migrated: Vec::new(),
+		created: Vec::new(),
+	};
+	if from == SCHEMA_VERSION {
+		return Ok(migration);
+	}
+
+	let backup = project_root.join(format!(
+		"schema-v{from}-backup-{}",
+		Local::now().format("%Y%m%dT%H%M%S")
+	));
+	std::fs::create_dir(&backup).map_err(|e| SchemaErr::Backup(backup.clone(), e))?;
+	for name in config
+		.tables
+		.iter()
+		.chain(std::iter::once(PROJECT_CONFIG_NAME))
+	{
+		let path = project_root.join(name);
+		if path.exists() {
+			std::fs::copy(&path, backup.join(name)).map_err(|e| SchemaErr::Backup(path, e))?;
+		}
+	}
+	migration.backup = Some(backup);
+
+	for table in Table::ALL {
+		let table_name = table.file_name(&config.tables);
+		let path = project_root.join(table_name);
+		if !path.exists() {
+			write_synced(&path, table.header().as_bytes())?;
+			migration.created.push(table_name.to_string());
+			continue;
+		}
+		let raw = std::fs::read_to_string(&path).map_err(|e| SchemaErr::Read(path.clone(), e))?;
+		let migrated = migrate_table(table, &raw, &config.fields)
+			.map_err(|e| SchemaErr::Migrate(table_name.to_string(), e))?;
+		write_synced(&project_root.join(new_table_name(table_name)), &migrated)?;
+		migration.migrated.push(table_name.to_string());
+	}
+	let migrated: Vec<&str> = migration.migrated.iter().map(String::as_str).collect();
+	commit_tables(project_root, &migrated).map_err(SchemaErr::Commit)?;
+
+	set_schema_version(project_root, SCHEMA_VERSION).map_err(SchemaErr::Config)?;
+	Ok(migration)
+}
+
+/// Rewrites `raw_table` with the current columns of `table`, filling new columns with their
+/// defaults.
+pub fn migrate_table(
+	table: Table,
+	raw_table: &str,
+	fields: &[FieldDef],
+) -> Result<Vec<u8>, csv::Error> {
+	let raw = rename_columns(raw_table, table.renamed_columns());
+	let migrated = match table {
+		Table::Component => reserialize::<Component>(&raw)?,
+		Table::Requirement => reserialize::<Requirement>(&raw)?,
+		Table::Link => reserialize::<Link>(&raw)?,
+		Table::History => reserialize::<RequirementRevision>(&raw)?,
+		Table::TestCase => reserialize::<TestCase>(&raw)?,
+		Table::TestResult => reserialize::<TestResult>(&raw)?,
+	};
+	let Some(migrated) = migrated else {
+		return Ok(table.header().as_bytes().to_vec());
+	};
+	if table == Table::Requirement {
+		let custom = read_custom_columns(&raw, REQUIREMENT_COLUMNS)?;
+		let rows: Vec<&CustomFields> = custom.iter().collect();
+		return append_custom_columns(&migrated, fields, &rows);
+	}
+	Ok(migrated)
+}
+
+fn rename_columns(raw_table: &str, renamed: &[(&str, &str)]) -> String {
+	let (header, records) = raw_table.split_once('\n').unwrap_or((raw_table, ""));
+	let header: Vec<&str> = header
+		.split(COLUMN_DELIMITER as char)
+		.map(|column| {
+			renamed
+				.iter()
+				.find(|(old, _)| *old == column)
+				.map_or(column, |(_, new)| new)
+		})
+		.collect();
+	format!(
+		"{}\n{records}",
+		header.join(&(COLUMN_DELIMITER as char).to_string())
+	)
+}
+
+/// `None` when there are no records, the header isn't written without one.
+fn reserialize<R: DeserializeOwned + Serialize>(
+	raw_table: &str,
+) -> Result<Option<Vec<u8>>, csv::Error> {
+	let records: Vec<R> = csv::ReaderBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(RECORD_DELIMITER))
+		.from_reader(raw_table.as_bytes())
+		.deserialize()
+		.collect::<Result<_, _>>()?;
+	if records.is_empty() {
+		return Ok(None);
+	}
+	let mut wtr = csv::WriterBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(RECORD_DELIMITER))
+		.from_writer(Vec::with_capacity(raw_table.len()));
+	for record in records {
+		wtr.serialize(record)?;
+	}
+	wtr.into_inner()
+		.map(Some)
+		.map_err(|e| csv::Error::from(e.into_error()))
+}
+
+fn write_synced(path: &Path, content: &[u8]) -> Result<(), SchemaErr> {
+	OpenOptions::new()
+		.write(true)
+		.create(true)
+		.truncate(true)
+		.open(path)
+		.and_then(|mut file| {
+			file.write_all(content)?;
+			file.sync_all()
+		})
+		.map_err(|e| SchemaErr::Write(path.into(), e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		IdList, LinkKind, RecordStatus, RequirementFunctional, RequirementPriority, Tags,
+		TestOutcome, VerificationMethod,
+	};
+	use chrono::TimeZone;
+
+	fn header_of<S: Serialize>(record: S) -> String {
+		let mut wtr = csv::WriterBuilder::new()
+			.delimiter(COLUMN_DELIMITER)
+			.terminator(csv::Terminator::Any(RECORD_DELIMITER))
+			.from_writer(Vec::new());
+		wtr.serialize(record).unwrap();
+		let table = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+		format!("{}\n", table.lines().next().unwrap())
+	}
+
+	#[test]
+	fn test_headers_match_records() {
+		let date = Local.with_ymd_and_hms(2025, 5, 27, 12, 0, 0).unwrap();
+		let requirement = Requirement {
+			id: 0,
+			key: String::new(),
+			component_id: 0,
+			parent_id: None,
+			title: String::new(),
+			functional: RequirementFunctional::Functional,
+			creation_date: date,
+			requirement_text: String::new(),
+			acceptance_criteria: String::new(),
+			verification_method: VerificationMethod::default(),
+			version: 0,
+			author: String::new(),
+			priority: RequirementPriority::Low,
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			risks: String::new(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		};
+		assert_eq!(REQUIREMENT_HEADER, header_of(&requirement));
+		assert_eq!(
+			REQUIREMENT_HEADER,
+			format!("{}\n", REQUIREMENT_COLUMNS.join("\t"))
+		);
+		assert_eq!(
+			COMPONENT_HEADER,
+			header_of(Component {
+				id: 0,
+				prefix: String::new(),
+				next_seq: 0,
+				parent_id: None,
+				name: String::new(),
+				description: String::new(),
+				creation_date: date,
+				status: RecordStatus::Accepted,
+				author: String::new(),
+				tags: Tags::default(),
+			})
+		);
+		assert_eq!(
+			LINK_HEADER,
+			header_of(Link {
+				id: 0,
+				from_id: 0,
+				kind: LinkKind::Refines,
+				to_id: 1,
+				creation_date: date,
+				status: RecordStatus::Accepted,
+			})
+		);
+		assert_eq!(
+			HISTORY_HEADER,
+			header_of(RequirementRevision {
+				requirement_id: 0,
+				version: 0,
+				title: String::new(),
+				functional: RequirementFunctional::Functional,
+				requirement_text: String::new(),
+				acceptance_criteria: String::new(),
+				verification_method: VerificationMethod::default(),
+				author: String::new(),
+				priority: RequirementPriority::Low,
+				risks: String::new(),
+				tags: Tags::default(),
+				superseded_on: date,
+			})
+		);
+		assert_eq!(
+			TEST_CASE_HEADER,
+			header_of(TestCase {
+				id: 0,
+				key: String::new(),
+				title: String::new(),
+				requirement_ids: IdList::default(),
+				procedure: String::new(),
+				creation_date: date,
+				status: RecordStatus::Accepted,
+				author: String::new(),
+			})
+		);
+		assert_eq!(
+			TEST_RESULT_HEADER,
+			header_of(TestResult {
+				id: 0,
+				test_case_id: 0,
+				outcome: TestOutcome::Pass,
+				run_date: date,
+				evidence: String::new(),
+				author: String::new(),
+			})
+		);
+	}
+
+	#[test]
+	fn test_check_header() {
+		assert!(check_header(Table::Link, "link.tsv", LINK_HEADER).is_ok());
+		assert!(check_header(Table::Link, "link.tsv", "").is_ok());
+		let with_custom = format!("{}\tsafety_level\n", REQUIREMENT_HEADER.trim_end());
+		assert!(check_header(Table::Requirement, "requirement.tsv", &with_custom).is_ok());
+
+		let legacy = "id\tkey\tcomponent_id\tparent_id\tfunctional\tcreation_date\trequirement\tversion\tauthor\tpriority\tstatus\tstatus_justification\trisks\tacceptance_criteria\tverification_method\ttags\n";
+		match check_header(Table::Requirement, "requirement.tsv", legacy) {
+			Err(SchemaErr::Header { missing, .. }) => {
+				assert_eq!(vec!["title", "requirement_text"], missing)
+			}
+			res => panic!("expected a header error, got: {res:?}"),
+		}
+		match check_header(
+			Table::Link,
+			"link.tsv",
+			"id\tfrom_id\tkind\tto_id\tstatus\tnote\n",
+		) {
+			Err(SchemaErr::Header {
+				missing,
+				unexpected,
+				..
+			}) => {
+				assert_eq!(vec!["creation_date"], missing);
+				assert_eq!(vec!["note"], unexpected);
+			}
+			res => panic!("expected a header error, got: {res:?}"),
+		}
+	}
+
+	#[test]
+	fn test_migrate_table() {
+		// made by init_project before the schema was versioned
+		let legacy = "id\tkey\tcomponent_id\tparent_id\tfunctional\tcreation_date\trequirement\tversion\tauthor\tpriority\tstatus\tstatus_justification\trisks\tacceptance_criteria\tverification_method\ttags\n";
+		let migrated = migrate_table(Table::Requirement, legacy, &[]).unwrap();
+		assert_eq!(REQUIREMENT_HEADER.as_bytes(), migrated.as_slice());
+
+		// saved before keys, parents and tags existed
+		let old_links = "id\tfrom_id\tkind\tto_id\tcreation_date\tstatus\n0\t0\trefines\t1\t2025-05-27T12:00:00-04:00\tAccepted\n";
+		let migrated =
+			String::from_utf8(migrate_table(Table::Link, old_links, &[]).unwrap()).unwrap();
+		assert!(migrated.starts_with(LINK_HEADER));
+		assert_eq!(2, migrated.lines().count());
+		let old_components = "id\tname\tdescription\tcreation_date\tstatus\tauthor\n0\tPower\tBatteries\t2025-05-27T12:00:00-04:00\tAccepted\tJM\n";
+		let migrated =
+			String::from_utf8(migrate_table(Table::Component, old_components, &[]).unwrap())
+				.unwrap();
+		assert!(check_header(Table::Component, "component.tsv", &migrated).is_ok());
+		assert!(
+			migrated
+				.lines()
+				.nth(1)
+				.unwrap()
+				.starts_with("0\t\t0\t\tPower\tBatteries\t")
+		);
+	}
+}
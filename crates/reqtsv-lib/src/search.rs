@@ -0,0 +1,477 @@
+//! Ranked full-text search over requirements and components.
+//!
+//! Searched fields are the title, requirement text, and risks of requirements, and the name and
+//! description of components. Text is split into lower case words, common words such as "the" and
+//! "shall" are dropped, and the rest are stemmed so "braking" finds "brakes". Records are ranked
+//! with [BM25](https://en.wikipedia.org/wiki/Okapi_BM25), a word in a title or name counts for
+//! more than one in the text.
+
+use std::{
+	collections::{HashMap, HashSet},
+	ops::Range,
+};
+
+use crate::{Component, RecordStatus, Requirement, json::decode_text};
+
+/// How much a word found in a title or name counts against one found in the other fields.
+const HEADING_WEIGHT: f64 = 3.0;
+/// BM25 term frequency saturation.
+const K1: f64 = 1.2;
+/// BM25 length normalization.
+const B: f64 = 0.75;
+/// Words of context before the first match in a snippet.
+const SNIPPET_BEFORE: usize = 6;
+/// Words in a snippet.
+const SNIPPET_WORDS: usize = 20;
+
+const STOP_WORDS: [&str; 28] = [
+	"a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "has", "have", "in", "is",
+	"it", "its", "must", "of", "on", "or", "shall", "should", "that", "the", "this", "to", "will",
+	"with",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitKind {
+	Component,
+	Requirement,
+}
+
+/// A record matching a search, best first from [`SearchIndex::search`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+	pub kind: HitKind,
+	pub id: u64,
+	pub key: String,
+	/// The title of a requirement or the name of a component.
+	pub heading: String,
+	pub status: RecordStatus,
+	pub score: f64,
+	pub snippet: Snippet,
+}
+
+/// Part of the field that best matched, on one line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+	/// e.g. "requirement_text"
+	pub field: &'static str,
+	pub text: String,
+	/// Byte ranges of the matched words in `text`.
+	pub highlights: Vec<Range<usize>>,
+}
+
+impl Snippet {
+	/// The text with each matched word put between `before` and `after` e.g. "**" and "**".
+	pub fn highlighted(&self, before: &str, after: &str) -> String {
+		let mut out = String::with_capacity(self.text.len());
+		let mut at = 0;
+		for range in self.highlights.iter() {
+			out.push_str(&self.text[at..range.start]);
+			out.push_str(before);
+			out.push_str(&self.text[range.clone()]);
+			out.push_str(after);
+			at = range.end;
+		}
+		out.push_str(&self.text[at..]);
+		out
+	}
+}
+
+/// A word of a field, stemmed.
+#[derive(Debug, Clone)]
+struct Word {
+	stem: String,
+	span: Range<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+	name: &'static str,
+	text: String,
+	weight: f64,
+	words: Vec<Word>,
+}
+
+#[derive(Debug, Clone)]
+struct Document {
+	kind: HitKind,
+	id: u64,
+	key: String,
+	heading: String,
+	status: RecordStatus,
+	fields: Vec<Field>,
+	/// Weighted count of words.
+	len: f64,
+}
+
+/// The searched fields of the records, built once and searched many times.
+#[derive(Debug, Clone)]
+pub struct SearchIndex {
+	documents: Vec<Document>,
+	/// How many documents have each stem.
+	frequency: HashMap<String, usize>,
+	avg_len: f64,
+}
+
+impl SearchIndex {
+	pub fn new(components: &[Component], requirements: &[Requirement]) -> Self {
+		let mut documents = Vec::with_capacity(components.len() + requirements.len());
+		for component in components {
+			documents.push(document(
+				HitKind::Component,
+				component.id,
+				component.key(),
+				&component.name,
+				component.status,
+				vec![
+					("name", component.name.clone(), HEADING_WEIGHT),
+					("description", decode_text(&component.description), 1.0),
+				],
+			));
+		}
+		for requirement in requirements {
+			documents.push(document(
+				HitKind::Requirement,
+				requirement.id,
+				requirement.key(),
+				&requirement.title,
+				requirement.status,
+				vec![
+					("title", requirement.title.clone(), HEADING_WEIGHT),
+					(
+						"requirement_text",
+						decode_text(&requirement.requirement_text),
+						1.0,
+					),
+					("risks", decode_text(&requirement.risks), 1.0),
+				],
+			));
+		}
+		let mut frequency: HashMap<String, usize> = HashMap::new();
+		for doc in documents.iter() {
+			let stems: HashSet<&str> = doc
+				.fields
+				.iter()
+				.flat_map(|f| f.words.iter().map(|w| w.stem.as_str()))
+				.collect();
+			for stem in stems {
+				*frequency.entry(stem.to_string()).or_default() += 1;
+			}
+		}
+		let avg_len = match documents.is_empty() {
+			true => 0.0,
+			false => documents.iter().map(|d| d.len).sum::<f64>() / documents.len() as f64,
+		};
+		Self {
+			documents,
+			frequency,
+			avg_len,
+		}
+	}
+
+	/// Records with any word of `query`, best first. Empty when the query only has common
+	/// words.
+	pub fn search(&self, query: &str) -> Vec<Hit> {
+		let mut terms: Vec<String> = words(query).into_iter().map(|w| w.stem).collect();
+		terms.sort();
+		terms.dedup();
+		let count = self.documents.len() as f64;
+		let mut hits: Vec<Hit> = Vec::new();
+		for doc in self.documents.iter() {
+			let mut score = 0.0;
+			for term in terms.iter() {
+				let tf: f64 = doc
+					.fields
+					.iter()
+					.map(|f| f.weight * f.words.iter().filter(|w| &w.stem == term).count() as f64)
+					.sum();
+				if tf == 0.0 {
+					continue;
+				}
+				let df = self.frequency.get(term).copied().unwrap_or(0) as f64;
+				let idf = ((count - df + 0.5) / (df + 0.5) + 1.0).ln();
+				let norm = K1 * (1.0 - B + B * doc.len / self.avg_len.max(1.0));
+				score += idf * tf * (K1 + 1.0) / (tf + norm);
+			}
+			if score > 0.0 {
+				hits.push(Hit {
+					kind: doc.kind,
+					id: doc.id,
+					key: doc.key.clone(),
+					heading: doc.heading.clone(),
+					status: doc.status,
+					score,
+					snippet: snippet(doc, &terms),
+				});
+			}
+		}
+		hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.key.cmp(&b.key)));
+		hits
+	}
+}
+
+fn document(
+	kind: HitKind,
+	id: u64,
+	key: String,
+	heading: &str,
+	status: RecordStatus,
+	fields: Vec<(&'static str, String, f64)>,
+) -> Document {
+	let fields: Vec<Field> = fields
+		.into_iter()
+		.map(|(name, text, weight)| Field {
+			name,
+			words: words(&text),
+			text,
+			weight,
+		})
+		.collect();
+	let len = fields.iter().map(|f| f.weight * f.words.len() as f64).sum();
+	Document {
+		kind,
+		id,
+		key,
+		heading: heading.to_string(),
+		status,
+		fields,
+		len,
+	}
+}
+
+/// The field with the most distinct matched words, the earliest on a tie, around its first
+/// match.
+fn snippet(doc: &Document, terms: &[String]) -> Snippet {
+	let matched = |w: &Word| terms.contains(&w.stem);
+	let mut best: Option<(&Field, usize)> = None;
+	for field in doc.fields.iter() {
+		let distinct: HashSet<&str> = field
+			.words
+			.iter()
+			.filter(|w| matched(w))
+			.map(|w| w.stem.as_str())
+			.collect();
+		if distinct.len() > best.map(|(_, n)| n).unwrap_or(0) {
+			best = Some((field, distinct.len()));
+		}
+	}
+	let Some((field, _)) = best else {
+		return Snippet {
+			field: doc.fields[0].name,
+			text: doc.fields[0].text.replace('\n', " "),
+			highlights: Vec::new(),
+		};
+	};
+	let first = field.words.iter().position(matched).unwrap_or(0);
+	let from = first.saturating_sub(SNIPPET_BEFORE);
+	let to = (from + SNIPPET_WORDS).min(field.words.len());
+	let start = match from {
+		0 => 0,
+		_ => field.words[from].span.start,
+	};
+	let end = match to == field.words.len() {
+		true => field.text.len(),
+		false => field.words[to - 1].span.end,
+	};
+	let mut text = String::new();
+	if start > 0 {
+		text.push_str("… ");
+	}
+	let offset = text.len();
+	text.push_str(&field.text[start..end].replace('\n', " "));
+	if end < field.text.len() {
+		text.push_str(" …");
+	}
+	let highlights = field.words[from..to]
+		.iter()
+		.filter(|w| matched(w))
+		.map(|w| w.span.start - start + offset..w.span.end - start + offset)
+		.collect();
+	Snippet {
+		field: field.name,
+		text,
+		highlights,
+	}
+}
+
+/// The words of `text` that aren't [`STOP_WORDS`], with where they are in it.
+fn words(text: &str) -> Vec<Word> {
+	let mut words = Vec::new();
+	let mut start = None;
+	for (i, ch) in text.char_indices().chain([(text.len(), ' ')]) {
+		match (ch.is_alphanumeric(), start) {
+			(true, None) => start = Some(i),
+			(false, Some(s)) => {
+				let word = text[s..i].to_lowercase();
+				if !STOP_WORDS.contains(&word.as_str()) {
+					words.push(Word {
+						stem: stem(&word),
+						span: s..i,
+					});
+				}
+				start = None;
+			}
+			_ => {}
+		}
+	}
+	words
+}
+
+/// Strips common English suffixes from a lower case word so "brakes", "braking", and "brake"
+/// all become "brak". Not a full Porter stemmer, only consistent.
+pub fn stem(word: &str) -> String {
+	// longest first, (suffix, replacement)
+	const SUFFIXES: [(&str, &str); 17] = [
+		("ational", "ate"),
+		("ization", "ize"),
+		("ations", "ate"),
+		("ation", "ate"),
+		("ments", ""),
+		("ness", ""),
+		("ment", ""),
+		("sses", "ss"),
+		("ings", ""),
+		("edly", ""),
+		("ies", "y"),
+		("ied", "y"),
+		("ing", ""),
+		("ed", ""),
+		("ly", ""),
+		("ss", "ss"),
+		("s", ""),
+	];
+	// keeps short words like "bus" and "red" whole
+	const MIN_STEM: usize = 3;
+	let mut stem = word.to_string();
+	if let Some((suffix, replacement)) = SUFFIXES.iter().find(|(suffix, replacement)| {
+		word.ends_with(suffix)
+			&& word.chars().count() - suffix.chars().count() + replacement.chars().count()
+				>= MIN_STEM
+	}) {
+		stem.truncate(stem.len() - suffix.len());
+		stem.push_str(replacement);
+		// "stopped" and "stopping" to "stop"
+		let chars: Vec<char> = stem.chars().collect();
+		if matches!(*suffix, "ed" | "ing" | "ings" | "edly")
+			&& chars.len() > MIN_STEM
+			&& chars[chars.len() - 1] == chars[chars.len() - 2]
+			&& !matches!(chars[chars.len() - 1], 'l' | 's' | 'z')
+			&& !"aeiou".contains(chars[chars.len() - 1])
+		{
+			stem.pop();
+		}
+	}
+	if stem.chars().count() > MIN_STEM && stem.ends_with('e') {
+		stem.pop();
+	}
+	stem
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		RequirementFunctional, RequirementPriority, Tags, VerificationMethod,
+		custom_field::CustomFields,
+	};
+	use chrono::{Local, TimeZone};
+
+	fn requirement(id: u64, title: &str, text: &str, risks: &str) -> Requirement {
+		Requirement {
+			id,
+			key: format!("PWR-000{id}"),
+			component_id: 0,
+			parent_id: None,
+			title: title.into(),
+			functional: RequirementFunctional::Functional,
+			creation_date: Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap(),
+			requirement_text: text.into(),
+			acceptance_criteria: String::new(),
+			verification_method: VerificationMethod::Test,
+			version: 0,
+			author: "JM".into(),
+			priority: RequirementPriority::High,
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			risks: risks.into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		}
+	}
+
+	#[test]
+	fn test_stem() {
+		for (words, expected) in [
+			(&["brake", "brakes", "braking", "braked"][..], "brak"),
+			(&["latency", "latencies"][..], "latency"),
+			(
+				&["require", "required", "requires", "requiring"][..],
+				"requir",
+			),
+			(&["stop", "stops", "stopped", "stopping"][..], "stop"),
+			(&["validate", "validation", "validated"][..], "validat"),
+			(&["bus"][..], "bus"),
+			(&["process", "processes"][..], "process"),
+		] {
+			for word in words {
+				assert_eq!(expected, stem(word), "stem of: {word}");
+			}
+		}
+	}
+
+	#[test]
+	fn test_search() {
+		let requirements = vec![
+			requirement(1, "Cabin lighting", "Lights dim when braking.", ""),
+			requirement(
+				2,
+				"Brake latency",
+				"The brakes shall engage\\nwithin 20 ms of the pedal.",
+				"",
+			),
+			requirement(
+				3,
+				"Infotainment",
+				"Play music.",
+				"Latency of the brake bus.",
+			),
+			requirement(4, "Seats", "Heated seats.", ""),
+		];
+		let index = SearchIndex::new(&[], &requirements);
+
+		let hits = index.search("brake latency");
+		let ids: Vec<u64> = hits.iter().map(|h| h.id).collect();
+		// the title counts for more, one matched word counts for less
+		assert_eq!(vec![2, 3, 1], ids);
+		assert_eq!("title", hits[0].snippet.field);
+		assert_eq!(
+			"**Brake** **latency**",
+			hits[0].snippet.highlighted("**", "**")
+		);
+		assert_eq!("risks", hits[1].snippet.field);
+		assert_eq!(
+			"[Latency] of the [brake] bus.",
+			hits[1].snippet.highlighted("[", "]")
+		);
+
+		assert_eq!(1, index.search("ENGAGED").len());
+		assert!(index.search("the shall").is_empty());
+		assert!(index.search("").is_empty());
+	}
+
+	#[test]
+	fn test_snippet() {
+		let text = (1..=40)
+			.map(|i| format!("w{i}"))
+			.collect::<Vec<_>>()
+			.join(" ")
+			.replace("w20", "pedal");
+		let requirements = vec![requirement(1, "Title", &text, "")];
+		let hits = SearchIndex::new(&[], &requirements).search("pedal");
+		let snippet = &hits[0].snippet;
+		assert_eq!("requirement_text", snippet.field);
+		assert!(snippet.text.starts_with("… w14 "));
+		assert!(snippet.text.ends_with(" w33 …"));
+		assert_eq!(1, snippet.highlights.len());
+		assert_eq!("pedal", &snippet.text[snippet.highlights[0].clone()]);
+	}
+}
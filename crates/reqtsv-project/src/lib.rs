@@ -13,7 +13,10 @@ use chrono::{DateTime, Local};
 use log::{debug, error, info};
 use maud::{Markup, Render, html};
 use reqtsv_lib::{
-	COMPONENT_HEADER, Component, REQUIREMENT_HEADER, Requirement, SaveFileError, save_file_strict,
+	COMPONENT_HEADER, Component, REQUIREMENT_HEADER, Requirement, SaveFileError,
+	config::{ProjectConfigErr, init_project_config, load_project_config},
+	lock::{LockErr, acquire_lock},
+	save_file_strict,
 };
 use sha3::Digest;
 use thiserror::Error;
@@ -42,22 +45,24 @@ pub enum InitProjectErr {
 	WriteComponentTbl(SaveFileError),
 	#[error("can't write Requirement table due to: {0:?}")]
 	WriteRequirementTbl(SaveFileError),
+	#[error("{0:}")]
+	Config(#[source] ProjectConfigErr),
+	#[error("{0:}")]
+	Lock(#[source] LockErr),
 }
 
 pub fn init_project(project_root: impl AsRef<Path>) -> Result<(), InitProjectErr> {
-	// reqcsv.toml
-	// reqcsv.lock
 	// styles.css
+	let _lock = acquire_lock(project_root.as_ref()).map_err(InitProjectErr::Lock)?;
+	let config = load_project_config(project_root.as_ref()).map_err(InitProjectErr::Config)?;
 
-	let component_path = project_root.as_ref().join(reqtsv_lib::COMPONENT_TABLE_NAME);
+	let component_path = project_root.as_ref().join(&config.tables.component);
 	if component_path.exists() {
 		return Err(InitProjectErr::ComponentTblExists(
 			component_path.into_boxed_path(),
 		));
 	}
-	let requirement_path = project_root
-		.as_ref()
-		.join(reqtsv_lib::REQUIREMENT_TABLE_NAME);
+	let requirement_path = project_root.as_ref().join(&config.tables.requirement);
 	if requirement_path.exists() {
 		return Err(InitProjectErr::RequirementTblExists(
 			requirement_path.into_boxed_path(),
@@ -92,6 +97,8 @@ pub fn init_project(project_root: impl AsRef<Path>) -> Result<(), InitProjectErr
 	save_file_strict(requirement_file, REQUIREMENT_HEADER.as_bytes())
 		.map_err(|e| InitProjectErr::WriteRequirementTbl(e))?;
 
+	init_project_config(project_root.as_ref()).map_err(InitProjectErr::Config)?;
+
 	Ok(())
 }
 
@@ -103,6 +110,8 @@ pub trait ProjectCtx {
 	fn get_components(&self) -> &[Component];
 	fn get_requirements(&self) -> &[Requirement];
 	fn get_css_path(&self) -> &Path;
+	/// Where the docs are built.
+	fn get_output_dir(&self) -> &Path;
 }
 
 fn open_overwrite<P: AsRef<Path>>(path: P) -> Result<File, std::io::Error> {
@@ -166,7 +175,7 @@ pub fn build_docs<Ctx>(ctx: &Ctx) -> Result<(), BuildDocsErr>
 where
 	Ctx: ProjectCtx,
 {
-	let mut build_dir = BuildDir::from_pathbuf(ctx.get_project_root().join("build"));
+	let mut build_dir = BuildDir::from_pathbuf(ctx.get_output_dir().to_path_buf());
 	// find the old if it exists
 	if build_dir.exists() {
 		if build_dir.is_file() {
@@ -530,7 +539,10 @@ pub fn hashed_table(raw_table: impl AsRef<[u8]>) -> Box<str> {
 mod tests {
 	use super::*;
 	use chrono::prelude::*;
-	use reqtsv_lib::{RecordStatus, RequirementFunctional, RequirementPriority};
+	use reqtsv_lib::{
+		RecordStatus, RequirementFunctional, RequirementPriority, Tags, VerificationMethod,
+		custom_field::CustomFields,
+	};
 
 	#[test]
 	fn test_table_hash() {
@@ -571,14 +583,22 @@ mod tests {
 			fn get_css_path(&self) -> &Path {
 				todo!()
 			}
+
+			fn get_output_dir(&self) -> &Path {
+				todo!()
+			}
 		}
 		let comp = Component {
 			id: 0,
+			prefix: String::new(),
+			next_seq: 0,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		let mut ctx = MockProject([comp]);
 		let built = build_index(&mut ctx);
@@ -597,19 +617,27 @@ mod tests {
 		struct MockProject([Component; 2]);
 		let comp_a = Component {
 			id: 0,
+			prefix: String::new(),
+			next_seq: 0,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		let comp_b = Component {
 			id: 1,
+			prefix: String::new(),
+			next_seq: 0,
+			parent_id: None,
 			name: "Comp B".into(),
 			description: "Test B".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author B".into(),
+			tags: Tags::default(),
 		};
 		let components: [Component; 2] = [comp_a, comp_b];
 		impl ProjectCtx for MockProject {
@@ -640,6 +668,10 @@ mod tests {
 			fn get_css_path(&self) -> &Path {
 				todo!()
 			}
+
+			fn get_output_dir(&self) -> &Path {
+				todo!()
+			}
 		}
 		let mut ctx = MockProject(components);
 		let built = build_components(&mut ctx);
@@ -658,46 +690,68 @@ mod tests {
 		struct MockProject([Requirement; 2], [Component; 2]);
 		let req_a = Requirement {
 			id: 0,
+			key: String::new(),
 			title: "Requirement A".into(),
 			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author A".into(),
 			component_id: 0,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let req_b = Requirement {
 			id: 1,
+			key: String::new(),
 			title: "Requirement B".into(),
 			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author B".into(),
 			component_id: 1,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let requirements: [Requirement; 2] = [req_a, req_b];
 		let comp_a = Component {
 			id: 0,
+			prefix: String::new(),
+			next_seq: 0,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		let comp_b = Component {
 			id: 1,
+			prefix: String::new(),
+			next_seq: 0,
+			parent_id: None,
 			name: "Comp B".into(),
 			description: "Test B".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author B".into(),
+			tags: Tags::default(),
 		};
 		let components: [Component; 2] = [comp_a, comp_b];
 		impl ProjectCtx for MockProject {
@@ -728,6 +782,10 @@ mod tests {
 			fn get_css_path(&self) -> &Path {
 				todo!()
 			}
+
+			fn get_output_dir(&self) -> &Path {
+				todo!()
+			}
 		}
 		let mut ctx = MockProject(requirements, components);
 		let built = build_all_requirements(&mut ctx);
@@ -746,46 +804,68 @@ mod tests {
 		struct MockProject([Requirement; 2], [Component; 2]);
 		let req_a = Requirement {
 			id: 0,
+			key: String::new(),
 			title: "Requirement A".into(),
 			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author A".into(),
 			component_id: 0,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let req_b = Requirement {
 			id: 1,
+			key: String::new(),
 			title: "Requirement B".into(),
 			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author B".into(),
 			component_id: 1,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let requirements: [Requirement; 2] = [req_a, req_b];
 		let comp_a = Component {
 			id: 0,
+			prefix: String::new(),
+			next_seq: 0,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		let comp_b = Component {
 			id: 1,
+			prefix: String::new(),
+			next_seq: 0,
+			parent_id: None,
 			name: "Comp B".into(),
 			description: "Test B".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author B".into(),
+			tags: Tags::default(),
 		};
 		// this is the key part of this test!
 		let components: [Component; 2] = [comp_b, comp_a];
@@ -817,6 +897,10 @@ mod tests {
 			fn get_css_path(&self) -> &Path {
 				todo!()
 			}
+
+			fn get_output_dir(&self) -> &Path {
+				todo!()
+			}
 		}
 		let mut ctx = MockProject(requirements, components);
 		let built = build_all_requirements(&mut ctx);
@@ -835,38 +919,56 @@ mod tests {
 		struct MockProject([Requirement; 2], [Component; 1]);
 		let req_a = Requirement {
 			id: 0,
+			key: String::new(),
 			title: "Requirement A".into(),
 			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author A".into(),
 			component_id: 0,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let req_b = Requirement {
 			id: 1,
+			key: String::new(),
 			title: "Requirement B".into(),
 			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author B".into(),
 			component_id: 1,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let requirements: [Requirement; 2] = [req_a, req_b];
 		let comp_a = Component {
 			id: 0,
+			prefix: String::new(),
+			next_seq: 0,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		// this is the key part of this test!
 		let components: [Component; 1] = [comp_a];
@@ -898,6 +1000,10 @@ mod tests {
 			fn get_css_path(&self) -> &Path {
 				todo!()
 			}
+
+			fn get_output_dir(&self) -> &Path {
+				todo!()
+			}
 		}
 		let mut ctx = MockProject(requirements, components);
 		let built = build_all_requirements(&mut ctx);
@@ -916,51 +1022,76 @@ mod tests {
 		struct MockProject([Requirement; 3]);
 		let req_a = Requirement {
 			id: 0,
+			key: String::new(),
 			title: "Requirement A".into(),
 			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: "Show it does A".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author A".into(),
 			component_id: 0,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let req_b = Requirement {
 			id: 1,
+			key: String::new(),
 			title: "Requirement B".into(),
 			requirement_text: "Thing shall do B".into(),
+			acceptance_criteria: "Show it does B".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 17, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author B".into(),
 			component_id: 1,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk B".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let req_c = Requirement {
 			id: 2,
+			key: String::new(),
 			title: "Requirement C".into(),
 			requirement_text: "Thing shall do c".into(),
+			acceptance_criteria: "Show it does c".into(),
+			verification_method: VerificationMethod::Test,
 			creation_date: Local.with_ymd_and_hms(2025, 06, 18, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
+			status_justification: String::new(),
 			author: "Author C".into(),
 			component_id: 0,
+			parent_id: None,
 			functional: RequirementFunctional::Functional,
 			version: 0,
 			priority: RequirementPriority::Mandated,
 			risks: "Risk C".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
 		};
 		let requirements: [Requirement; 3] = [req_a, req_b, req_c];
 		let component = Component {
 			id: 0,
+			prefix: String::new(),
+			next_seq: 0,
+			parent_id: None,
 			name: "Comp A".into(),
 			description: "Test A".into(),
 			creation_date: Local.with_ymd_and_hms(2025, 06, 16, 0, 0, 0).unwrap(),
 			status: RecordStatus::Accepted,
 			author: "Author A".into(),
+			tags: Tags::default(),
 		};
 		// this is the key part of this test!
 		impl ProjectCtx for MockProject {
@@ -991,6 +1122,10 @@ mod tests {
 			fn get_css_path(&self) -> &Path {
 				todo!()
 			}
+
+			fn get_output_dir(&self) -> &Path {
+				todo!()
+			}
 		}
 		let mut ctx = MockProject(requirements);
 		let built = build_a_component(&mut ctx, &component);
@@ -3,7 +3,7 @@ use std::{io::Write, path::Path};
 
 use argh::FromArgs;
 use log::{LevelFilter, error, info};
-use reqtsv_lib::{Project, get_project_root};
+use reqtsv_lib::{Project, filter::FilterErr, get_project_root};
 use reqtsv_project::{
 	BuildDocsErr, InitProjectErr, ProjectCtx, build_docs, hashed_table, init_project,
 };
@@ -48,9 +48,19 @@ fn main() -> Result<(), Error> {
 	}
 
 	if reqtsv.build {
-		info!("Building requirements docs at: {:?}/docs", &project_root);
-		let project = get_project_root(&project_root).map_err(|gpre| Error::ProjectRoot(gpre))?;
+		let mut project =
+			get_project_root(&project_root).map_err(|gpre| Error::ProjectRoot(gpre))?;
+		if reqtsv.exclude_deleted || project.config.exclude_deleted {
+			project.exclude_deleted();
+		}
+		if let Some(filter) = &reqtsv.filter {
+			let filter = filter.parse().map_err(Error::Filter)?;
+			project
+				.filter_requirements(&filter)
+				.map_err(Error::Filter)?;
+		}
 		let mut ctx = CtxImpl::from(project);
+		info!("Building requirements docs at: {:?}", &ctx.output_dir);
 		build_docs(&mut ctx).map_err(|e| Error::BuildDocs(e))?
 	}
 
@@ -62,6 +72,7 @@ struct CtxImpl {
 	component_tlb_hash: Box<str>,
 	requirement_tlb_hash: Box<str>,
 	css_path: Box<Path>,
+	output_dir: Box<Path>,
 }
 
 impl From<Project> for CtxImpl {
@@ -69,7 +80,8 @@ impl From<Project> for CtxImpl {
 		Self {
 			component_tlb_hash: hashed_table(project.raw_components.as_bytes()),
 			requirement_tlb_hash: hashed_table(project.raw_requirements.as_bytes()),
-			css_path: project.root.join("styles.css").into_boxed_path(),
+			css_path: project.config.css_path(&project.root).into_boxed_path(),
+			output_dir: project.config.output_dir(&project.root).into_boxed_path(),
 			project,
 		}
 	}
@@ -77,7 +89,7 @@ impl From<Project> for CtxImpl {
 
 impl ProjectCtx for CtxImpl {
 	fn get_project_title(&self) -> &str {
-		"TODO Reqcsv Title"
+		&self.project.project_title
 	}
 
 	fn get_project_root(&self) -> &std::path::Path {
@@ -103,6 +115,10 @@ impl ProjectCtx for CtxImpl {
 	fn get_css_path(&self) -> &std::path::Path {
 		&self.css_path
 	}
+
+	fn get_output_dir(&self) -> &std::path::Path {
+		&self.output_dir
+	}
 }
 
 #[derive(Error, Debug)]
@@ -113,6 +129,8 @@ enum Error {
 	BuildDocs(BuildDocsErr),
 	#[error("Failed to open project: {0:}")]
 	ProjectRoot(reqtsv_lib::GetProjectRootErr),
+	#[error("Failed to filter requirements: {0:}")]
+	Filter(FilterErr),
 }
 
 #[derive(FromArgs, Debug, PartialEq)]
@@ -130,6 +148,13 @@ struct ReqtsvProject {
 	#[argh(switch, short = 'b')]
 	/// build the specified project
 	build: bool,
+	#[argh(switch)]
+	/// leave deleted records out of the build, defaults to the project config
+	exclude_deleted: bool,
+	#[argh(option)]
+	/// only put the requirements matching a filter expression in the build e.g.
+	/// "priority = High and status = Accepted", see reqtsv_lib::filter
+	filter: Option<String>,
 	#[argh(switch, short = 'v')]
 	/// verbose logging
 	verbose: bool,
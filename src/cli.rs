@@ -0,0 +1,712 @@
+//! Subcommands that change or print the tables without prompting, for scripts and CI e.g.
+//! `reqtsv ./reqs requirement set-status NAV-2 approved --why "reviewed 2024-05-01"`.
+//!
+//! Field values come from a draft or edit file given with `--file`, then `--set field=value`
+//! arguments on top, and go through the same validation as the menus. `list` and `show` print
+//! JSON with `--format`, see [`reqtsv_lib::json`], and `list --filter` takes an expression, see
+//! [`reqtsv_lib::filter`]. `search` ranks records by the words in them, and `check` validates a
+//! project for CI.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use argh::FromArgs;
+use const_format::formatcp;
+use inline_colorization::*;
+use reqtsv_lib::{
+	Project,
+	check::{Severity, check_project},
+	config::{ProjectConfig, load_project_config},
+	filter::{Filter, select},
+	get_project_root,
+	json::{ComponentJson, JsonFormat, RequirementJson, write_list, write_record},
+	lock::{LockErr, ProjectLock, acquire_lock},
+	search::SearchIndex,
+};
+use thiserror::Error;
+
+use crate::{
+	AppCtx, EditFile, RecordType, apply_edit,
+	component::{Component, ComponentTomlDraft, insert_component},
+	delete_record_by_id, err_loc, find_id_by_id_or_key, recover_tables, ref_record_by_id,
+	requirement::{Requirement, RequirementEdit, insert_requirement, set_component, set_status},
+	search::print_hits,
+};
+
+/// Why a subcommand failed, scripts tell them apart by [`Failure::exit_code`]. Any other error
+/// exits with 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum Failure {
+	#[error("no such record")]
+	NotFound,
+	#[error("refused")]
+	Refused,
+	#[error("project is locked")]
+	Locked,
+	#[error("project check failed")]
+	CheckFailed,
+}
+
+impl Failure {
+	pub fn exit_code(self) -> i32 {
+		match self {
+			Failure::NotFound => 2,
+			Failure::Refused => 3,
+			Failure::Locked => 4,
+			Failure::CheckFailed => 5,
+		}
+	}
+}
+
+/// The code to exit with for an error returned by [`run_component`], [`run_requirement`], or
+/// [`run_check`].
+pub fn exit_code(e: &anyhow::Error) -> i32 {
+	e.downcast_ref::<Failure>()
+		.map(|failure| failure.exit_code())
+		.unwrap_or(1)
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "component")]
+/// List, show, add, edit, or delete components without prompting.
+pub struct ComponentCmd {
+	#[argh(subcommand)]
+	action: ComponentAction,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand)]
+enum ComponentAction {
+	List(List),
+	Show(Show),
+	Add(ComponentAdd),
+	Edit(Edit),
+	Delete(Delete),
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "requirement")]
+/// List, show, add, edit, or delete requirements, or move them between components and statuses,
+/// without prompting.
+pub struct RequirementCmd {
+	#[argh(subcommand)]
+	action: RequirementAction,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand)]
+enum RequirementAction {
+	List(List),
+	Show(Show),
+	Add(RequirementAdd),
+	Edit(Edit),
+	Delete(Delete),
+	SetComponent(SetComponent),
+	SetStatus(SetStatus),
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "list")]
+/// Print a tab separated line for each record: key, ID, status, then the name or title.
+struct List {
+	#[argh(option)]
+	/// print "json" or "ndjson" instead, see reqtsv_lib::json for the schema
+	format: Option<JsonFormat>,
+	#[argh(option)]
+	/// only print the records matching a filter expression e.g.
+	/// "priority in (High, Mandated) and creation_date > 2025-06-01", see reqtsv_lib::filter
+	filter: Option<String>,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "show")]
+/// Print every field of a record.
+struct Show {
+	#[argh(positional)]
+	/// ID or key of the record
+	id_or_key: String,
+	#[argh(option)]
+	/// print "json" or "ndjson" instead, see reqtsv_lib::json for the schema
+	format: Option<JsonFormat>,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "add")]
+/// Insert an accepted component.
+struct ComponentAdd {
+	#[argh(option)]
+	/// component draft file to read the fields from
+	file: Option<PathBuf>,
+	#[argh(option)]
+	/// field=value, applied after the file, values starting with "[" are TOML arrays e.g.
+	/// tags=["ui"]
+	set: Vec<String>,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "add")]
+/// Insert a proposed requirement.
+struct RequirementAdd {
+	#[argh(option)]
+	/// ID or prefix of the accepted component the requirement is for
+	component: String,
+	#[argh(option)]
+	/// requirement draft file to read the fields from
+	file: Option<PathBuf>,
+	#[argh(option)]
+	/// field=value, applied after the file, values starting with "[" are TOML arrays e.g.
+	/// tags=["ui"], custom fields are set with custom.name=value
+	set: Vec<String>,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "edit")]
+/// Change the fields of a record, fields not given keep their current value.
+struct Edit {
+	#[argh(positional)]
+	/// ID or key of the record
+	id_or_key: String,
+	#[argh(option)]
+	/// edit file to read the fields from
+	file: Option<PathBuf>,
+	#[argh(option)]
+	/// field=value, applied after the file, values starting with "[" are TOML arrays e.g.
+	/// tags=["ui"], custom fields are set with custom.name=value
+	set: Vec<String>,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "delete")]
+/// Mark a record deleted, it stays in the table until purged.
+struct Delete {
+	#[argh(positional)]
+	/// ID or key of the record
+	id_or_key: String,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "set-component")]
+/// Move a requirement to another accepted component, it keeps its key.
+struct SetComponent {
+	#[argh(positional)]
+	/// ID or key of the requirement
+	id_or_key: String,
+	#[argh(positional)]
+	/// ID or prefix of the component
+	component: String,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "set-status")]
+/// Move a requirement along its lifecycle e.g. from Proposed to Approved.
+struct SetStatus {
+	#[argh(positional)]
+	/// ID or key of the requirement
+	id_or_key: String,
+	#[argh(positional)]
+	/// the new status, case doesn't matter
+	status: String,
+	#[argh(option)]
+	/// why the status is changing
+	why: String,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "search")]
+/// Print the requirements and components best matching some words, with the matches
+/// highlighted, see reqtsv_lib::search.
+pub struct SearchCmd {
+	#[argh(positional)]
+	/// words to look for
+	query: Vec<String>,
+	#[argh(option, default = "20")]
+	/// most matches to print, defaults to 20
+	limit: usize,
+	#[argh(switch)]
+	/// print deleted records too
+	deleted: bool,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "check")]
+/// Check the tables and draft and edit files without opening the project, printing every problem
+/// with its file and line, see reqtsv_lib::check. Exits with 5 when there are errors.
+pub struct CheckCmd {
+	#[argh(switch, short = 'W')]
+	/// fail on warnings too
+	warnings_as_errors: bool,
+	#[argh(option)]
+	/// print "json" for the whole report or "ndjson" for a line per problem instead
+	format: Option<JsonFormat>,
+}
+
+pub fn run_component(project_root: Box<Path>, read_only: bool, cmd: ComponentCmd) -> Result<()> {
+	match cmd.action {
+		ComponentAction::List(list) => {
+			let filter = parse_filter(list.filter.as_deref())?;
+			let project = load_project(&project_root)?;
+			let components = select(project.components_json(), filter.as_ref())
+				.context(formatcp!("{} can't filter components", err_loc!()))
+				.context(Failure::Refused)?;
+			match list.format {
+				Some(format) => print_json(|out| write_list(out, format, &components)),
+				None => {
+					for component in components {
+						println!(
+							"{}\t{}\t{}\t{}",
+							component.key, component.id, component.status, component.name
+						);
+					}
+					Ok(())
+				}
+			}
+		}
+		ComponentAction::Show(Show {
+			id_or_key,
+			format: Some(format),
+		}) => {
+			let project = load_project(&project_root)?;
+			let component = find_in_project(&project.components, &id_or_key, |c| (c.id, c.key()))?;
+			let json = ComponentJson::new(component, &project.components);
+			print_json(|out| write_record(out, format, &json))
+		}
+		ComponentAction::Show(show) => {
+			let ctx = open(project_root, true)?;
+			let id = find_record::<Component>(&ctx, &show.id_or_key)?;
+			let component = ref_record_by_id::<Component>(&ctx, id).expect("found above");
+			print!("{component}");
+			Ok(())
+		}
+		ComponentAction::Add(add) => change(project_root, read_only, |ctx| {
+			let mut table = toml::Table::new();
+			if !ctx.config.default_author.is_empty() {
+				table.insert(
+					"author".into(),
+					toml::Value::String(ctx.config.default_author.clone()),
+				);
+			}
+			let table = field_values(table, add.file.as_deref(), &add.set)?;
+			let draft = ComponentTomlDraft::from_table(table).context(Failure::Refused)?;
+			insert_component(ctx, draft)
+				.context(Failure::Refused)
+				.map(|_id| ())
+		}),
+		ComponentAction::Edit(edit) => change(project_root, read_only, |ctx| {
+			edit_record::<Component>(ctx, edit)
+		}),
+		ComponentAction::Delete(delete) => change(project_root, read_only, |ctx| {
+			delete_by_id_or_key::<Component>(ctx, &delete.id_or_key)
+		}),
+	}
+}
+
+pub fn run_requirement(
+	project_root: Box<Path>,
+	read_only: bool,
+	cmd: RequirementCmd,
+) -> Result<()> {
+	match cmd.action {
+		RequirementAction::List(list) => {
+			let filter = parse_filter(list.filter.as_deref())?;
+			let project = load_project(&project_root)?;
+			let requirements = select(project.requirements_json(), filter.as_ref())
+				.context(formatcp!("{} can't filter requirements", err_loc!()))
+				.context(Failure::Refused)?;
+			match list.format {
+				Some(format) => print_json(|out| write_list(out, format, &requirements)),
+				None => {
+					for requirement in requirements {
+						println!(
+							"{}\t{}\t{}\t{}",
+							requirement.key, requirement.id, requirement.status, requirement.title
+						);
+					}
+					Ok(())
+				}
+			}
+		}
+		RequirementAction::Show(Show {
+			id_or_key,
+			format: Some(format),
+		}) => {
+			let project = load_project(&project_root)?;
+			let requirement =
+				find_in_project(&project.requirements, &id_or_key, |r| (r.id, r.key()))?;
+			let json = RequirementJson::new(
+				requirement,
+				&project.components,
+				&project.requirements,
+				&project.config.fields,
+			);
+			print_json(|out| write_record(out, format, &json))
+		}
+		RequirementAction::Show(show) => {
+			let ctx = open(project_root, true)?;
+			let id = find_record::<Requirement>(&ctx, &show.id_or_key)?;
+			let requirement = ref_record_by_id::<Requirement>(&ctx, id).expect("found above");
+			print!("{requirement}");
+			Ok(())
+		}
+		RequirementAction::Add(add) => change(project_root, read_only, |ctx| {
+			let component_id = find_record::<Component>(ctx, &add.component)?;
+			let mut table = toml::Table::new();
+			if !ctx.config.default_author.is_empty() {
+				table.insert(
+					"author".into(),
+					toml::Value::String(ctx.config.default_author.clone()),
+				);
+			}
+			let table = field_values(table, add.file.as_deref(), &add.set)?;
+			let draft: RequirementEdit =
+				edit_from_table(table, &ctx.config).context(Failure::Refused)?;
+			insert_requirement(ctx, component_id, draft)
+				.context(Failure::Refused)
+				.map(|_id| ())
+		}),
+		RequirementAction::Edit(edit) => change(project_root, read_only, |ctx| {
+			edit_record::<Requirement>(ctx, edit)
+		}),
+		RequirementAction::Delete(delete) => change(project_root, read_only, |ctx| {
+			delete_by_id_or_key::<Requirement>(ctx, &delete.id_or_key)
+		}),
+		RequirementAction::SetComponent(args) => change(project_root, read_only, |ctx| {
+			let id = find_record::<Requirement>(ctx, &args.id_or_key)?;
+			let component_id = find_record::<Component>(ctx, &args.component)?;
+			set_component(ctx, id, component_id).context(Failure::Refused)
+		}),
+		RequirementAction::SetStatus(args) => change(project_root, read_only, |ctx| {
+			let id = find_record::<Requirement>(ctx, &args.id_or_key)?;
+			let requirement = ref_record_by_id::<Requirement>(ctx, id).expect("found above");
+			let current = requirement.get_status();
+			let next = current.requirement_transitions();
+			let status = next
+				.iter()
+				.find(|status| status.to_string().eq_ignore_ascii_case(args.status.trim()))
+				.copied()
+				.ok_or_else(|| {
+					let next: Vec<String> = next.iter().map(ToString::to_string).collect();
+					anyhow!(format!(
+						"{} {} can't move from status: {current} to: \"{}\", it can move to: [{}]",
+						err_loc!(),
+						requirement.get_key(),
+						args.status,
+						next.join(", ")
+					))
+				})
+				.context(Failure::Refused)?;
+			set_status(ctx, id, status, &args.why).context(Failure::Refused)
+		}),
+	}
+}
+
+pub fn run_search(project_root: Box<Path>, cmd: SearchCmd) -> Result<()> {
+	let project = load_project(&project_root)?;
+	let hits =
+		SearchIndex::new(&project.components, &project.requirements).search(&cmd.query.join(" "));
+	print_hits(&hits, cmd.limit, cmd.deleted);
+	Ok(())
+}
+
+pub fn run_check(project_root: Box<Path>, cmd: CheckCmd) -> Result<()> {
+	let report = check_project(&project_root);
+	match cmd.format {
+		Some(JsonFormat::Json) => print_json(|out| write_record(out, JsonFormat::Json, &report))?,
+		Some(JsonFormat::Ndjson) => {
+			print_json(|out| write_list(out, JsonFormat::Ndjson, &report.issues))?
+		}
+		None => {
+			for issue in report.issues.iter() {
+				match issue.severity {
+					Severity::Error => println!("{color_red}{issue}{color_reset}"),
+					Severity::Warning => println!("{color_yellow}{issue}{color_reset}"),
+				}
+			}
+			println!("{} errors, {} warnings", report.errors, report.warnings);
+		}
+	}
+	if report.failed(cmd.warnings_as_errors) {
+		return Err(anyhow!(format!(
+			"{} {} errors, {} warnings",
+			err_loc!(),
+			report.errors,
+			report.warnings
+		)))
+		.context(Failure::CheckFailed);
+	}
+	Ok(())
+}
+
+/// Opens the project like the menus do, without prompting. Read-only projects aren't locked
+/// or recovered, that is up to the session holding the lock.
+fn open(project_root: Box<Path>, read_only: bool) -> Result<AppCtx> {
+	let config = load_project_config(&project_root).context("Failed to load project config")?;
+	if !read_only {
+		let (journal, recoveries) = recover_tables(&project_root, &config.tables)?;
+		if let Some(journal) = journal {
+			eprintln!("{color_yellow}Recovered interrupted commit, {journal}{color_reset}");
+		}
+		for recovery in recoveries {
+			eprintln!("{color_yellow}Recovered interrupted save of {recovery}{color_reset}");
+		}
+	}
+	AppCtx::load(project_root, config, read_only)
+}
+
+/// The tables as [`reqtsv_lib`] reads them, for the JSON output.
+fn load_project(project_root: &Path) -> Result<Project> {
+	get_project_root(project_root).context(formatcp!("{} can't load project", err_loc!()))
+}
+
+/// `None` without an expression, a bad one is refused.
+pub fn parse_filter(expr: Option<&str>) -> Result<Option<Filter>> {
+	expr.filter(|expr| !expr.trim().is_empty())
+		.map(|expr| {
+			expr.parse::<Filter>()
+				.context(formatcp!("{} bad filter", err_loc!()))
+				.context(Failure::Refused)
+		})
+		.transpose()
+}
+
+/// Like [`find_id_by_id_or_key`] for the records of a [`Project`], `id_key` gives the ID and
+/// key of a record.
+fn find_in_project<'r, R>(
+	records: &'r [R],
+	id_or_key: &str,
+	id_key: impl Fn(&R) -> (u64, String),
+) -> Result<&'r R> {
+	let id_or_key = id_or_key.trim();
+	records
+		.iter()
+		.find(|r| id_key(r).1.eq_ignore_ascii_case(id_or_key))
+		.or_else(|| {
+			let id = id_or_key.parse::<u64>().ok()?;
+			records.iter().find(|r| id_key(r).0 == id)
+		})
+		.ok_or_else(|| {
+			anyhow!(format!(
+				"{} no record with ID or key: \"{id_or_key}\"",
+				err_loc!()
+			))
+		})
+		.context(Failure::NotFound)
+}
+
+fn print_json(
+	write: impl FnOnce(&mut std::io::StdoutLock) -> Result<(), serde_json::Error>,
+) -> Result<()> {
+	write(&mut std::io::stdout().lock()).context(formatcp!("{} can't print JSON", err_loc!()))
+}
+
+/// Locks and opens the project, runs `change`, then saves the changed tables.
+fn change(
+	project_root: Box<Path>,
+	read_only: bool,
+	change: impl FnOnce(&mut AppCtx) -> Result<()>,
+) -> Result<()> {
+	let lock: Option<ProjectLock> = match read_only {
+		true => None,
+		false => Some(acquire_lock(&project_root).map_err(|e| match e {
+			LockErr::Held(_) | LockErr::Busy(_) => anyhow!(e).context(Failure::Locked),
+			e => anyhow!(e).context(formatcp!("{} can't lock project", err_loc!())),
+		})?),
+	};
+	let mut ctx = open(project_root, read_only)?;
+	// nothing was changed if it failed, there is nothing to save
+	change(&mut ctx)?;
+	let saved = ctx.save();
+	drop(lock);
+	saved
+}
+
+fn find_record<R: RecordType>(ctx: &AppCtx, id_or_key: &str) -> Result<u64> {
+	find_id_by_id_or_key::<R>(ctx, id_or_key.trim()).context(Failure::NotFound)
+}
+
+fn edit_record<R: RecordType>(ctx: &mut AppCtx, edit: Edit) -> Result<()>
+where
+	for<'r> R::EditFile: From<&'r R>,
+{
+	let id = find_record::<R>(ctx, &edit.id_or_key)?;
+	let record = ref_record_by_id::<R>(ctx, id).expect("found above");
+	let current = match toml::Value::try_from(R::EditFile::from(record)) {
+		Ok(toml::Value::Table(table)) => table,
+		_ => {
+			return Err(anyhow!(formatcp!(
+				"{} can't convert record to an edit file",
+				err_loc!()
+			)));
+		}
+	};
+	let key = record.get_key().into_owned();
+	let table = field_values(current, edit.file.as_deref(), &edit.set)?;
+	let edit_file: R::EditFile = edit_from_table(table, &ctx.config).context(Failure::Refused)?;
+	apply_edit::<R>(ctx, id, edit_file).context(Failure::Refused)?;
+	println!("Updated: {key}");
+	Ok(())
+}
+
+fn delete_by_id_or_key<R: RecordType>(ctx: &mut AppCtx, id_or_key: &str) -> Result<()> {
+	let id = find_record::<R>(ctx, id_or_key)?;
+	delete_record_by_id::<R>(ctx, id).context(Failure::Refused)?;
+	println!("Deleted: {}", id_or_key.trim());
+	Ok(())
+}
+
+/// Deserializes and sanitizes an edit file given as a TOML table.
+fn edit_from_table<EF: EditFile>(table: toml::Table, config: &ProjectConfig) -> Result<EF> {
+	let edit: EF = toml::Value::Table(table)
+		.try_into()
+		.context(formatcp!("{} bad field values", err_loc!()))?;
+	edit.sanitize(config)
+}
+
+/// `fields` with the file's fields, then each `--set`, put over it. Tables such as `[custom]`
+/// are merged rather than replaced.
+fn field_values(
+	mut fields: toml::Table,
+	file: Option<&Path>,
+	set: &[String],
+) -> Result<toml::Table> {
+	if let Some(path) = file {
+		let raw = std::fs::read_to_string(path)
+			.with_context(|| format!("{} can't read file: {:?}", err_loc!(), path))
+			.context(Failure::NotFound)?;
+		let from_file: toml::Table = toml::from_str(&raw)
+			.with_context(|| format!("{} bad file content format in: {:?}", err_loc!(), path))
+			.context(Failure::Refused)?;
+		merge_fields(&mut fields, from_file);
+	}
+	for arg in set {
+		let (name, value) = parse_set(arg).context(Failure::Refused)?;
+		let mut path: Vec<&str> = name.split('.').collect();
+		let last = path.pop().expect("split gives at least one item");
+		let mut set_field = toml::Table::new();
+		set_field.insert(last.to_string(), value);
+		let set_field = path.into_iter().rev().fold(set_field, |inner, table| {
+			let mut outer = toml::Table::new();
+			outer.insert(table.to_string(), toml::Value::Table(inner));
+			outer
+		});
+		merge_fields(&mut fields, set_field);
+	}
+	Ok(fields)
+}
+
+fn merge_fields(into: &mut toml::Table, from: toml::Table) {
+	for (name, value) in from {
+		match (into.get_mut(&name), value) {
+			(Some(toml::Value::Table(into)), toml::Value::Table(from)) => merge_fields(into, from),
+			(_, value) => {
+				into.insert(name, value);
+			}
+		}
+	}
+}
+
+/// Splits "field=value", the value is a string unless it starts with "[".
+fn parse_set(arg: &str) -> Result<(&str, toml::Value)> {
+	let (name, value) = arg.split_once('=').ok_or_else(|| {
+		anyhow!(format!(
+			"{} expected field=value, found: \"{arg}\"",
+			err_loc!()
+		))
+	})?;
+	let name = name.trim();
+	if name.is_empty() || name.split('.').any(str::is_empty) {
+		return Err(anyhow!(format!(
+			"{} no field name in: \"{arg}\"",
+			err_loc!()
+		)));
+	}
+	if !value.trim_start().starts_with('[') {
+		return Ok((name, toml::Value::String(value.to_string())));
+	}
+	let parsed: toml::Table = toml::from_str(&format!("value = {value}"))
+		.with_context(|| format!("{} bad TOML array in: \"{arg}\"", err_loc!()))?;
+	let value = parsed.into_iter().next().map(|(_, value)| value);
+	Ok((name, value.expect("parsed from \"value = \"")))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_set() {
+		let (name, value) = parse_set(" title=Thing shall do A").unwrap();
+		assert_eq!(
+			("title", toml::Value::String("Thing shall do A".into())),
+			(name, value)
+		);
+		// only the first "=" splits
+		let (name, value) = parse_set("custom.note=a=b").unwrap();
+		assert_eq!(
+			("custom.note", toml::Value::String("a=b".into())),
+			(name, value)
+		);
+		let (name, value) = parse_set("tags=[\"a\", \"b\"]").unwrap();
+		assert_eq!(
+			("tags", toml::Value::Array(vec!["a".into(), "b".into()])),
+			(name, value)
+		);
+
+		assert!(parse_set("title").is_err());
+		assert!(parse_set("=value").is_err());
+		assert!(parse_set("custom.=value").is_err());
+		assert!(parse_set("tags=[\"a\"").is_err());
+	}
+
+	#[test]
+	fn test_field_values() {
+		let fields: toml::Table = toml::from_str(
+			"title = \"A\"\nauthor = \"Author A\"\n[custom]\nsupplier = \"S\"\nnote = \"N\"\n",
+		)
+		.unwrap();
+		let set = [
+			"title=B".to_string(),
+			"custom.note=M".to_string(),
+			"custom.nested.depth=2".to_string(),
+		];
+		let merged = field_values(fields, None, &set).unwrap();
+		let expected: toml::Table = toml::from_str(
+			"title = \"B\"\nauthor = \"Author A\"\n[custom]\nsupplier = \"S\"\nnote = \"M\"\n[custom.nested]\ndepth = \"2\"\n",
+		)
+		.unwrap();
+		assert_eq!(expected, merged);
+
+		// a value replaces a table rather than merging in to it
+		let mut into: toml::Table = toml::from_str("[custom]\nnote = \"N\"\n").unwrap();
+		merge_fields(&mut into, toml::from_str("custom = \"none\"\n").unwrap());
+		assert_eq!(toml::Value::String("none".into()), into["custom"]);
+
+		let e = field_values(toml::Table::new(), None, &["title".to_string()]).unwrap_err();
+		assert_eq!(Failure::Refused.exit_code(), exit_code(&e));
+		let missing = Path::new("/nonexistent/reqtsv-cli-test.toml");
+		let e = field_values(toml::Table::new(), Some(missing), &[]).unwrap_err();
+		assert_eq!(Failure::NotFound.exit_code(), exit_code(&e));
+	}
+
+	#[test]
+	fn test_exit_code() {
+		assert_eq!(
+			vec![2, 3, 4, 5],
+			[
+				Failure::NotFound,
+				Failure::Refused,
+				Failure::Locked,
+				Failure::CheckFailed
+			]
+			.map(Failure::exit_code)
+		);
+		assert_eq!(1, exit_code(&anyhow!("something else")));
+		assert_eq!(4, exit_code(&anyhow::Error::new(Failure::Locked)));
+
+		// the failure is found under context added after it
+		let e = Err::<(), _>(anyhow!("no requirement: NAV-0009"))
+			.context(Failure::NotFound)
+			.context("can't show requirement")
+			.unwrap_err();
+		assert_eq!(2, exit_code(&e));
+	}
+}
@@ -3,6 +3,10 @@ use std::{borrow::Cow, fmt::Display, io::Read, path::Path};
 use anyhow::{Context, Result, anyhow};
 use chrono::{DateTime, Local};
 use const_format::formatcp;
+use reqtsv_lib::{
+	MAX_KEY_PREFIX_LEN, Tags, component_path_by, config::ProjectConfig, creates_cycle,
+	format_requirement_key, is_valid_key_prefix,
+};
 use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
@@ -10,8 +14,12 @@ use strum_macros::EnumString;
 use walkdir::DirEntry;
 
 use crate::{
-	AppCtx, EditFile, RecordStatus, RecordType, contains_any, create_draft_file, create_edit_file,
-	delete_record, err_loc, escape_normalize_nl, file_list_chose,
+	AppCtx, EditFile, RecordStatus, RecordType, check_tags, contains_any, create_draft_file,
+	create_edit_file, delete_record, err_loc, escape_normalize_nl, file_list_chose,
+	find_id_by_id_or_key, fmt_draft_author_field, fmt_tags_field, list, mut_record_by_id,
+	project::IdMap,
+	prompt_for_record_id,
+	purge::next_id,
 	select_menu::{AfterRun, SelectMenu},
 	update_record,
 };
@@ -27,31 +35,43 @@ pub trait ComponentMenuCtx: AsRef<Path> + AsMut<Vec<Component>> {}
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct ComponentEdit {
 	name: String,
+	/// Only applied to components that don't have a prefix yet.
+	#[serde(default)]
+	prefix: Option<String>,
 	description: String,
 	author: String,
+	#[serde(default)]
+	tags: Vec<String>,
 }
 
 impl Default for ComponentEdit {
 	fn default() -> Self {
 		Self {
 			name: "write component name".into(),
+			prefix: None,
 			description: "write description".into(),
 			author: "author name or initials here".into(),
+			tags: Vec::new(),
 		}
 	}
 }
 
 impl EditFile for ComponentEdit {
-	fn sanitize(self) -> Result<Self> {
+	fn sanitize(self, _config: &ProjectConfig) -> Result<Self> {
 		sanitize_component_edit(self)
 	}
 
-	fn fmt_as_draft(f: &mut impl std::fmt::Write) -> std::fmt::Result {
+	fn fmt_as_draft(f: &mut impl std::fmt::Write, config: &ProjectConfig) -> std::fmt::Result {
 		writeln!(
 			f,
 			"# Do not include any tab characters in the document\n# Do not include any new-lines in the name field"
 		)?;
 		writeln!(f, "name = \"type name here\"\n")?;
+		writeln!(
+			f,
+			"# Requirement keys start with this prefix (e.g. \"PWR\" gives \"PWR-0001\")\n# Use 1 to {MAX_KEY_PREFIX_LEN} upper case letters or digits, it can't be changed later"
+		)?;
+		writeln!(f, "prefix = \"TYPE PREFIX HERE\"\n")?;
 		writeln!(
 			f,
 			"# If writing on multiple lines use triple quotes (e.g. \"\"\"stuff\"\"\")"
@@ -60,8 +80,9 @@ impl EditFile for ComponentEdit {
 			f,
 			"desciption = \"\"\"write description here\nuse more than one line if you want\"\"\"\n"
 		)?;
-		writeln!(f, "# Do not include any new-lines in the author field")?;
-		writeln!(f, "author = \"author name here\"")
+		fmt_draft_author_field(f, config, "author name here")?;
+		writeln!(f)?;
+		fmt_tags_field(f, &[])
 	}
 }
 
@@ -69,8 +90,10 @@ impl From<&Component> for ComponentEdit {
 	fn from(value: &Component) -> Self {
 		Self {
 			name: value.name.to_string(),
+			prefix: None,
 			description: value.description.to_string(),
 			author: value.author.to_string(),
+			tags: value.tags.0.clone(),
 		}
 	}
 }
@@ -79,8 +102,10 @@ impl From<Component> for ComponentEdit {
 	fn from(value: Component) -> Self {
 		Self {
 			name: value.name,
+			prefix: None,
 			description: value.description,
 			author: value.author,
+			tags: value.tags.0,
 		}
 	}
 }
@@ -88,11 +113,19 @@ impl From<Component> for ComponentEdit {
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Component {
 	id: u64,
+	#[serde(default)]
+	prefix: String,
+	#[serde(default)]
+	next_seq: u64,
+	#[serde(default)]
+	parent_id: Option<u64>,
 	name: String,
 	description: String,
 	creation_date: DateTime<Local>,
 	status: RecordStatus,
 	author: String,
+	#[serde(default)]
+	tags: Tags,
 }
 
 impl RecordType for Component {
@@ -102,6 +135,14 @@ impl RecordType for Component {
 		self.id
 	}
 
+	fn get_key(&self) -> Cow<'_, str> {
+		if self.prefix.is_empty() {
+			Cow::Owned(self.id.to_string())
+		} else {
+			Cow::Borrowed(&self.prefix)
+		}
+	}
+
 	fn get_tbl_mut(app_ctx: &mut AppCtx) -> &mut Vec<Self> {
 		&mut app_ctx.components
 	}
@@ -115,9 +156,14 @@ impl RecordType for Component {
 	}
 
 	fn update_from_edit(&mut self, ef: Self::EditFile) {
+		if let (true, Some(prefix)) = (self.prefix.is_empty(), ef.prefix) {
+			self.prefix = prefix;
+			self.next_seq = 1;
+		}
 		self.name = ef.name;
 		self.description = ef.description;
 		self.author = ef.author;
+		self.tags = Tags::from(ef.tags);
 	}
 
 	fn set_accepted(&mut self) {
@@ -131,6 +177,12 @@ impl RecordType for Component {
 				err_loc!(),
 				self.name
 			))
+		} else if rhs.prefix.as_ref().is_some_and(|p| *p == self.prefix) {
+			Err(anyhow!(
+				"{} record with prefix: \"{}\"",
+				err_loc!(),
+				self.prefix
+			))
 		} else {
 			Ok(())
 		}
@@ -144,12 +196,23 @@ impl RecordType for Component {
 		self.status
 	}
 
-	fn fmt_as_edit(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
+	fn fmt_as_edit(
+		&self,
+		f: &mut impl std::fmt::Write,
+		_config: &ProjectConfig,
+	) -> std::fmt::Result {
 		writeln!(
 			f,
 			"# Do not include any tab characters in the document\n# Do not include any new-lines in the name field"
 		)?;
 		writeln!(f, "name = \"{}\"\n", self.name)?;
+		if self.prefix.is_empty() {
+			writeln!(
+				f,
+				"# This component has no requirement key prefix yet, it can only be set once\n# Use 1 to {MAX_KEY_PREFIX_LEN} upper case letters or digits"
+			)?;
+			writeln!(f, "prefix = \"\"\n")?;
+		}
 		writeln!(
 			f,
 			"# If writing on multiple lines use triple quotes (e.g. \"\"\"stuff\"\"\")"
@@ -166,17 +229,72 @@ impl RecordType for Component {
 		}
 		writeln!(f, "\"\n")?;
 		writeln!(f, "# Do not include any new-lines in the author field")?;
-		writeln!(f, "author = \"{}\"", self.author)
+		writeln!(f, "author = \"{}\"\n", self.author)?;
+		fmt_tags_field(f, &self.tags.0)
 	}
 }
 
 impl Component {
+	/// Moves the component to its new ID and points it at the new ID of its parent.
+	pub(crate) fn renumber(&mut self, component_ids: &IdMap) -> Result<()> {
+		if let Some(parent_id) = self.parent_id {
+			self.parent_id = Some(*component_ids.get(&parent_id).ok_or_else(|| {
+				anyhow!(format!(
+					"{} component: {} refers to missing parent ID: {parent_id}",
+					err_loc!(),
+					self.prefix
+				))
+			})?);
+		}
+		self.id = component_ids[&self.id];
+		Ok(())
+	}
+
 	pub fn name(&self) -> &str {
 		&self.name
 	}
 	pub fn status(&self) -> RecordStatus {
 		self.status
 	}
+	pub fn creation_date(&self) -> DateTime<Local> {
+		self.creation_date
+	}
+
+	pub fn author(&self) -> &str {
+		&self.author
+	}
+
+	pub fn prefix(&self) -> &str {
+		&self.prefix
+	}
+
+	pub fn parent_id(&self) -> Option<u64> {
+		self.parent_id
+	}
+	/// Names from the root component down to this one e.g. "Vehicle / Powertrain / Inverter".
+	pub fn path(&self, components: &[Component]) -> String {
+		component_path_by(self.id, |id| {
+			components
+				.iter()
+				.find(|c| c.id == id)
+				.or((id == self.id).then_some(self))
+				.map(|c| (c.name.as_str(), c.parent_id))
+		})
+	}
+	/// Hands out the next requirement key for this component, keys are never reused.
+	pub fn allocate_requirement_key(&mut self) -> Result<String> {
+		if self.prefix.is_empty() {
+			return Err(anyhow!(format!(
+				"{} component \"{}\" at ID: {} has no key prefix",
+				err_loc!(),
+				self.name,
+				self.id
+			)));
+		}
+		let key = format_requirement_key(&self.prefix, self.next_seq);
+		self.next_seq += 1;
+		Ok(key)
+	}
 }
 
 impl Ord for Component {
@@ -194,6 +312,10 @@ impl PartialOrd for Component {
 impl Display for Component {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		writeln!(f, "id = {}", self.id)?;
+		writeln!(f, "prefix = \"{}\"", self.prefix)?;
+		if let Some(parent_id) = self.parent_id {
+			writeln!(f, "parent_id = {parent_id}")?;
+		}
 		writeln!(f, "name = \"{}\"", self.name)?;
 		write!(f, "desciption = \"")?;
 		if self.description.contains("\\n") {
@@ -208,7 +330,8 @@ impl Display for Component {
 		}
 		writeln!(f, "creation date = \"{}\"", self.creation_date)?;
 		writeln!(f, "status = \"{}\"", self.status)?;
-		writeln!(f, "author = \"{}\"", self.author)
+		writeln!(f, "author = \"{}\"", self.author)?;
+		writeln!(f, "tags = \"{}\"", self.tags)
 	}
 }
 
@@ -221,16 +344,36 @@ impl Component {
 #[derive(Serialize, Deserialize, Eq, PartialEq)]
 pub struct ComponentTomlDraft {
 	name: String,
+	prefix: String,
 	description: String,
 	author: String,
+	#[serde(default)]
+	tags: Vec<String>,
+}
+
+impl ComponentTomlDraft {
+	/// A draft given as a TOML table rather than a draft file, sanitized like one.
+	pub fn from_table(table: toml::Table) -> Result<Self> {
+		let draft: Self = toml::Value::Table(table)
+			.try_into()
+			.context(formatcp!("{} component draft format error", err_loc!()))?;
+		sanitize_component_draft(draft).context(formatcp!(
+			"{} component input contains illegal characters",
+			err_loc!()
+		))
+	}
 }
 
 #[derive(Debug, Copy, Clone, strum_macros::Display, EnumIter, EnumString)]
 pub enum ComponentMenu {
+	#[strum(serialize = "List")]
+	List,
 	#[strum(serialize = "Create New Draft")]
 	NewDraft,
 	#[strum(serialize = "Insert & Accept Draft")]
 	Insert,
+	#[strum(serialize = "Set Parent")]
+	SetParent,
 	#[strum(serialize = "Create an Edit File")]
 	Edit,
 	#[strum(serialize = "Update Using an Edit File")]
@@ -248,10 +391,12 @@ impl SelectMenu for ComponentMenu {
 
 	fn run(&mut self, ctx: &mut AppCtx) -> Result<()> {
 		match self {
+			ComponentMenu::List => list::list_components(ctx),
 			ComponentMenu::NewDraft => {
 				create_draft_file::<ComponentEdit>(ctx, &COMPONENT_DRAFT_PREFIX)
 			}
 			ComponentMenu::Insert => insert_component_draft(ctx),
+			ComponentMenu::SetParent => set_parent(ctx),
 			ComponentMenu::Delete => delete_record::<Component>(ctx),
 			ComponentMenu::Edit => create_edit_file::<Component>(ctx, &COMPONENT_EDIT_PREFIX),
 			ComponentMenu::Update => update_record::<Component>(ctx, &COMPONENT_EDIT_PREFIX),
@@ -261,8 +406,10 @@ impl SelectMenu for ComponentMenu {
 
 	fn purpose(&self) -> &'static str {
 		match self {
+			ComponentMenu::List => "list components",
 			ComponentMenu::NewDraft => "create draft",
 			ComponentMenu::Insert => "insert component",
+			ComponentMenu::SetParent => "set parent",
 			ComponentMenu::Edit => "edit component",
 			ComponentMenu::Update => "update component",
 			ComponentMenu::Delete => "delete component",
@@ -292,7 +439,16 @@ fn insert_component_draft(ctx: &mut AppCtx) -> Result<()> {
 
 	// insert into component table...
 	let draft = open_component_draft(&draft_file_entry)?;
+	insert_component(ctx, draft).map(|_id| ())
+}
 
+/// Inserts an accepted component and returns its ID. Names and prefixes must be unique.
+pub fn insert_component(ctx: &mut AppCtx, draft: ComponentTomlDraft) -> Result<u64> {
+	let id = next_id(
+		&ctx.project_root,
+		&ctx.config.tables.component,
+		ctx.components.iter().map(|c| c.id),
+	)?;
 	let components: &mut Vec<Component> = ctx.as_mut();
 	// check for name conflict
 	if let Some(e) = components.iter().find(|c| c.name == draft.name).map(|c| {
@@ -305,18 +461,81 @@ fn insert_component_draft(ctx: &mut AppCtx) -> Result<()> {
 	}) {
 		return Err(e);
 	}
+	// check for prefix conflict, deleted components keep their prefix so keys are never reused
+	if let Some(e) = components
+		.iter()
+		.find(|c| c.prefix == draft.prefix)
+		.map(|c| {
+			anyhow!(format!(
+				"{} component with prefix: \"{}\" already exists at ID: {}",
+				err_loc!(),
+				c.prefix,
+				c.id
+			))
+		}) {
+		return Err(e);
+	}
 
-	let id = components.iter().max().map(|c| c.id).unwrap_or(0);
 	let name = draft.name.replace('\n', "\\n");
-	println!("Inserted component: \"{name}\" at ID: {id}");
+	println!(
+		"Inserted component: \"{name}\" with prefix: \"{}\" at ID: {id}",
+		draft.prefix
+	);
 	components.push(Component {
 		id,
+		prefix: draft.prefix,
+		next_seq: 1,
+		parent_id: None,
 		name,
 		description: draft.description,
 		creation_date: Local::now(),
 		status: RecordStatus::Accepted,
 		author: draft.author,
+		tags: Tags::from(draft.tags),
 	});
+	ctx.write_components()?;
+	Ok(id)
+}
+
+fn set_parent(ctx: &mut AppCtx) -> Result<()> {
+	// pick component
+	let component_id = match prompt_for_record_id::<Component>(ctx)
+		.context(formatcp!("{} can't prompt for component ID", err_loc!()))?
+	{
+		Some(id) => id,
+		None => return Ok(()),
+	};
+
+	// pick parent, empty removes it
+	let ans = inquire::Text::new("What is the ID or prefix of the parent component?")
+		.with_help_message("Leave empty to make this a top level component")
+		.prompt_skippable()
+		.context(formatcp!("{} can't prompt for parent ID", err_loc!()))?;
+	let parent_id = match ans.as_deref().map(str::trim) {
+		None => return Ok(()),
+		Some("") => None,
+		Some(id_or_key) => Some(find_id_by_id_or_key::<Component>(ctx, id_or_key)?),
+	};
+
+	if let Some(parent_id) = parent_id {
+		let components = &ctx.components;
+		let parent_of = |id: u64| {
+			components
+				.iter()
+				.find(|c| c.id == id)
+				.and_then(|c| c.parent_id)
+		};
+		if creates_cycle(component_id, parent_id, parent_of) {
+			return Err(anyhow!(format!(
+				"{} component at ID: {parent_id} is already a sub-component of ID: {component_id}",
+				err_loc!()
+			)));
+		}
+	}
+
+	let component = mut_record_by_id(&mut ctx.components, component_id)
+		.context(formatcp!("{} can't find record", err_loc!()))?;
+	component.parent_id = parent_id;
 	ctx.write_components()
 }
 
@@ -366,7 +585,8 @@ fn open_component_draft(entry: &DirEntry) -> Result<ComponentTomlDraft> {
 	))
 }
 
-/// Escape NL or CRNL to "\n" in description, error on NL or CRLF in name, and error on tab character anywhere.
+/// Escape NL or CRNL to "\n" in description, error on NL or CRLF in name, error on a malformed prefix,
+/// and error on tab character anywhere.
 fn sanitize_component_draft(mut draft: ComponentTomlDraft) -> Result<ComponentTomlDraft> {
 	if contains_any(&['\n', '\r', '\t'], draft.name.as_str()) {
 		return Err(anyhow!(formatcp!(
@@ -374,6 +594,13 @@ fn sanitize_component_draft(mut draft: ComponentTomlDraft) -> Result<ComponentTo
 			err_loc!()
 		)));
 	}
+	if !is_valid_key_prefix(&draft.prefix) {
+		return Err(anyhow!(formatcp!(
+			"{} prefix must be 1 to {} upper case letters or digits starting with a letter",
+			err_loc!(),
+			MAX_KEY_PREFIX_LEN
+		)));
+	}
 	if draft.description.contains('\t') {
 		return Err(anyhow!(formatcp!(
 			"{} description contains one or more tab characters",
@@ -386,6 +613,7 @@ fn sanitize_component_draft(mut draft: ComponentTomlDraft) -> Result<ComponentTo
 			err_loc!()
 		)));
 	}
+	check_tags(&draft.tags)?;
 
 	if let Cow::Owned(o) = escape_normalize_nl(&draft.description) {
 		draft.description = o
@@ -401,6 +629,19 @@ fn sanitize_component_edit(mut draft: ComponentEdit) -> Result<ComponentEdit> {
 			err_loc!()
 		)));
 	}
+	// an empty prefix means "leave it unset"
+	draft.prefix = draft.prefix.filter(|p| !p.is_empty());
+	if draft
+		.prefix
+		.as_ref()
+		.is_some_and(|p| !is_valid_key_prefix(p))
+	{
+		return Err(anyhow!(formatcp!(
+			"{} prefix must be 1 to {} upper case letters or digits starting with a letter",
+			err_loc!(),
+			MAX_KEY_PREFIX_LEN
+		)));
+	}
 	if draft.description.contains('\t') {
 		return Err(anyhow!(formatcp!(
 			"{} description contains one or more tab characters",
@@ -413,8 +654,38 @@ fn sanitize_component_edit(mut draft: ComponentEdit) -> Result<ComponentEdit> {
 			err_loc!()
 		)));
 	}
+	check_tags(&draft.tags)?;
 	if let Cow::Owned(o) = escape_normalize_nl(&draft.description) {
 		draft.description = o
 	}
 	Ok(draft)
 }
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+
+	use super::*;
+
+	#[test]
+	fn test_renumber() {
+		let mut component = Component {
+			id: 7,
+			prefix: "NAV".into(),
+			next_seq: 1,
+			parent_id: Some(3),
+			name: "Navigation".into(),
+			description: String::new(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+			tags: Tags::default(),
+		};
+		let mut orphan = component.clone();
+		component.renumber(&IdMap::from([(3, 0), (7, 1)])).unwrap();
+		assert_eq!((1, Some(0)), (component.id, component.parent_id));
+
+		// a parent that isn't in the table
+		assert!(orphan.renumber(&IdMap::from([(7, 0)])).is_err());
+	}
+}
@@ -0,0 +1,550 @@
+//! Commits the component and requirement tables to git after each change, turned on with
+//! `git_auto_commit` in the project config.
+//!
+//! A commit is queued each time a table is written and made once [`crate::AppCtx::save`] swapped
+//! the tables in, so `HEAD` never holds a change the working tree doesn't. Git plumbing keeps
+//! whatever else is staged out of them.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fmt::Display,
+	path::Path,
+	process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result, anyhow};
+use const_format::formatcp;
+
+use crate::{RecordStatus, RecordType, component::Component, err_loc, requirement::Requirement};
+
+/// A record whose table is committed, see [`AutoCommit`].
+pub trait CommittedRecord: RecordType + Clone + PartialEq {
+	/// Named in the commit message e.g. "requirement".
+	const KIND: &'static str;
+	fn author(&self) -> &str;
+	/// `None` for records not in a component.
+	fn component_id(&self) -> Option<u64> {
+		None
+	}
+}
+
+impl CommittedRecord for Component {
+	const KIND: &'static str = "component";
+
+	fn author(&self) -> &str {
+		self.author()
+	}
+}
+
+impl CommittedRecord for Requirement {
+	const KIND: &'static str = "requirement";
+
+	fn author(&self) -> &str {
+		self.author()
+	}
+
+	fn component_id(&self) -> Option<u64> {
+		Some(self.component_id())
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Change {
+	Insert,
+	Update,
+	Delete,
+	/// Moved to another component.
+	NewComponent,
+	/// Gone from the table e.g. purged.
+	Remove,
+}
+
+impl Display for Change {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Change::Insert => write!(f, "insert"),
+			Change::Update => write!(f, "update"),
+			Change::Delete => write!(f, "delete"),
+			Change::NewComponent => write!(f, "change component"),
+			Change::Remove => write!(f, "remove"),
+		}
+	}
+}
+
+/// How each record differs between `before` and `after` as (change, ID, key, author).
+fn changes<R: CommittedRecord>(before: &[R], after: &[R]) -> Vec<(Change, u64, String, String)> {
+	let mut changes = Vec::new();
+	for record in after {
+		let change = match before.iter().find(|b| b.get_id() == record.get_id()) {
+			None => Change::Insert,
+			Some(b) if b == record => continue,
+			Some(b)
+				if b.get_status() != RecordStatus::Deleted
+					&& record.get_status() == RecordStatus::Deleted =>
+			{
+				Change::Delete
+			}
+			Some(b) if b.component_id() != record.component_id() => Change::NewComponent,
+			Some(_) => Change::Update,
+		};
+		changes.push((
+			change,
+			record.get_id(),
+			record.get_key().into_owned(),
+			record.author().to_string(),
+		));
+	}
+	for record in before
+		.iter()
+		.filter(|b| !after.iter().any(|a| a.get_id() == b.get_id()))
+	{
+		changes.push((
+			Change::Remove,
+			record.get_id(),
+			record.get_key().into_owned(),
+			record.author().to_string(),
+		));
+	}
+	changes.sort();
+	changes
+}
+
+/// e.g. "reqtsv requirement: update NAV-2, NAV-3; insert NAV-4" followed by a line for each record
+/// and "Reqtsv-Author:" trailers.
+fn commit_message(kind: &str, changes: &[(Change, u64, String, String)]) -> String {
+	// the subject lists keys for small changes, counts for big ones
+	const MAX_SUBJECT_KEYS: usize = 3;
+	let kinds: BTreeSet<Change> = changes.iter().map(|(change, ..)| *change).collect();
+	let subject: Vec<String> = kinds
+		.iter()
+		.map(|kind_of_change| {
+			let keys: Vec<&str> = changes
+				.iter()
+				.filter(|(change, ..)| change == kind_of_change)
+				.map(|(_, _, key, _)| key.as_str())
+				.collect();
+			if keys.len() > MAX_SUBJECT_KEYS {
+				format!("{kind_of_change} {} records", keys.len())
+			} else {
+				format!("{kind_of_change} {}", keys.join(", "))
+			}
+		})
+		.collect();
+	let mut message = format!("reqtsv {kind}: {}\n\n", subject.join("; "));
+	for (change, id, key, author) in changes {
+		message.push_str(&format!(
+			"{change} {kind} ID: {id} key: {key} by: {author}\n"
+		));
+	}
+	let authors: BTreeSet<&str> = changes
+		.iter()
+		.map(|(.., author)| author.as_str())
+		.filter(|author| !author.is_empty())
+		.collect();
+	if !authors.is_empty() {
+		message.push('\n');
+	}
+	for author in authors {
+		message.push_str(&format!("Reqtsv-Author: {author}\n"));
+	}
+	message
+}
+
+/// A commit waiting for the session to save, see [`AutoCommit`].
+#[derive(Debug)]
+struct PendingCommit {
+	message: String,
+	/// (path relative to the project root, content) of each file committed.
+	files: Vec<(String, String)>,
+}
+
+/// Commits each write of the component or requirement table. The commits wait until
+/// [`crate::AppCtx::save`] swapped the tables in, then are made in order.
+#[derive(Debug)]
+pub struct AutoCommit {
+	/// Project root relative to the top of the repo e.g. "docs/reqs/", empty at the top.
+	prefix: String,
+	/// The tables as of the last write, so the next commit can say what changed.
+	components: Vec<Component>,
+	requirements: Vec<Requirement>,
+	/// The ID map of a Re-Number All in progress, its writes get one message and the map.
+	renumbering: Option<(String, String)>,
+	pending: Vec<PendingCommit>,
+}
+
+impl AutoCommit {
+	/// Errors when `project_root` isn't in a git repo.
+	pub fn new(
+		project_root: &Path,
+		components: Vec<Component>,
+		requirements: Vec<Requirement>,
+	) -> Result<Self> {
+		let prefix =
+			git(project_root, &["rev-parse", "--show-prefix"], None).context(formatcp!(
+				"{} git_auto_commit is set but the project isn't in a git repo",
+				err_loc!()
+			))?;
+		Ok(Self {
+			prefix,
+			components,
+			requirements,
+			renumbering: None,
+			pending: Vec::new(),
+		})
+	}
+
+	/// Queues a commit of the component table as written, `table` is its content.
+	pub fn components_written(&mut self, table_name: &str, table: &[u8], components: &[Component]) {
+		let before = std::mem::replace(&mut self.components, components.to_vec());
+		self.queue(table_name, table, &before, components);
+	}
+
+	/// Queues a commit of the requirement table as written, `table` is its content.
+	pub fn requirements_written(
+		&mut self,
+		table_name: &str,
+		table: &[u8],
+		requirements: &[Requirement],
+	) {
+		let before = std::mem::replace(&mut self.requirements, requirements.to_vec());
+		self.queue(table_name, table, &before, requirements);
+	}
+
+	/// Writes until [`AutoCommit::end_renumbering`] re-number records, described by the ID map
+	/// at `id_map`. The map is committed with the first of them.
+	pub fn start_renumbering(&mut self, id_map: &Path) -> Result<()> {
+		let content = std::fs::read_to_string(id_map)
+			.with_context(|| format!("{} can't read ID map: {:?}", err_loc!(), id_map))?;
+		let name = id_map
+			.file_name()
+			.map(|name| name.to_string_lossy().into_owned())
+			.unwrap_or_default();
+		self.renumbering = Some((name, content));
+		Ok(())
+	}
+
+	pub fn end_renumbering(&mut self) {
+		self.renumbering = None;
+	}
+
+	fn queue<R: CommittedRecord>(
+		&mut self,
+		table_name: &str,
+		table: &[u8],
+		before: &[R],
+		after: &[R],
+	) {
+		let changes = changes(before, after);
+		if changes.is_empty() {
+			return;
+		}
+		let mut files = vec![(
+			table_name.to_string(),
+			String::from_utf8_lossy(table).into_owned(),
+		)];
+		let message = match &self.renumbering {
+			Some((id_map, content)) => {
+				// the ID map only needs committing once
+				if !self
+					.pending
+					.iter()
+					.any(|p| p.files.iter().any(|(name, _)| name == id_map))
+				{
+					files.push((id_map.clone(), content.clone()));
+				}
+				renumber_message(R::KIND, id_map)
+			}
+			None => commit_message(R::KIND, &changes),
+		};
+		self.pending.push(PendingCommit { message, files });
+	}
+
+	/// Makes the queued commits on top of `HEAD`, call once the tables they hold are saved.
+	pub fn commit(&mut self, project_root: &Path) -> Result<()> {
+		let pending = std::mem::take(&mut self.pending);
+		if pending.is_empty() {
+			return Ok(());
+		}
+		let git_dir = git(project_root, &["rev-parse", "--absolute-git-dir"], None)?;
+		let scratch_index = Path::new(&git_dir).join("reqtsv-index");
+		let old_head = git(project_root, &["rev-parse", "--verify", "-q", "HEAD"], None).ok();
+		let mut head = old_head.clone();
+		// the last blob of each file, for the index
+		let mut staged: BTreeMap<String, String> = BTreeMap::new();
+		for commit in pending {
+			for (name, content) in commit.files {
+				let blob = git(
+					project_root,
+					&["hash-object", "-w", "--stdin", "--path", &name],
+					Some(&content),
+				)?;
+				staged.insert(format!("{}{name}", self.prefix), blob);
+			}
+			let res = commit_tree(
+				project_root,
+				&scratch_index,
+				head.as_deref(),
+				&staged,
+				&commit.message,
+			);
+			let _ = std::fs::remove_file(&scratch_index);
+			head = Some(res?);
+		}
+		let new_head = head.expect("a commit was made");
+		let mut update_ref = vec!["update-ref", "-m", "reqtsv auto-commit", "HEAD", &new_head];
+		if let Some(old_head) = &old_head {
+			update_ref.push(old_head);
+		}
+		git(project_root, &update_ref, None)?;
+		// stage the tables too so the index agrees with HEAD
+		for (repo_path, blob) in staged {
+			let cache_info = format!("100644,{blob},{repo_path}");
+			git(
+				project_root,
+				&["update-index", "--add", "--cacheinfo", &cache_info],
+				None,
+			)?;
+		}
+		Ok(())
+	}
+}
+
+/// e.g. "reqtsv requirement: renumber IDs" and where the old to new IDs are.
+fn renumber_message(kind: &str, id_map: &str) -> String {
+	format!("reqtsv {kind}: renumber IDs\n\nThe old to new IDs are in: {id_map}\n")
+}
+
+/// Commits `files` (repo path to blob) on top of `head` through `scratch_index` so whatever else
+/// is staged isn't committed. Returns the commit, `HEAD` isn't moved.
+fn commit_tree(
+	project_root: &Path,
+	scratch_index: &Path,
+	head: Option<&str>,
+	files: &BTreeMap<String, String>,
+	message: &str,
+) -> Result<String> {
+	let scratch = [("GIT_INDEX_FILE", scratch_index.as_os_str())];
+	match head {
+		Some(head) => git_env(project_root, &["read-tree", head], &scratch, None)?,
+		None => git_env(project_root, &["read-tree", "--empty"], &scratch, None)?,
+	};
+	for (repo_path, blob) in files {
+		let cache_info = format!("100644,{blob},{repo_path}");
+		git_env(
+			project_root,
+			&["update-index", "--add", "--cacheinfo", &cache_info],
+			&scratch,
+			None,
+		)?;
+	}
+	let tree = git_env(project_root, &["write-tree"], &scratch, None)?;
+	let mut commit_tree = vec!["commit-tree", tree.as_str()];
+	if let Some(head) = head {
+		commit_tree.extend(["-p", head]);
+	}
+	git(project_root, &commit_tree, Some(message))
+}
+
+fn git(dir: &Path, args: &[&str], stdin: Option<&str>) -> Result<String> {
+	git_env(dir, args, &[], stdin)
+}
+
+/// Runs git in `dir` and returns what it printed, trimmed.
+fn git_env(
+	dir: &Path,
+	args: &[&str],
+	envs: &[(&str, &std::ffi::OsStr)],
+	stdin: Option<&str>,
+) -> Result<String> {
+	let mut child = Command::new("git")
+		.args(args)
+		.current_dir(dir)
+		.envs(envs.iter().copied())
+		.stdin(if stdin.is_some() {
+			Stdio::piped()
+		} else {
+			Stdio::null()
+		})
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.with_context(|| format!("{} can't run git {}", err_loc!(), args.join(" ")))?;
+	if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+		use std::io::Write;
+		pipe.write_all(input.as_bytes())
+			.with_context(|| format!("{} can't write to git {}", err_loc!(), args.join(" ")))?;
+	}
+	let output = child
+		.wait_with_output()
+		.with_context(|| format!("{} can't run git {}", err_loc!(), args.join(" ")))?;
+	if !output.status.success() {
+		return Err(anyhow!(format!(
+			"{} git {} failed: {}",
+			err_loc!(),
+			args.join(" "),
+			String::from_utf8_lossy(&output.stderr).trim()
+		)));
+	}
+	Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use reqtsv_lib::REQUIREMENT_HEADER;
+
+	use super::*;
+	use crate::parse_table;
+
+	/// (ID, component ID, status, author) rows of a requirement table.
+	fn table(rows: &[(u64, u64, &str, &str)]) -> String {
+		let mut table = REQUIREMENT_HEADER.to_string();
+		for (id, component_id, status, author) in rows {
+			table.push_str(&format!(
+				"{id}\tNAV-{id}\t{component_id}\t\tRequirement {id}\tFunctional\t2025-05-27T12:00:00-04:00\tThing shall do {id}\tDoes {id}\tTest\t0\t{author}\tMandated\t{status}\t\t\t\n"
+			));
+		}
+		table
+	}
+
+	fn requirements(rows: &[(u64, u64, &str, &str)]) -> Vec<Requirement> {
+		parse_table(&table(rows)).unwrap()
+	}
+
+	#[test]
+	fn test_changes() {
+		let before = requirements(&[
+			(1, 0, "Accepted", "A"),
+			(2, 0, "Accepted", "A"),
+			(3, 0, "Accepted", "A"),
+			(4, 0, "Accepted", "A"),
+		]);
+		let after = requirements(&[
+			(1, 0, "Accepted", "A"),
+			(2, 0, "Deleted", "A"),
+			(3, 1, "Accepted", "A"),
+			(5, 0, "Proposed", "B"),
+		]);
+		let kinds: Vec<(Change, u64)> = changes(&before, &after)
+			.into_iter()
+			.map(|(change, id, ..)| (change, id))
+			.collect();
+		assert_eq!(
+			vec![
+				(Change::Insert, 5),
+				(Change::Delete, 2),
+				(Change::NewComponent, 3),
+				(Change::Remove, 4),
+			],
+			kinds
+		);
+		assert!(changes(&before, &before).is_empty());
+	}
+
+	#[test]
+	fn test_commit_message() {
+		let change =
+			|change, id: u64, author: &str| (change, id, format!("NAV-{id}"), author.to_string());
+		let message = commit_message(
+			"requirement",
+			&[
+				change(Change::Insert, 4, "B"),
+				change(Change::Update, 2, "A"),
+				change(Change::Update, 3, ""),
+			],
+		);
+		assert_eq!(
+			"reqtsv requirement: insert NAV-4; update NAV-2, NAV-3\n\n\
+			insert requirement ID: 4 key: NAV-4 by: B\n\
+			update requirement ID: 2 key: NAV-2 by: A\n\
+			update requirement ID: 3 key: NAV-3 by: \n\n\
+			Reqtsv-Author: A\n\
+			Reqtsv-Author: B\n",
+			message
+		);
+
+		// too many keys for the subject
+		let updates: Vec<_> = (0..4).map(|id| change(Change::Update, id, "")).collect();
+		let message = commit_message("requirement", &updates);
+		assert!(message.starts_with("reqtsv requirement: update 4 records\n\n"));
+		assert!(!message.contains("Reqtsv-Author"));
+	}
+
+	#[test]
+	fn test_auto_commit() {
+		let root = std::env::temp_dir().join(format!("reqtsv-git-test-{}", std::process::id()));
+		if root.exists() {
+			std::fs::remove_dir_all(&root).unwrap();
+		}
+		let project_root = root.join("reqs");
+		std::fs::create_dir_all(&project_root).unwrap();
+		git(&root, &["init", "-q"], None).unwrap();
+		git(&root, &["config", "user.name", "Author A"], None).unwrap();
+		git(&root, &["config", "user.email", "a@example.com"], None).unwrap();
+		// staged but not part of the commits
+		std::fs::write(root.join("notes.txt"), "notes").unwrap();
+		git(&root, &["add", "notes.txt"], None).unwrap();
+
+		let table_name = "requirement.tsv";
+		let first = [(1, 0, "Accepted", "A")];
+		let second = [(1, 0, "Accepted", "A"), (2, 0, "Proposed", "B")];
+		let third = [(1, 0, "Deleted", "A"), (2, 0, "Proposed", "B")];
+		let mut auto_commit =
+			AutoCommit::new(&project_root, Vec::new(), requirements(&first)).unwrap();
+		assert_eq!("reqs/", auto_commit.prefix);
+		// a write that changed nothing isn't committed
+		auto_commit.requirements_written(
+			table_name,
+			table(&first).as_bytes(),
+			&requirements(&first),
+		);
+		for rows in [&second[..], &third[..]] {
+			auto_commit.requirements_written(
+				table_name,
+				table(rows).as_bytes(),
+				&requirements(rows),
+			);
+		}
+		// nothing is committed until the tables are saved
+		assert!(git(&root, &["rev-parse", "--verify", "-q", "HEAD"], None).is_err());
+
+		std::fs::write(project_root.join(table_name), table(&third)).unwrap();
+		auto_commit.commit(&project_root).unwrap();
+		let subjects = git(&root, &["log", "--format=%s"], None).unwrap();
+		assert_eq!(
+			"reqtsv requirement: delete NAV-1\nreqtsv requirement: insert NAV-2",
+			subjects
+		);
+		let files = git(&root, &["show", "--name-only", "--format=", "HEAD"], None).unwrap();
+		assert_eq!("reqs/requirement.tsv", files);
+		// the working tree and index agree with HEAD, the other staged file is still staged
+		let status = git(&root, &["status", "--porcelain"], None).unwrap();
+		assert_eq!("A  notes.txt", status);
+
+		// re-numbering is one commit with the ID map
+		let renumbered = [(0, 0, "Deleted", "A"), (1, 0, "Proposed", "B")];
+		let id_map = project_root.join("id-map-20250527T120000.tsv");
+		std::fs::write(&id_map, "table\told_id\tnew_id\tkey\n").unwrap();
+		auto_commit.start_renumbering(&id_map).unwrap();
+		auto_commit.requirements_written(
+			table_name,
+			table(&renumbered).as_bytes(),
+			&requirements(&renumbered),
+		);
+		auto_commit.end_renumbering();
+		std::fs::write(project_root.join(table_name), table(&renumbered)).unwrap();
+		auto_commit.commit(&project_root).unwrap();
+		let message = git(&root, &["log", "-1", "--format=%B"], None).unwrap();
+		assert_eq!(
+			"reqtsv requirement: renumber IDs\n\nThe old to new IDs are in: id-map-20250527T120000.tsv",
+			message
+		);
+		let files = git(&root, &["show", "--name-only", "--format=", "HEAD"], None).unwrap();
+		assert_eq!(
+			"reqs/id-map-20250527T120000.tsv\nreqs/requirement.tsv",
+			files
+		);
+		let status = git(&root, &["status", "--porcelain"], None).unwrap();
+		assert_eq!("A  notes.txt", status);
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+}
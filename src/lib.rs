@@ -2,39 +2,87 @@ use std::{
 	borrow::Cow,
 	fmt::Display,
 	fs::{File, OpenOptions},
-	io::{Read, Write},
+	io::Write,
 	path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Result, anyhow};
 use component::{Component, ComponentMenuCtx};
 use const_format::formatcp;
-use requirement::Requirement;
-use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use git::AutoCommit;
+use inline_colorization::*;
+use reqtsv_lib::{
+	HISTORY_HEADER, LINK_HEADER, Link, TEST_CASE_HEADER, TEST_RESULT_HEADER, TestResult,
+	config::{
+		DEFAULT_CSS, ProjectConfig, TableNames, archive_table_name, init_project_config,
+		load_project_config, new_table_name, old_table_name,
+	},
+	custom_field::{CustomFields, REQUIREMENT_COLUMNS, append_custom_columns, read_custom_columns},
+	recovery::{
+		JournalRecovery, Recovery, commit_tables, recover_journal, recover_table, table_is_complete,
+	},
+	schema::{Table, check_header, check_schema_version},
+};
+use requirement::{Requirement, RequirementRevision};
+use serde::{Serialize, de::DeserializeOwned};
+use test_case::TestCase;
+use undo::OperationLog;
 use walkdir::{DirEntry, WalkDir};
 
+pub mod cli;
 pub mod component;
+pub mod git;
 // pub mod error;
+pub mod link;
+pub mod list;
 pub mod project;
+pub mod purge;
 pub mod requirement;
+pub mod search;
 pub mod select_menu;
+pub mod test_case;
+pub mod undo;
 
 pub const COLUMN_DELIMITER: u8 = b'\t';
 pub const RECORD_DELIMITER: u8 = b'\n';
-pub const COMPONENT_HEADER: &'static str = "id\tname\tdescription\tcreation_date\tstatus\tauthor\n";
-pub const REQUIREMENT_HEADER: &'static str = "id\tcomponent_id\tfunctional\tcreation_date\trequirement\tversion\tauthor\tpriority\tstatus\tstatus_justification\trisks\n";
+pub use reqtsv_lib::{COMPONENT_HEADER, REQUIREMENT_HEADER};
 
 #[derive(Debug)]
 pub struct AppCtx {
 	pub components: Vec<Component>,
 	pub requirements: Vec<Requirement>,
+	pub links: Vec<Link>,
+	pub history: Vec<RequirementRevision>,
+	pub test_cases: Vec<TestCase>,
+	pub test_results: Vec<TestResult>,
 	pub project_root: Box<Path>, // TODO: delete? field on in memory record
 	pub component_file: File,
 	pub requirement_file: File,
+	// `None` when a read-only session found no table, see `load_added_table`
+	pub link_file: Option<File>,
+	pub history_file: Option<File>,
+	pub test_case_file: Option<File>,
+	pub test_result_file: Option<File>,
 	pub component_new_path: Box<Path>,
 	pub requirement_new_path: Box<Path>,
+	pub link_new_path: Box<Path>,
+	pub history_new_path: Box<Path>,
+	pub test_case_new_path: Box<Path>,
+	pub test_result_new_path: Box<Path>,
 	pub updated_component: bool,
 	pub updated_requirement: bool,
+	pub updated_link: bool,
+	pub updated_history: bool,
+	pub updated_test_case: bool,
+	pub updated_test_result: bool,
+	/// Archive tables written by a purge, committed with the tables at exit.
+	pub updated_archives: Vec<String>,
+	pub config: ProjectConfig,
+	/// Set when another session holds the project lock, tables are never written.
+	pub read_only: bool,
+	pub operations: OperationLog,
+	/// `Some` when `git_auto_commit` is set in the project config.
+	pub git: Option<AutoCommit>,
 }
 
 /// Puts "file!():line!():" e.g. "src/lib.rs:41:"
@@ -46,7 +94,11 @@ macro_rules! err_loc {
 }
 
 pub fn mut_record_by_id<R: RecordType>(records: &mut Vec<R>, id: u64) -> Result<&mut R> {
-	if records.get(id as usize).is_some() {
+	// IDs stop matching positions once records are purged
+	if records
+		.get(id as usize)
+		.is_some_and(|rec| rec.get_id() == id)
+	{
 		Ok(&mut records[id as usize])
 	} else {
 		match records.iter_mut().find(|rec| rec.get_id() == id) {
@@ -57,8 +109,162 @@ pub fn mut_record_by_id<R: RecordType>(records: &mut Vec<R>, id: u64) -> Result<
 }
 
 impl AppCtx {
+	/// Loads every table of the project at `project_root`, creating the tables older projects
+	/// don't have. Progress goes to stderr so commands can print results on stdout.
+	pub fn load(project_root: Box<Path>, config: ProjectConfig, read_only: bool) -> Result<Self> {
+		let tables = config.tables.clone();
+		check_schema_version(&config).context(formatcp!("{} can't open project", err_loc!()))?;
+
+		//serialize and verify all tables
+		eprintln!("Loading component table...");
+		let component_tbl_path = project_root.join(&tables.component).into_boxed_path();
+
+		let (component_file, raw_component_tbl) = load_table(component_tbl_path, true)?;
+		check_header(Table::Component, &tables.component, &raw_component_tbl)?;
+		let components: Vec<Component> = parse_table(&raw_component_tbl)?;
+
+		eprintln!("Loading requirement table...");
+		let requirement_tbl_path = project_root.join(&tables.requirement).into_boxed_path();
+
+		let (requirement_file, raw_requirement_tbl) = load_table(requirement_tbl_path, true)?;
+		check_header(
+			Table::Requirement,
+			&tables.requirement,
+			&raw_requirement_tbl,
+		)?;
+		let mut requirements: Vec<Requirement> = parse_table(&raw_requirement_tbl)?;
+		let custom = read_custom_columns(&raw_requirement_tbl, REQUIREMENT_COLUMNS)
+			.context(formatcp!("{} corrupt custom field", err_loc!()))?;
+		for (requirement, custom) in requirements.iter_mut().zip(custom) {
+			requirement.set_custom(custom);
+		}
+
+		eprintln!("Loading link table...");
+		let link_tbl_path = project_root.join(&tables.link).into_boxed_path();
+		let (link_file, raw_link_tbl) =
+			load_added_table(&link_tbl_path, LINK_HEADER, "link", read_only)?;
+		check_header(Table::Link, &tables.link, &raw_link_tbl)?;
+		let links: Vec<Link> = parse_table(&raw_link_tbl)?;
+
+		eprintln!("Loading history table...");
+		let history_tbl_path = project_root.join(&tables.history).into_boxed_path();
+		let (history_file, raw_history_tbl) =
+			load_added_table(&history_tbl_path, HISTORY_HEADER, "history", read_only)?;
+		check_header(Table::History, &tables.history, &raw_history_tbl)?;
+		let history: Vec<RequirementRevision> = parse_table(&raw_history_tbl)?;
+
+		eprintln!("Loading test case table...");
+		let test_case_tbl_path = project_root.join(&tables.test_case).into_boxed_path();
+		let (test_case_file, raw_test_case_tbl) = load_added_table(
+			&test_case_tbl_path,
+			TEST_CASE_HEADER,
+			"test case",
+			read_only,
+		)?;
+		check_header(Table::TestCase, &tables.test_case, &raw_test_case_tbl)?;
+		let test_cases: Vec<TestCase> = parse_table(&raw_test_case_tbl)?;
+
+		eprintln!("Loading test result table...");
+		let test_result_tbl_path = project_root.join(&tables.test_result).into_boxed_path();
+		let (test_result_file, raw_test_result_tbl) = load_added_table(
+			&test_result_tbl_path,
+			TEST_RESULT_HEADER,
+			"test result",
+			read_only,
+		)?;
+		check_header(Table::TestResult, &tables.test_result, &raw_test_result_tbl)?;
+		let test_results: Vec<TestResult> = parse_table(&raw_test_result_tbl)?;
+
+		eprintln!("Project Root: {:?}", &project_root);
+		let new_path = |table_name: &str| -> Box<Path> {
+			project_root
+				.join(new_table_name(table_name))
+				.into_boxed_path()
+		};
+		let component_new_path = new_path(&tables.component);
+		let requirement_new_path = new_path(&tables.requirement);
+		let link_new_path = new_path(&tables.link);
+		let history_new_path = new_path(&tables.history);
+		let test_case_new_path = new_path(&tables.test_case);
+		let test_result_new_path = new_path(&tables.test_result);
+		let git = match config.git_auto_commit && !read_only {
+			true => Some(AutoCommit::new(
+				&project_root,
+				components.clone(),
+				requirements.clone(),
+			)?),
+			false => None,
+		};
+		Ok(AppCtx {
+			components,
+			requirements,
+			links,
+			history,
+			test_cases,
+			test_results,
+			project_root,
+			component_file,
+			requirement_file,
+			link_file,
+			history_file,
+			test_case_file,
+			test_result_file,
+			component_new_path,
+			requirement_new_path,
+			link_new_path,
+			history_new_path,
+			test_case_new_path,
+			test_result_new_path,
+			updated_component: false,
+			updated_requirement: false,
+			updated_link: false,
+			updated_history: false,
+			updated_test_case: false,
+			updated_test_result: false,
+			updated_archives: Vec::new(),
+			config,
+			read_only,
+			operations: OperationLog::default(),
+			git,
+		})
+	}
+
+	/// Swaps in every table changed this session, all or nothing.
+	pub fn save(mut self) -> Result<()> {
+		let tables = self.config.tables.clone();
+		drop(self.component_file);
+		drop(self.requirement_file);
+		drop(self.link_file);
+		drop(self.history_file);
+		drop(self.test_case_file);
+		drop(self.test_result_file);
+		let mut updated: Vec<&str> = [
+			(self.updated_requirement, tables.requirement.as_str()),
+			(self.updated_component, tables.component.as_str()),
+			(self.updated_link, tables.link.as_str()),
+			(self.updated_history, tables.history.as_str()),
+			(self.updated_test_case, tables.test_case.as_str()),
+			(self.updated_test_result, tables.test_result.as_str()),
+		]
+		.into_iter()
+		.filter_map(|(updated, table_name)| updated.then_some(table_name))
+		.collect();
+		updated.extend(self.updated_archives.iter().map(String::as_str));
+		// all or nothing, tables refer to each other by ID
+		let committed = commit_tables(&self.project_root, &updated);
+		committed.context(formatcp!("{} can't save changed tables", err_loc!()))?;
+		if let Some(git) = self.git.as_mut() {
+			warn_not_committed(git.commit(&self.project_root));
+		}
+		Ok(())
+	}
+
 	pub fn get_component_by_id(&mut self, id: u64) -> Result<&mut Component> {
-		if self.components.get(id as usize).is_some() {
+		if self
+			.components
+			.get(id as usize)
+			.is_some_and(|comp| comp.get_id() == id)
+		{
 			Ok(&mut self.components[id as usize])
 		} else {
 			match self.components.iter_mut().find(|comp| comp.get_id() == id) {
@@ -72,6 +278,18 @@ impl AppCtx {
 	where
 		S: Serialize,
 	{
+		let table = serialize_table(records)
+			.with_context(|| format!("{} can't serialize table: {:?}", err_loc!(), tbl_new_path))?;
+		self.wrtie_table_bytes(tbl_new_path, &table)
+	}
+
+	fn wrtie_table_bytes(&self, tbl_new_path: &Path, table: &[u8]) -> Result<()> {
+		if self.read_only {
+			return Err(anyhow!(formatcp!(
+				"{} project is open read-only, changes can't be saved",
+				err_loc!()
+			)));
+		}
 		let mut f_new = OpenOptions::new()
 			.write(true)
 			.create(true)
@@ -81,23 +299,13 @@ impl AppCtx {
 				|| format!("{} can't create new file: {:?}", err_loc!(), tbl_new_path,),
 			)?;
 
-		let mut wtr = csv::WriterBuilder::new()
-			.delimiter(COLUMN_DELIMITER)
-			.has_headers(true)
-			.terminator(csv::Terminator::Any(RECORD_DELIMITER))
-			.from_writer(&mut f_new);
-
-		for record in records {
-			wtr.serialize(record).with_context(|| {
-				format!(
-					"{} can't write record to file: {:?}",
-					err_loc!(),
-					tbl_new_path
-				)
-			})?
-		}
-
-		drop(wtr);
+		f_new.write_all(table).with_context(|| {
+			format!(
+				"{} can't write records to file: {:?}",
+				err_loc!(),
+				tbl_new_path
+			)
+		})?;
 
 		f_new
 			.flush()
@@ -113,59 +321,158 @@ impl AppCtx {
 	}
 
 	pub fn write_components(&mut self) -> Result<()> {
-		self.wrtie_table(&self.component_new_path, self.components.iter())
+		let table = serialize_table(self.components.iter())
+			.context(formatcp!("{} can't serialize component table", err_loc!()))?;
+		self.wrtie_table_bytes(&self.component_new_path, &table)
 			.context(formatcp!("{} can't write component table", err_loc!()))?;
 		self.updated_component = true;
+		if let Some(git) = self.git.as_mut() {
+			git.components_written(&self.config.tables.component, &table, &self.components);
+		}
 		Ok(())
 	}
 
 	fn write_requirements(&mut self) -> Result<()> {
-		self.wrtie_table(&self.requirement_new_path, self.requirements.iter())
+		let table = serialize_table(self.requirements.iter()).context(formatcp!(
+			"{} can't serialize requirement table",
+			err_loc!()
+		))?;
+		let custom: Vec<&CustomFields> = self.requirements.iter().map(|r| r.custom()).collect();
+		let table = append_custom_columns(&table, &self.config.fields, &custom)
+			.context(formatcp!("{} can't add custom fields to table", err_loc!()))?;
+		self.wrtie_table_bytes(&self.requirement_new_path, &table)
 			.context(formatcp!("{} can't write requirement table", err_loc!()))?;
 		self.updated_requirement = true;
+		if let Some(git) = self.git.as_mut() {
+			git.requirements_written(&self.config.tables.requirement, &table, &self.requirements);
+		}
+		Ok(())
+	}
+
+	pub fn write_links(&mut self) -> Result<()> {
+		self.wrtie_table(&self.link_new_path, self.links.iter())
+			.context(formatcp!("{} can't write link table", err_loc!()))?;
+		self.updated_link = true;
+		Ok(())
+	}
+
+	pub fn write_history(&mut self) -> Result<()> {
+		self.wrtie_table(&self.history_new_path, self.history.iter())
+			.context(formatcp!("{} can't write history table", err_loc!()))?;
+		self.updated_history = true;
+		Ok(())
+	}
+
+	pub fn write_test_cases(&mut self) -> Result<()> {
+		self.wrtie_table(&self.test_case_new_path, self.test_cases.iter())
+			.context(formatcp!("{} can't write test case table", err_loc!()))?;
+		self.updated_test_case = true;
+		Ok(())
+	}
+
+	pub fn write_test_results(&mut self) -> Result<()> {
+		self.wrtie_table(&self.test_result_new_path, self.test_results.iter())
+			.context(formatcp!("{} can't write test result table", err_loc!()))?;
+		self.updated_test_result = true;
+		Ok(())
+	}
+
+	/// The components and requirements of the session as [`reqtsv_lib`] reads them, for the
+	/// views shared with the other tools e.g. [`reqtsv_lib::filter`].
+	pub fn lib_records(
+		&self,
+	) -> Result<(Vec<reqtsv_lib::Component>, Vec<reqtsv_lib::Requirement>)> {
+		let table = serialize_table(self.components.iter())
+			.context(formatcp!("{} can't serialize component table", err_loc!()))?;
+		let components = parse_table(&String::from_utf8_lossy(&table))?;
+		let table = serialize_table(self.requirements.iter()).context(formatcp!(
+			"{} can't serialize requirement table",
+			err_loc!()
+		))?;
+		let mut requirements: Vec<reqtsv_lib::Requirement> =
+			parse_table(&String::from_utf8_lossy(&table))?;
+		for (requirement, session) in requirements.iter_mut().zip(&self.requirements) {
+			requirement.custom = session.custom().clone();
+		}
+		Ok((components, requirements))
+	}
+
+	/// Adds the records in `table`, a serialized table with a header, to the end of the archive
+	/// of `table_name`. The archive is created if there isn't one.
+	pub fn append_archive(&mut self, table_name: &str, table: &[u8]) -> Result<()> {
+		let archive_name = archive_table_name(table_name);
+		let new_path = self.project_root.join(new_table_name(&archive_name));
+		let current_path = self.project_root.join(&archive_name);
+		// the new archive has what this session already purged
+		let existing_path = [&new_path, &current_path]
+			.into_iter()
+			.find(|path| path.exists());
+		let table = match existing_path {
+			None => table.to_vec(),
+			Some(path) => {
+				let mut archive = std::fs::read(path)
+					.with_context(|| format!("{} can't read archive: {:?}", err_loc!(), path))?;
+				let split_header = |t: &[u8]| -> usize {
+					t.iter()
+						.position(|b| *b == RECORD_DELIMITER)
+						.map(|i| i + 1)
+						.unwrap_or(t.len())
+				};
+				let header_len = split_header(table);
+				if archive[..split_header(&archive)] != table[..header_len] {
+					return Err(anyhow!(format!(
+						"{} the columns of archive: {:?} don't match the table",
+						err_loc!(),
+						path
+					)));
+				}
+				archive.extend_from_slice(&table[header_len..]);
+				archive
+			}
+		};
+		self.wrtie_table_bytes(&new_path, &table)
+			.with_context(|| format!("{} can't write archive: {archive_name}", err_loc!()))?;
+		if !self.updated_archives.contains(&archive_name) {
+			self.updated_archives.push(archive_name);
+		}
 		Ok(())
 	}
 }
 
+/// The table is saved either way so a failed commit doesn't fail the operation.
+fn warn_not_committed(committed: Result<()>) {
+	if let Err(e) = committed {
+		eprintln!("{color_yellow}Saved but can't commit to git due to:{color_reset}");
+		for e in e.chain() {
+			eprintln!("{color_yellow}{e}{color_reset}");
+		}
+	}
+}
+
 impl AsRef<Path> for AppCtx {
 	fn as_ref(&self) -> &Path {
 		&self.project_root
 	}
 }
 
-pub fn open_edit_file<EF>(entry: &DirEntry) -> Result<EF>
+pub fn open_edit_file<EF>(entry: &DirEntry, config: &ProjectConfig) -> Result<EF>
 where
 	EF: EditFile,
 {
-	// open file
-	let mut file = std::fs::OpenOptions::new()
-		.read(true)
-		.write(false)
-		.truncate(false)
-		.create(false)
-		.open(&entry.path())
-		.with_context(|| format!("{} can't open edit file: {:?}", err_loc!(), &entry.path()))?;
+	read_edit_file(entry.path(), config)
+}
 
-	// read
-	let mut buf = String::with_capacity(
-		entry
-			.metadata()
-			.ok()
-			.map(|m| m.len() as usize)
-			.unwrap_or(4096),
-	);
-	file.read_to_string(&mut buf)
-		.with_context(|| format!("{} can't read edit file: {:?}", err_loc!(), &entry.path()))?;
-	// deserialize
-	let edit = toml::from_str::<EF>(buf.as_str()).with_context(|| {
-		format!(
-			"{} bad file content format in: {:?}",
-			err_loc!(),
-			&entry.file_name()
-		)
-	})?;
+/// Reads, deserializes, and sanitizes a draft or edit file.
+pub fn read_edit_file<EF>(path: &Path, config: &ProjectConfig) -> Result<EF>
+where
+	EF: EditFile,
+{
+	let buf = std::fs::read_to_string(path)
+		.with_context(|| format!("{} can't read edit file: {:?}", err_loc!(), path))?;
+	let edit = toml::from_str::<EF>(buf.as_str())
+		.with_context(|| format!("{} bad file content format in: {:?}", err_loc!(), path))?;
 
-	edit.sanitize()
+	edit.sanitize(config)
 }
 
 impl AsMut<Vec<Component>> for AppCtx {
@@ -182,65 +489,158 @@ impl AsMut<Vec<Requirement>> for AppCtx {
 
 impl ComponentMenuCtx for AppCtx {}
 
+/// Creates the tables named in the project config, and the config itself if there isn't one yet.
 pub fn init_project(project_root: impl AsRef<Path>) -> Result<()> {
-	let component_path = project_root.as_ref().join(component::COMPONENT_TABLE_NAME);
-	if component_path.exists() {
+	let config = load_project_config(project_root.as_ref())
+		.context(formatcp!("{} can't load project config", err_loc!()))?;
+	let tables = [
+		(&config.tables.component, COMPONENT_HEADER),
+		(&config.tables.requirement, REQUIREMENT_HEADER),
+		(&config.tables.link, LINK_HEADER),
+		(&config.tables.history, HISTORY_HEADER),
+		(&config.tables.test_case, TEST_CASE_HEADER),
+		(&config.tables.test_result, TEST_RESULT_HEADER),
+	];
+
+	if let Some(table_path) = tables
+		.iter()
+		.map(|(table_name, _)| project_root.as_ref().join(table_name))
+		.find(|table_path| table_path.exists())
+	{
 		return Err(anyhow!(format!(
-			"{} component table: {:?} exists",
+			"{} table: {:?} exists",
 			err_loc!(),
-			&component_path
+			&table_path
 		)));
 	}
-	let requirement_path = project_root
-		.as_ref()
-		.join(requirement::REQUIREMENT_TABLE_NAME);
-	if requirement_path.exists() {
-		return Err(anyhow!(format!(
-			"{} requirement table: {:?} exists",
-			err_loc!(),
-			&requirement_path
-		)));
+
+	for (table_name, header) in tables {
+		create_table(project_root.as_ref().join(table_name), header)?;
 	}
 
-	let component_file = std::fs::OpenOptions::new()
-		.read(true)
-		.write(true)
-		.truncate(true)
-		.create(true)
-		.open(&component_path)
-		.with_context(|| {
-			format!(
-				"{} can't create component table at: {:?}",
-				err_loc!(),
-				&component_path
-			)
-		})?;
+	let config = init_project_config(project_root.as_ref())
+		.context(formatcp!("{} can't create project config", err_loc!()))?;
+	let css_path = config.css_path(project_root.as_ref());
+	if !css_path.exists() {
+		std::fs::write(&css_path, DEFAULT_CSS)
+			.with_context(|| format!("{} can't create style sheet: {:?}", err_loc!(), &css_path))?;
+	}
 
-	write_flush_sync(
-		WriteFlushSync::Done(component_file),
-		COMPONENT_HEADER.as_bytes(),
-	)?;
+	Ok(())
+}
 
-	let requirement_file = std::fs::OpenOptions::new()
+/// Creates a table file holding only the header row.
+pub fn create_table(table_path: impl AsRef<Path>, header: &str) -> Result<()> {
+	let table_file = std::fs::OpenOptions::new()
 		.read(true)
 		.write(true)
 		.truncate(true)
 		.create(true)
-		.open(&requirement_path)
+		.open(&table_path)
 		.with_context(|| {
 			format!(
-				"{} can't create requirement table at: {:?}",
+				"{} can't create table at: {:?}",
 				err_loc!(),
-				&component_path
+				table_path.as_ref()
 			)
 		})?;
 
-	write_flush_sync(
-		WriteFlushSync::Done(requirement_file),
-		REQUIREMENT_HEADER.as_bytes(),
-	)?;
+	write_flush_sync(WriteFlushSync::Done(table_file), header.as_bytes())
+}
 
-	Ok(())
+/// Serializes `records` as a TSV table with headers.
+pub fn serialize_table<S: Serialize>(records: impl Iterator<Item = S>) -> Result<Vec<u8>> {
+	let mut wtr = csv::WriterBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.has_headers(true)
+		.terminator(csv::Terminator::Any(RECORD_DELIMITER))
+		.from_writer(Vec::with_capacity(4096));
+
+	for record in records {
+		wtr.serialize(record)
+			.context(formatcp!("{} can't serialize record", err_loc!()))?
+	}
+
+	wtr.into_inner()
+		.context(formatcp!("{} can't flush serialized table", err_loc!()))
+}
+
+/// Deserializes every record of a raw table, the first bad record is an error.
+pub fn parse_table<R: DeserializeOwned>(raw_table: &str) -> Result<Vec<R>> {
+	let mut tsv_reader = csv::ReaderBuilder::new()
+		.delimiter(COLUMN_DELIMITER)
+		.terminator(csv::Terminator::Any(RECORD_DELIMITER))
+		.from_reader(raw_table.as_bytes());
+
+	let max_records = raw_table.chars().filter(|ch| *ch == '\n').count();
+	let mut records: Vec<R> = Vec::with_capacity(max_records);
+	for res in tsv_reader
+		.deserialize::<R>()
+		.map(|res| res.context(formatcp!("{} corrupt table entry", err_loc!())))
+	{
+		records.push(res?);
+	}
+	Ok(records)
+}
+
+/// Finishes or rolls back a commit or table swap interrupted by a crash, see
+/// [`reqtsv_lib::recovery`]. Only call while holding the project lock, another session's
+/// "x.new.tsv" isn't stray.
+pub fn recover_tables(
+	project_root: &Path,
+	tables: &TableNames,
+) -> Result<(Option<JournalRecovery>, Vec<Recovery>)> {
+	fn is_valid<R: DeserializeOwned>(raw: &str, header: &str) -> bool {
+		table_is_complete(raw, header) && parse_table::<R>(raw).is_ok()
+	}
+	type Validator<'a> = (&'a str, fn(&str) -> bool);
+	let mut validators: Vec<Validator> = vec![
+		(&tables.component, |raw| {
+			is_valid::<Component>(raw, COMPONENT_HEADER)
+		}),
+		(&tables.requirement, |raw| {
+			is_valid::<Requirement>(raw, REQUIREMENT_HEADER)
+		}),
+		(&tables.link, |raw| is_valid::<Link>(raw, LINK_HEADER)),
+		(&tables.history, |raw| {
+			is_valid::<RequirementRevision>(raw, HISTORY_HEADER)
+		}),
+		(&tables.test_case, |raw| {
+			is_valid::<TestCase>(raw, TEST_CASE_HEADER)
+		}),
+		(&tables.test_result, |raw| {
+			is_valid::<TestResult>(raw, TEST_RESULT_HEADER)
+		}),
+	];
+	// archives are saved like the tables they come from
+	let archive_names: Vec<String> = validators
+		.iter()
+		.map(|(table_name, _)| archive_table_name(table_name))
+		.collect();
+	let archive_validators: Vec<Validator> = archive_names
+		.iter()
+		.map(String::as_str)
+		.zip(validators.iter().map(|(_, is_valid)| *is_valid))
+		.collect();
+	validators.extend(archive_validators);
+
+	let journal = recover_journal(project_root, |table_name, raw| {
+		validators
+			.iter()
+			.find(|(name, _)| *name == table_name)
+			.is_some_and(|(_, is_valid)| is_valid(raw))
+	})
+	.context(formatcp!("{} can't recover interrupted commit", err_loc!()))?;
+
+	let mut recoveries = Vec::new();
+	for (table_name, is_valid) in validators {
+		if let Some(recovery) = recover_table(project_root, table_name, is_valid)
+			.context(formatcp!("{} can't recover interrupted save", err_loc!()))?
+		{
+			recoveries.push(recovery);
+		}
+	}
+	Ok((journal, recoveries))
 }
 
 #[derive(Debug, Clone)]
@@ -300,36 +700,68 @@ fn file_list_chose(
 	}
 }
 
-pub fn prompt_for_record_id() -> Result<Option<u64>> {
-	inquire::CustomType::<u64>::new("What ID are you looking for?")
-		.with_formatter(&|i| format!("ID: {}", i))
-		.with_error_message("Please type a valid integer")
-		.with_help_message("Type the integer number ID of the table entry")
+/// Prompts for an integer ID or a key (e.g. "PWR-0012") and resolves it to the record's ID.
+pub fn prompt_for_record_id<R: RecordType>(app_ctx: &AppCtx) -> Result<Option<u64>> {
+	let ans = inquire::Text::new("What ID or key are you looking for?")
+		.with_help_message("Type the integer ID or the key (e.g. PWR-0012) of the table entry")
 		.prompt_skippable()
-		.context("Can't prompt for u64")
+		.context("Can't prompt for ID or key")?;
+	match ans {
+		Some(id_or_key) => find_id_by_id_or_key::<R>(app_ctx, id_or_key.trim()).map(Some),
+		None => Ok(None),
+	}
+}
+
+/// Keys are matched first (ignoring case), then integer IDs.
+pub fn find_id_by_id_or_key<R: RecordType>(app_ctx: &AppCtx, id_or_key: &str) -> Result<u64> {
+	let records = R::get_tbl(app_ctx);
+	if let Some(record) = records
+		.iter()
+		.find(|r| r.get_key().eq_ignore_ascii_case(id_or_key))
+	{
+		return Ok(record.get_id());
+	}
+	match id_or_key.parse::<u64>() {
+		Ok(id) if records.iter().any(|r| r.get_id() == id) => Ok(id),
+		_ => Err(anyhow!(format!(
+			"{} no record with ID or key: \"{id_or_key}\"",
+			err_loc!()
+		))),
+	}
 }
 
 pub trait EditFile: DeserializeOwned + Default + Serialize {
-	fn sanitize(self) -> Result<Self>;
-	fn fmt_as_draft(f: &mut impl std::fmt::Write) -> std::fmt::Result;
+	fn sanitize(self, config: &ProjectConfig) -> Result<Self>;
+	fn fmt_as_draft(f: &mut impl std::fmt::Write, config: &ProjectConfig) -> std::fmt::Result;
 }
 
 pub trait RecordType:
-	Sized + DeserializeOwned + Ord + std::fmt::Debug + Serialize + Display
+	Sized + Clone + DeserializeOwned + Ord + std::fmt::Debug + Serialize + Display
 where
 	Self::EditFile: EditFile,
 {
 	type EditFile;
 	fn get_id(&self) -> u64;
+	/// The durable human-readable key, or the ID as text for records without one.
+	fn get_key(&self) -> Cow<'_, str>;
 	fn get_tbl_mut(app_ctx: &mut AppCtx) -> &mut Vec<Self>;
 	fn get_tbl(app_ctx: &AppCtx) -> &Vec<Self>;
 	fn write_table(app_ctx: &mut AppCtx) -> Result<()>;
 	fn check_for_conflict(&self, rhs: &Self::EditFile) -> Result<()>;
-	fn fmt_as_edit(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result;
+	fn fmt_as_edit(&self, f: &mut impl std::fmt::Write, config: &ProjectConfig)
+	-> std::fmt::Result;
 	fn set_accepted(&mut self);
 	fn set_deleted(&mut self);
 	fn get_status(&self) -> RecordStatus;
 	fn update_from_edit(&mut self, ef: Self::EditFile);
+	/// Called after an edit replaced `previous` and the table was written, to keep what it was.
+	fn archive(_app_ctx: &mut AppCtx, _previous: Self) -> Result<()> {
+		Ok(())
+	}
+	/// Called after an edit file is read, to check or resolve what it refers to in other tables.
+	fn resolve_edit(_app_ctx: &AppCtx, _ef: &mut Self::EditFile) -> Result<()> {
+		Ok(())
+	}
 }
 
 pub fn ref_record_by_id<R: RecordType>(app_ctx: &AppCtx, id: u64) -> Option<&R> {
@@ -341,18 +773,17 @@ pub fn atomic_file_update(
 	file_content: &[u8],
 	old_file: Option<File>,
 ) -> Result<()> {
-	// create new
+	// create new, named like the tables so recover_tables finds them
 	let (new, old) = {
-		let mut current_buf_a = current.as_ref().to_path_buf();
-		let mut current_buf_b = current_buf_a.clone();
-		let mut current_name = current.as_ref().file_name().unwrap().to_os_string();
-		current_name.push(".new");
-		current_buf_a.set_file_name(&current_name);
-		current_name.clear();
-		current_name.push(current.as_ref().file_name().unwrap());
-		current_name.push(".old");
-		current_buf_b.set_file_name(current_name);
-		(current_buf_a, current_buf_b)
+		let current_name = current.as_ref().file_name().unwrap().to_string_lossy();
+		(
+			current
+				.as_ref()
+				.with_file_name(new_table_name(&current_name)),
+			current
+				.as_ref()
+				.with_file_name(old_table_name(&current_name)),
+		)
 	};
 
 	let f_new = OpenOptions::new()
@@ -391,7 +822,7 @@ pub fn atomic_file_update(
 }
 
 pub fn create_edit_file<R: RecordType>(ctx: &mut AppCtx, edit_prefix: &'static str) -> Result<()> {
-	let id = match prompt_for_record_id()
+	let id = match prompt_for_record_id::<R>(ctx)
 		.context(formatcp!("{} can't prompt for record ID", err_loc!()))?
 	{
 		Some(id) => id,
@@ -403,7 +834,7 @@ pub fn create_edit_file<R: RecordType>(ctx: &mut AppCtx, edit_prefix: &'static s
 		Some(record) => {
 			// create document
 			let mut path = std::path::PathBuf::from(ctx.as_ref());
-			path.push(format!("{edit_prefix}-{}.toml", record.get_id()));
+			path.push(format!("{edit_prefix}-{}.toml", record.get_key()));
 			let file = std::fs::OpenOptions::new()
 				.read(true)
 				.write(true)
@@ -416,7 +847,7 @@ pub fn create_edit_file<R: RecordType>(ctx: &mut AppCtx, edit_prefix: &'static s
 			let mut edit_file_string = String::with_capacity(4096);
 
 			record
-				.fmt_as_edit(&mut edit_file_string)
+				.fmt_as_edit(&mut edit_file_string, &ctx.config)
 				.expect("infallible write to String");
 
 			crate::write_flush_sync(
@@ -439,8 +870,8 @@ pub fn update_record<R: RecordType>(ctx: &mut AppCtx, edit_prefix: &str) -> Resu
 		Some(efe) => efe,
 		None => return Ok(()),
 	};
-	// get number portion of the file name
-	let number_portion = edit_file_entry
+	// get ID or key portion of the file name
+	let key_portion = edit_file_entry
 		.file_name()
 		.to_str()
 		.expect("we filter to_str() on is_some_and() when searching for files")
@@ -448,17 +879,28 @@ pub fn update_record<R: RecordType>(ctx: &mut AppCtx, edit_prefix: &str) -> Resu
 		.expect("we check that name starts with the edit prefix when searching")
 		.strip_suffix(".toml")
 		.expect("we check that name ends with .toml when searching");
-	// parse number
-	let id: u64 = number_portion.parse::<u64>().with_context(|| {
+	let key_portion = key_portion.strip_prefix('-').unwrap_or(key_portion);
+	let id: u64 = find_id_by_id_or_key::<R>(ctx, key_portion).with_context(|| {
 		format!(
-			"{} edit file name has non-integer in number portion: \"{number_portion}\"",
+			"{} edit file name has unknown ID or key: \"{key_portion}\"",
 			err_loc!()
 		)
 	})?;
 
 	// load update file
-	let edit_file: R::EditFile = open_edit_file(&edit_file_entry)
+	let edit_file: R::EditFile = open_edit_file(&edit_file_entry, &ctx.config)
 		.with_context(|| format!("{} can't get edit file", err_loc!()))?;
+	apply_edit::<R>(ctx, id, edit_file)
+}
+
+/// Replaces the record at `id` with a sanitized edit file, refusing edits that conflict with
+/// another record.
+pub fn apply_edit<R: RecordType>(
+	ctx: &mut AppCtx,
+	id: u64,
+	mut edit_file: R::EditFile,
+) -> Result<()> {
+	R::resolve_edit(ctx, &mut edit_file)?;
 
 	// load table
 
@@ -479,19 +921,46 @@ pub fn update_record<R: RecordType>(ctx: &mut AppCtx, edit_prefix: &str) -> Resu
 	}
 
 	// find in db
+	let records: &mut Vec<R> = R::get_tbl_mut(ctx);
 	let record = match records.get_mut(id as usize) {
-		Some(c) => c,
-		None => records
+		Some(c) if c.get_id() == id => c,
+		_ => records
 			.iter_mut()
 			.find(|c| c.get_id() == id)
 			.ok_or_else(|| anyhow!(format!("{} no record at ID: {id}", err_loc!())))?,
 	};
 
-	// update
+	// update, the current version is only kept once the edit is written
+	let previous = record.clone();
 	record.update_from_edit(edit_file);
 	record.set_accepted();
 	// ctx.write_components()
-	R::write_table(ctx)
+	if let Err(e) = R::write_table(ctx) {
+		if let Some(record) = R::get_tbl_mut(ctx).iter_mut().find(|c| c.get_id() == id) {
+			*record = previous;
+		}
+		return Err(e);
+	}
+	R::archive(ctx, previous)
+}
+
+/// Loads a table added after the project may have been made, creating it with only its header.
+/// A read-only session doesn't create it and reads it as empty.
+fn load_added_table(
+	table_path: &Path,
+	header: &str,
+	table: &str,
+	read_only: bool,
+) -> Result<(Option<File>, String)> {
+	if !table_path.exists() {
+		if read_only {
+			return Ok((None, header.to_string()));
+		}
+		eprintln!("Creating {table} table at: {:?}", table_path);
+		create_table(table_path, header)?;
+	}
+	let (file, raw_table) = load_table(table_path, true)?;
+	Ok((Some(file), raw_table))
 }
 
 pub fn load_table(table_path: impl AsRef<Path>, write: bool) -> Result<(File, String)> {
@@ -571,6 +1040,43 @@ pub fn escape_normalize_nl(input: &str) -> Cow<'_, str> {
 	input
 }
 
+/// Errors on a tag holding a tab, new-line, or the tag separator.
+pub fn check_tags(tags: &[String]) -> Result<()> {
+	match tags.iter().find(|t| !reqtsv_lib::is_valid_tag(t)) {
+		Some(bad) => Err(anyhow!(format!(
+			"{} tag: {bad:?} is empty or contains a tab, new-line, or \"{}\"",
+			err_loc!(),
+			reqtsv_lib::TAG_SEPARATOR
+		))),
+		None => Ok(()),
+	}
+}
+
+/// Writes the `tags` field of a draft or edit file.
+pub fn fmt_tags_field(f: &mut impl std::fmt::Write, tags: &[String]) -> std::fmt::Result {
+	writeln!(
+		f,
+		"# A list of tags (e.g. [\"safety\", \"ui\"]), do not include tabs, new-lines, or \"{}\"",
+		reqtsv_lib::TAG_SEPARATOR
+	)?;
+	writeln!(f, "tags = {}", toml::Value::from(tags.to_vec()))
+}
+
+/// Writes the `author` field of a draft file, filled in with the project's default author if it has one.
+pub fn fmt_draft_author_field(
+	f: &mut impl std::fmt::Write,
+	config: &ProjectConfig,
+	placeholder: &str,
+) -> std::fmt::Result {
+	let author = if config.default_author.is_empty() {
+		placeholder
+	} else {
+		config.default_author.as_str()
+	};
+	writeln!(f, "# Do not include any new-lines in the author field")?;
+	writeln!(f, "author = \"{author}\"")
+}
+
 pub fn contains_any(to_find: &[char], search_str: &str) -> bool {
 	search_str.chars().find(|ch| to_find.contains(ch)).is_some()
 }
@@ -586,22 +1092,8 @@ pub fn find_positions(to_find: &[char], search_str: &str) -> Option<Box<[(usize,
 	op_vec.map(|vec| vec.into_boxed_slice())
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
-pub enum RecordStatus {
-	Draft,
-	Accepted,
-	Deleted,
-}
+pub use reqtsv_lib::RecordStatus;
 
-impl Display for RecordStatus {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		match self {
-			RecordStatus::Draft => write!(f, "Draft"),
-			RecordStatus::Accepted => write!(f, "Accepted"),
-			RecordStatus::Deleted => write!(f, "Deleted"),
-		}
-	}
-}
 pub fn create_draft_file<EF: EditFile>(ctx: &mut AppCtx, draft_prefix: &'static str) -> Result<()> {
 	let (file_handle, file_path) =
 		get_rand_file(&ctx.project_root, draft_prefix).map_err(|e| match e {
@@ -618,7 +1110,7 @@ pub fn create_draft_file<EF: EditFile>(ctx: &mut AppCtx, draft_prefix: &'static
 
 	let mut buf = String::with_capacity(1024);
 
-	EF::fmt_as_draft(&mut buf).expect("infallible write to String");
+	EF::fmt_as_draft(&mut buf, &ctx.config).expect("infallible write to String");
 
 	crate::write_flush_sync(crate::WriteFlushSync::Done(file_handle), buf.as_bytes()).context(
 		formatcp!("{} can't write default draft to disk", err_loc!()),
@@ -673,8 +1165,8 @@ pub fn delete_record<R>(ctx: &mut AppCtx) -> Result<()>
 where
 	R: RecordType,
 {
-	let op_id =
-		prompt_for_record_id().context(formatcp!("{} can't prompt for record ID", err_loc!(),))?;
+	let op_id = prompt_for_record_id::<R>(ctx)
+		.context(formatcp!("{} can't prompt for record ID", err_loc!(),))?;
 	match op_id {
 		Some(id) => {
 			println!("Attempting to delete record at ID: {id}...");
@@ -701,8 +1193,7 @@ where
 			match ans {
 				true => {
 					println!("Deleting...");
-					record.set_deleted();
-					R::write_table(ctx)
+					delete_record_by_id::<R>(ctx, id)
 				}
 				false => {
 					println!("canceling...");
@@ -713,3 +1204,18 @@ where
 		None => Ok(()),
 	}
 }
+
+/// Marks the record at `id` deleted, it stays in the table until purged.
+pub fn delete_record_by_id<R: RecordType>(ctx: &mut AppCtx, id: u64) -> Result<()> {
+	let record = mut_record_by_id(R::get_tbl_mut(ctx), id)
+		.context(formatcp!("{} can't find record", err_loc!()))?;
+	if let RecordStatus::Deleted = record.get_status() {
+		return Err(anyhow!(format!(
+			"{} record: {} already deleted",
+			err_loc!(),
+			record.get_key()
+		)));
+	}
+	record.set_deleted();
+	R::write_table(ctx)
+}
@@ -0,0 +1,137 @@
+use std::fmt::Display;
+
+use anyhow::{Context, Result, anyhow};
+use chrono::Local;
+use const_format::formatcp;
+use reqtsv_lib::{Link, LinkKind, RecordStatus};
+use strum::IntoEnumIterator;
+
+use crate::{
+	AppCtx, RecordType, err_loc, prompt_for_record_id, ref_record_by_id, requirement::Requirement,
+};
+
+/// A link as shown in the "Remove Link" list.
+#[derive(Debug, Clone)]
+struct LinkChose {
+	id: u64,
+	description: String,
+}
+
+impl Display for LinkChose {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.description)
+	}
+}
+
+fn requirement_key(ctx: &AppCtx, id: u64) -> String {
+	ref_record_by_id::<Requirement>(ctx, id)
+		.map(|r| r.get_key().into_owned())
+		.unwrap_or_else(|| format!("missing ID: {id}"))
+}
+
+pub fn add_link(ctx: &mut AppCtx) -> Result<()> {
+	println!("Pick the requirement the link starts from");
+	let from_id = match prompt_for_record_id::<Requirement>(ctx)
+		.context(formatcp!("{} can't prompt for requirement ID", err_loc!()))?
+	{
+		Some(id) => id,
+		None => return Ok(()),
+	};
+
+	let kind = match inquire::Select::new("How does it relate?", LinkKind::iter().collect())
+		.prompt_skippable()
+		.context(formatcp!("{} can't prompt for link kind", err_loc!()))?
+	{
+		Some(kind) => kind,
+		None => return Ok(()),
+	};
+
+	println!("Pick the requirement the link points to");
+	let to_id = match prompt_for_record_id::<Requirement>(ctx)
+		.context(formatcp!("{} can't prompt for requirement ID", err_loc!()))?
+	{
+		Some(id) => id,
+		None => return Ok(()),
+	};
+
+	if from_id == to_id {
+		return Err(anyhow!(formatcp!(
+			"{} a requirement can't link to itself",
+			err_loc!()
+		)));
+	}
+	if let Some(existing) = ctx
+		.links
+		.iter()
+		.find(|l| l.is_active() && l.from_id == from_id && l.to_id == to_id && l.kind == kind)
+	{
+		return Err(anyhow!(format!(
+			"{} the same link already exists at ID: {}",
+			err_loc!(),
+			existing.id
+		)));
+	}
+
+	let id = ctx.links.iter().map(|l| l.id + 1).max().unwrap_or(0);
+	println!(
+		"Linked: {} {kind} {} at ID: {id}",
+		requirement_key(ctx, from_id),
+		requirement_key(ctx, to_id)
+	);
+	ctx.links.push(Link {
+		id,
+		from_id,
+		kind,
+		to_id,
+		creation_date: Local::now(),
+		status: RecordStatus::Accepted,
+	});
+	ctx.write_links()
+}
+
+pub fn remove_link(ctx: &mut AppCtx) -> Result<()> {
+	let req_id = match prompt_for_record_id::<Requirement>(ctx)
+		.context(formatcp!("{} can't prompt for requirement ID", err_loc!()))?
+	{
+		Some(id) => id,
+		None => return Ok(()),
+	};
+
+	// incoming and outgoing
+	let links: Vec<LinkChose> = ctx
+		.links
+		.iter()
+		.filter(|l| l.is_active() && (l.from_id == req_id || l.to_id == req_id))
+		.map(|l| LinkChose {
+			id: l.id,
+			description: format!(
+				"{} {} {}",
+				requirement_key(ctx, l.from_id),
+				l.kind,
+				requirement_key(ctx, l.to_id)
+			),
+		})
+		.collect();
+	if links.is_empty() {
+		return Err(anyhow!(format!(
+			"{} requirement at ID: {req_id} has no links",
+			err_loc!()
+		)));
+	}
+
+	let link_id = match inquire::Select::new("Chose the link to remove", links)
+		.prompt_skippable()
+		.context(formatcp!("{} can't prompt for link", err_loc!()))?
+	{
+		Some(link) => link.id,
+		None => return Ok(()),
+	};
+
+	let link = ctx
+		.links
+		.iter_mut()
+		.find(|l| l.id == link_id)
+		.expect("we only list links from the table");
+	link.status = RecordStatus::Deleted;
+	ctx.write_links()
+}
@@ -0,0 +1,66 @@
+//! Lists components or requirements in the menus, picked with a filter expression, see
+//! [`reqtsv_lib::filter`].
+
+use anyhow::{Context, Result};
+use const_format::formatcp;
+use inline_colorization::*;
+use reqtsv_lib::{
+	filter::select,
+	json::{ComponentJson, RequirementJson},
+};
+
+use crate::{AppCtx, cli::parse_filter, err_loc};
+
+/// `None` when the prompt is skipped, an empty filter lists every record.
+fn prompt_filter(kind: &str) -> Result<Option<String>> {
+	inquire::Text::new(&format!("Which {kind}?"))
+		.with_help_message(
+			"A filter e.g. status = Accepted and creation_date > 2025-06-01, empty lists all",
+		)
+		.prompt_skippable()
+		.context(formatcp!("{} can't prompt for filter", err_loc!()))
+}
+
+pub fn list_components(ctx: &mut AppCtx) -> Result<()> {
+	let Some(expr) = prompt_filter("components")? else {
+		return Ok(());
+	};
+	let filter = parse_filter(Some(&expr))?;
+	let (components, _) = ctx.lib_records()?;
+	let json = components
+		.iter()
+		.map(|c| ComponentJson::new(c, &components))
+		.collect();
+	let selected = select(json, filter.as_ref())
+		.context(formatcp!("{} can't filter components", err_loc!()))?;
+	for component in &selected {
+		println!(
+			"{color_cyan}{}{color_reset}\t{}\t{}",
+			component.key, component.status, component.path
+		);
+	}
+	println!("{} of {} components", selected.len(), components.len());
+	Ok(())
+}
+
+pub fn list_requirements(ctx: &mut AppCtx) -> Result<()> {
+	let Some(expr) = prompt_filter("requirements")? else {
+		return Ok(());
+	};
+	let filter = parse_filter(Some(&expr))?;
+	let (components, requirements) = ctx.lib_records()?;
+	let json = requirements
+		.iter()
+		.map(|r| RequirementJson::new(r, &components, &requirements, &ctx.config.fields))
+		.collect();
+	let selected = select(json, filter.as_ref())
+		.context(formatcp!("{} can't filter requirements", err_loc!()))?;
+	for requirement in &selected {
+		println!(
+			"{color_cyan}{}{color_reset}\t{}\t{}\t{}",
+			requirement.key, requirement.status, requirement.priority, requirement.title
+		);
+	}
+	println!("{} of {} requirements", selected.len(), requirements.len());
+	Ok(())
+}
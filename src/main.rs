@@ -1,19 +1,26 @@
-use std::{
-	fs::rename,
-	path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow};
 use argh::FromArgs;
 use const_format::formatcp;
 use inline_colorization::*;
 use reqtsv::{
-	AppCtx, COLUMN_DELIMITER,
-	component::{self, COMPONENT_TABLE_NAME, Component},
-	err_loc, init_project, load_table,
+	AppCtx, cli,
+	component::{self},
+	err_loc, init_project,
 	project::{self},
-	requirement::{REQUIREMENT_TABLE_NAME, Requirement, RequirementMenu},
+	recover_tables,
+	requirement::RequirementMenu,
+	search::search_menu,
 	select_menu::select_menu_loop,
+	test_case::TestCaseMenu,
+	undo::{redo, undo},
+};
+use reqtsv_lib::{
+	config::load_project_config,
+	lock::{LockErr, ProjectLock, acquire_lock},
+	merge::merge_tables,
+	schema::migrate_project,
 };
 
 fn main() -> Result<()> {
@@ -27,79 +34,77 @@ fn main() -> Result<()> {
 		return Ok(());
 	}
 
+	if let Some(command) = reqtsv.command {
+		return run_command(command, reqtsv.project, reqtsv.read_only);
+	}
+
 	// let temp = tempdir::TempDir::new("reqtsv_example").unwrap();
 	// let project_root = temp.path();
-	let project_root = reqtsv.project.into_boxed_path();
+	let project_root = project_root(reqtsv.project)?;
+
+	// held until the tables are swapped in at exit
+	let lock = if reqtsv.read_only {
+		None
+	} else {
+		lock_project(&project_root)?
+	};
+	let read_only = lock.is_none();
+	if read_only {
+		println!("{color_yellow}Opening project read-only, changes can't be saved{color_reset}");
+	}
 
 	if reqtsv.init {
+		if read_only {
+			return Err(anyhow!(formatcp!(
+				"{} can't initialize a project read-only",
+				err_loc!()
+			)));
+		}
 		println!("Creating new project at: {:?}", &project_root);
 		init_project(&project_root).context("Failed to initialize project")?;
 	}
 
-	//serialize and verify both tables
-	println!("Loading component table...");
-	let component_tbl_path = project_root.join(COMPONENT_TABLE_NAME).into_boxed_path();
+	let config = load_project_config(&project_root).context("Failed to load project config")?;
+	let tables = config.tables.clone();
 
-	let (component_file, raw_component_tbl) = load_table(component_tbl_path, true)?;
-
-	let mut tsv_reader = csv::ReaderBuilder::new()
-		.delimiter(COLUMN_DELIMITER)
-		.terminator(csv::Terminator::Any(b'\n'))
-		.from_reader(raw_component_tbl.as_bytes());
-
-	let max_records = raw_component_tbl.chars().filter(|ch| *ch == '\n').count();
-	let mut components: Vec<Component> = Vec::with_capacity(max_records);
-	for res in tsv_reader
-		.deserialize::<Component>()
-		.map(|res| res.context(formatcp!("{} corrupt table entry", err_loc!())))
-	{
-		match res {
-			Ok(record) => {
-				components.push(record);
-			}
-			Err(e) => return Err(e),
+	if !read_only {
+		let (journal, recoveries) = recover_tables(&project_root, &tables)?;
+		if let Some(journal) = journal {
+			println!("{color_yellow}Recovered interrupted commit, {journal}{color_reset}");
+		}
+		for recovery in recoveries {
+			println!("{color_yellow}Recovered interrupted save of {recovery}{color_reset}");
 		}
 	}
 
-	println!("Loading requirement table...");
-	let requirement_tbl_path = project_root.join(REQUIREMENT_TABLE_NAME).into_boxed_path();
-
-	let (requirement_file, raw_requirement_tbl) = load_table(requirement_tbl_path, true)?;
-
-	let mut tsv_reader = csv::ReaderBuilder::new()
-		.delimiter(COLUMN_DELIMITER)
-		.terminator(csv::Terminator::Any(b'\n'))
-		.from_reader(raw_requirement_tbl.as_bytes());
-
-	let max_records = raw_requirement_tbl.chars().filter(|ch| *ch == '\n').count();
-	let mut requirements: Vec<Requirement> = Vec::with_capacity(max_records);
-	for res in tsv_reader
-		.deserialize::<Requirement>()
-		.map(|res| res.context(formatcp!("{} corrupt table entry", err_loc!())))
-	{
-		match res {
-			Ok(record) => {
-				requirements.push(record);
+	if reqtsv.migrate {
+		if read_only {
+			return Err(anyhow!(formatcp!(
+				"{} can't migrate a project read-only",
+				err_loc!()
+			)));
+		}
+		let migration = migrate_project(&project_root)
+			.context(formatcp!("{} can't migrate project", err_loc!()))?;
+		match migration.backup {
+			Some(backup) => {
+				println!(
+					"Migrated project from schema version {} to {}",
+					migration.from, migration.to
+				);
+				for table_name in migration.migrated {
+					println!("Upgraded table: {table_name}");
+				}
+				for table_name in migration.created {
+					println!("Created table: {table_name}");
+				}
+				println!("Backup of the old tables at: {backup:?}");
 			}
-			Err(e) => return Err(e),
+			None => println!("Project is already at schema version {}", migration.to),
 		}
+		return Ok(());
 	}
-
-	println!("Project Root: {:?}", &project_root);
-	let component_new_path: Box<Path> = project_root.join("component.new.tsv").into_boxed_path();
-	let requirement_new_path: Box<Path> =
-		project_root.join("requirement.new.tsv").into_boxed_path();
-	let mut app_ctx = AppCtx {
-		components,
-		requirements,
-		project_root,
-		component_file,
-		requirement_file,
-		component_new_path,
-		requirement_new_path,
-		updated_component: false,
-		updated_requirement: false,
-	};
+	let mut app_ctx = AppCtx::load(project_root, config, read_only)?;
 
 	if let Err(e) = main_menu_loop(&mut app_ctx) {
 		for e in e.chain() {
@@ -108,70 +113,30 @@ fn main() -> Result<()> {
 		eprintln!("{color_red}Exiting...{color_reset}")
 	};
 
-	let req_new = app_ctx.requirement_new_path;
-	let comp_new = app_ctx.component_new_path;
+	let saved = app_ctx.save();
+	drop(lock);
+	saved
+}
 
-	drop(app_ctx.component_file);
-	drop(app_ctx.requirement_file);
-	let project_root = app_ctx.project_root;
-	if app_ctx.updated_requirement {
-		let req_current = project_root.join(REQUIREMENT_TABLE_NAME);
-		let req_old = project_root.join("requirement.old.tsv");
-		// mv x.tsv x.old.tsv
-		match rename(&req_current, &req_old).with_context(|| {
-			format!(
-				"{} can't move {:?} to {:?}",
-				err_loc!(),
-				&req_current,
-				&req_old
-			)
-		}) {
-			Err(e) => {
-				// we want to try moving the other table so don't return on error here
-				eprintln!("{color_red}{e}{color_reset}")
-			}
-			_ => {
-				// mv x.new.tsv x.tsv
-				rename(&req_new, &req_current).with_context(|| {
-					format!(
-						"{} can't move {:?} to {:?}",
-						err_loc!(),
-						&req_new,
-						&req_current
-					)
-				})?;
+/// `None` when another session holds the lock and the user opens the project read-only.
+fn lock_project(project_root: &Path) -> Result<Option<ProjectLock>> {
+	match acquire_lock(project_root) {
+		Ok(lock) => Ok(Some(lock)),
+		Err(e @ (LockErr::Held(_) | LockErr::Busy(_))) => {
+			eprintln!("{color_yellow}{e}{color_reset}");
+			let read_only = inquire::Confirm::new("Open the project read-only?")
+				.with_default(false)
+				.with_help_message("changes can't be saved in read-only mode")
+				.prompt()
+				.context(formatcp!("{} error prompting for read-only", err_loc!()))?;
+			if read_only {
+				Ok(None)
+			} else {
+				Err(anyhow!(formatcp!("{} project is locked", err_loc!())))
 			}
 		}
-		std::fs::remove_file(&req_old)
-			.with_context(|| format!("{} can't delete {:?}", err_loc!(), &req_old))?;
+		Err(e) => Err(anyhow!(e).context(formatcp!("{} can't lock project", err_loc!()))),
 	}
-	if app_ctx.updated_component {
-		let comp_current = project_root.join(COMPONENT_TABLE_NAME);
-		let comp_old = project_root.join("component.old.tsv");
-		// mv x.tsv x.old.tsv
-		// we don't want to try to move new to current after this if this errors...
-		// so return on error
-		rename(&comp_current, &comp_old).with_context(|| {
-			format!(
-				"{} can't move {:?} to {:?}",
-				err_loc!(),
-				&comp_current,
-				&comp_old
-			)
-		})?;
-		// mv x.new.tsv x.tsv
-		rename(&comp_new, &comp_current).with_context(|| {
-			format!(
-				"{} can't move {:?} to {:?}",
-				err_loc!(),
-				&comp_current,
-				&comp_old
-			)
-		})?;
-		std::fs::remove_file(&comp_old)
-			.with_context(|| format!("{} can't delete {:?}", err_loc!(), &comp_old))?;
-	}
-	Ok(())
 }
 
 #[derive(FromArgs, Debug, PartialEq)]
@@ -181,11 +146,109 @@ struct Reqtsv {
 	/// print version number and exit
 	version: bool,
 	#[argh(positional)]
-	/// directory containing project
-	project: PathBuf,
+	/// directory containing project, needed unless a command says otherwise
+	project: Option<PathBuf>,
 	#[argh(switch, short = 'i')]
 	/// initialize project and exit
 	init: bool,
+	#[argh(switch, short = 'r')]
+	/// open the project without locking it, changes can't be saved
+	read_only: bool,
+	#[argh(switch)]
+	/// upgrade the project's tables to the current schema, after backing them up, and exit
+	migrate: bool,
+	#[argh(subcommand)]
+	command: Option<Command>,
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand)]
+enum Command {
+	MergeDriver(MergeDriver),
+	Component(cli::ComponentCmd),
+	Requirement(cli::RequirementCmd),
+	Search(cli::SearchCmd),
+	Check(cli::CheckCmd),
+}
+
+#[derive(FromArgs, Debug, PartialEq)]
+#[argh(subcommand, name = "merge-driver")]
+/// Merge a table by record, for git to run as "reqtsv merge-driver %O %A %B", see reqtsv_lib::merge.
+/// Exits with 1 if there are conflicts to resolve, or records were renumbered as references to
+/// them from other tables aren't rewritten.
+struct MergeDriver {
+	#[argh(positional)]
+	/// the common ancestor's table
+	base: PathBuf,
+	#[argh(positional)]
+	/// our table, replaced by the merged table
+	ours: PathBuf,
+	#[argh(positional)]
+	/// their table
+	theirs: PathBuf,
+}
+
+/// Subcommands other than the merge driver exit with [`cli::exit_code`] when they fail.
+fn run_command(command: Command, project: Option<PathBuf>, read_only: bool) -> Result<()> {
+	let res = match command {
+		Command::MergeDriver(args) => return merge_driver(args),
+		Command::Component(cmd) => {
+			project_root(project).and_then(|root| cli::run_component(root, read_only, cmd))
+		}
+		Command::Requirement(cmd) => {
+			project_root(project).and_then(|root| cli::run_requirement(root, read_only, cmd))
+		}
+		Command::Search(cmd) => project_root(project).and_then(|root| cli::run_search(root, cmd)),
+		Command::Check(cmd) => project_root(project).and_then(|root| cli::run_check(root, cmd)),
+	};
+	if let Err(e) = res {
+		for e in e.chain() {
+			eprintln!("{color_red}{e}{color_reset}")
+		}
+		std::process::exit(cli::exit_code(&e));
+	}
+	Ok(())
+}
+
+fn project_root(project: Option<PathBuf>) -> Result<Box<Path>> {
+	project
+		.map(PathBuf::into_boxed_path)
+		.ok_or_else(|| anyhow!(formatcp!("{} no project directory given", err_loc!())))
+}
+
+fn merge_driver(args: MergeDriver) -> Result<()> {
+	let read = |path: &Path| {
+		std::fs::read_to_string(path)
+			.with_context(|| format!("{} can't read table at: {:?}", err_loc!(), path))
+	};
+	let merged = merge_tables(&read(&args.base)?, &read(&args.ours)?, &read(&args.theirs)?)
+		.with_context(|| format!("{} can't merge table at: {:?}", err_loc!(), &args.ours))?;
+	std::fs::write(&args.ours, &merged.table).with_context(|| {
+		format!(
+			"{} can't write merged table to: {:?}",
+			err_loc!(),
+			&args.ours
+		)
+	})?;
+	for renumbered in merged.renumbered.iter() {
+		eprintln!(
+			"{color_yellow}Their new record ID: {} is now ID: {}, update what refers to it{color_reset}",
+			renumbered.old_id, renumbered.new_id
+		);
+	}
+	for conflict in merged.conflicts.iter() {
+		eprintln!("{color_red}Conflict: {conflict}{color_reset}");
+	}
+	if !merged.conflicts.is_empty() || !merged.renumbered.is_empty() {
+		return Err(anyhow!(format!(
+			"{} {} conflicts and {} renumbered records in: {:?}",
+			err_loc!(),
+			merged.conflicts.len(),
+			merged.renumbered.len(),
+			&args.ours
+		)));
+	}
+	Ok(())
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -194,6 +257,10 @@ enum MainMenu {
 	Project,
 	Exit,
 	Requirement,
+	Search,
+	TestCase,
+	Undo,
+	Redo,
 }
 
 impl AsRef<str> for MainMenu {
@@ -203,6 +270,10 @@ impl AsRef<str> for MainMenu {
 			MainMenu::Exit => "Exit",
 			MainMenu::Project => "Project",
 			MainMenu::Requirement => "Requirement",
+			MainMenu::Search => "Search",
+			MainMenu::TestCase => "Test Case",
+			MainMenu::Undo => "Undo",
+			MainMenu::Redo => "Redo",
 		}
 	}
 }
@@ -212,6 +283,10 @@ enum DoNext {
 	ComponentMenu,
 	ProjectMenu,
 	RequirementMenu,
+	TestCaseMenu,
+	Search,
+	Undo,
+	Redo,
 	Exit,
 	Loop,
 }
@@ -224,7 +299,7 @@ impl MainMenu {
 
 fn main_menu_loop(app_ctx: &mut AppCtx) -> Result<()> {
 	loop {
-		let state = main_menu()?;
+		let state = main_menu(app_ctx)?;
 		match state {
 			DoNext::ComponentMenu => {
 				select_menu_loop::<component::ComponentMenu>(app_ctx, "components")?;
@@ -235,6 +310,12 @@ fn main_menu_loop(app_ctx: &mut AppCtx) -> Result<()> {
 			DoNext::RequirementMenu => {
 				select_menu_loop::<RequirementMenu>(app_ctx, "requirements")?;
 			}
+			DoNext::TestCaseMenu => {
+				select_menu_loop::<TestCaseMenu>(app_ctx, "test cases")?;
+			}
+			DoNext::Search => report_error("search", search_menu(app_ctx))?,
+			DoNext::Undo => report_error("undo", undo(app_ctx))?,
+			DoNext::Redo => report_error("redo", redo(app_ctx))?,
 			DoNext::Exit => {
 				println!("Exiting...");
 				return Ok(());
@@ -244,13 +325,38 @@ fn main_menu_loop(app_ctx: &mut AppCtx) -> Result<()> {
 	}
 }
 
-fn main_menu() -> Result<DoNext> {
-	let options: Vec<&str> = vec![
+/// Prints the error unless the user cancelled a prompt, like [`select_menu_loop`].
+fn report_error(purpose: &str, res: Result<()>) -> Result<()> {
+	match res {
+		Err(e) if e.downcast_ref::<inquire::InquireError>().is_some() => Err(e),
+		Err(e) => {
+			eprintln!("{color_red}Can't {purpose} due to:{color_reset}");
+			for e in e.chain() {
+				eprintln!("{color_red}{e}{color_reset}")
+			}
+			Ok(())
+		}
+		Ok(()) => Ok(()),
+	}
+}
+
+fn main_menu(app_ctx: &AppCtx) -> Result<DoNext> {
+	let mut options: Vec<&str> = vec![
 		MainMenu::Requirement.as_ref(),
 		MainMenu::Component.as_ref(),
+		MainMenu::TestCase.as_ref(),
 		MainMenu::Project.as_ref(),
-		MainMenu::Exit.as_ref(),
+		MainMenu::Search.as_ref(),
 	];
+	if let Some(description) = app_ctx.operations.next_undo() {
+		println!("Can undo: {description}");
+		options.push(MainMenu::Undo.as_ref());
+	}
+	if let Some(description) = app_ctx.operations.next_redo() {
+		println!("Can redo: {description}");
+		options.push(MainMenu::Redo.as_ref());
+	}
+	options.push(MainMenu::Exit.as_ref());
 
 	let ans: Result<Option<&str>, inquire::InquireError> =
 		inquire::Select::new("What would you like to operate on?", options).prompt_skippable();
@@ -258,7 +364,11 @@ fn main_menu() -> Result<DoNext> {
 	match ans {
 		Ok(Some(choice)) if MainMenu::Requirement.visit(choice) => Ok(DoNext::RequirementMenu),
 		Ok(Some(choice)) if MainMenu::Component.visit(choice) => Ok(DoNext::ComponentMenu),
+		Ok(Some(choice)) if MainMenu::TestCase.visit(choice) => Ok(DoNext::TestCaseMenu),
 		Ok(Some(choice)) if MainMenu::Project.visit(choice) => Ok(DoNext::ProjectMenu),
+		Ok(Some(choice)) if MainMenu::Search.visit(choice) => Ok(DoNext::Search),
+		Ok(Some(choice)) if MainMenu::Undo.visit(choice) => Ok(DoNext::Undo),
+		Ok(Some(choice)) if MainMenu::Redo.visit(choice) => Ok(DoNext::Redo),
 		Ok(Some(choice)) if MainMenu::Exit.visit(choice) => Ok(DoNext::Exit),
 		Err(iqe) => {
 			Err(anyhow!(iqe).context(formatcp!("{} error prompting main menu", err_loc!())))
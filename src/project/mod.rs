@@ -1,14 +1,28 @@
-use std::fmt::Display;
+use std::{borrow::Cow, collections::BTreeMap, fmt::Display, path::Path};
 
+use anyhow::{Context, Result, anyhow};
+use chrono::Local;
+use const_format::formatcp;
+use reqtsv_lib::Link;
+use serde::Serialize;
 use strum::{EnumIter, EnumString, IntoEnumIterator};
 use thiserror::Error;
+use walkdir::WalkDir;
 
 use crate::{
-	AppCtx,
+	AppCtx, RecordType,
+	component::{COMPONENT_DRAFT_PREFIX, COMPONENT_EDIT_PREFIX},
+	err_loc,
+	purge::{next_id, purge_deleted},
+	requirement::{REQUIREMENT_DRAFT_PREFIX, REQUIREMENT_EDIT_PREFIX},
 	select_menu::{AfterRun, SelectMenu},
+	serialize_table,
+	test_case::{TEST_CASE_DRAFT_PREFIX, TEST_CASE_EDIT_PREFIX},
+	write_flush_sync,
 };
 
-use anyhow::Result;
+/// Old record ID to new record ID.
+pub type IdMap = BTreeMap<u64, u64>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct DocFileName {
@@ -93,6 +107,8 @@ pub const DOC_FILE_EXTENTION: &'static str = ".md";
 
 #[derive(Debug, Copy, Clone, strum_macros::Display, EnumIter, EnumString)]
 pub enum ProjectMenu {
+	#[strum(serialize = "Purge Deleted")]
+	PurgeDeleted,
 	/// Makes search more efficiant after a purge
 	#[strum(serialize = "Re-Number all IDs")]
 	ReNumberAll,
@@ -107,16 +123,18 @@ impl SelectMenu for ProjectMenu {
 		Self::iter().collect()
 	}
 
-	fn run(&mut self, _ctx: &mut AppCtx) -> Result<()> {
+	fn run(&mut self, ctx: &mut AppCtx) -> Result<()> {
 		match self {
-			ProjectMenu::ReNumberAll => todo!(),
+			ProjectMenu::PurgeDeleted => purge_deleted(ctx),
+			ProjectMenu::ReNumberAll => renumber_all(ctx),
 			ProjectMenu::BuildDocs => todo!(),
-			ProjectMenu::Back => todo!(),
+			ProjectMenu::Back => Ok(()),
 		}
 	}
 
 	fn purpose(&self) -> &'static str {
 		match self {
+			ProjectMenu::PurgeDeleted => "purge deleted records",
 			ProjectMenu::ReNumberAll => "re-number records",
 			ProjectMenu::BuildDocs => "build docs",
 			ProjectMenu::Back => "go back to main menu",
@@ -126,8 +144,290 @@ impl SelectMenu for ProjectMenu {
 	fn after(&self) -> AfterRun {
 		match self {
 			ProjectMenu::Back => AfterRun::GoBack,
-			ProjectMenu::ReNumberAll => AfterRun::Continue,
+			ProjectMenu::PurgeDeleted | ProjectMenu::ReNumberAll => AfterRun::Continue,
 			ProjectMenu::BuildDocs => todo!(),
 		}
 	}
+
+	fn undoable(&self) -> bool {
+		// undoing would put back records that are also in the archives, or leave an ID map that
+		// no longer applies
+		!matches!(self, ProjectMenu::PurgeDeleted | ProjectMenu::ReNumberAll)
+	}
+}
+
+/// A line of the file [`renumber_all`] writes so documents that cite IDs can be updated.
+#[derive(Debug, Serialize)]
+struct IdMapping<'k> {
+	table: &'static str,
+	old_id: u64,
+	new_id: u64,
+	key: Cow<'k, str>,
+}
+
+/// Dense IDs for the records of `table_name` in the current order, starting past the IDs in its
+/// archive e.g. 0, 3, 7 become 0, 1, 2 with no archive, or 5, 6, 7 when 4 was purged.
+pub(crate) fn dense_ids(
+	project_root: &Path,
+	table_name: &str,
+	ids: impl Iterator<Item = u64>,
+) -> Result<IdMap> {
+	// purged IDs are never handed out again, see `next_id`
+	let first_id = next_id(project_root, table_name, std::iter::empty())?;
+	Ok(dense_ids_from(ids, first_id))
+}
+
+fn dense_ids_from(ids: impl Iterator<Item = u64>, first_id: u64) -> IdMap {
+	let mut old_ids: Vec<u64> = ids.collect();
+	old_ids.sort_unstable();
+	old_ids.into_iter().zip(first_id..).collect()
+}
+
+fn is_identity(ids: &IdMap) -> bool {
+	ids.iter().all(|(old_id, new_id)| old_id == new_id)
+}
+
+/// Drafts and edit files can name records by ID, so they'd point at the wrong record after.
+/// Looks as deep as the menus that insert them do.
+fn pending_files(project_root: &Path) -> Result<Vec<String>> {
+	let prefixes = [
+		COMPONENT_DRAFT_PREFIX,
+		COMPONENT_EDIT_PREFIX,
+		REQUIREMENT_DRAFT_PREFIX,
+		REQUIREMENT_EDIT_PREFIX,
+		TEST_CASE_DRAFT_PREFIX,
+		TEST_CASE_EDIT_PREFIX,
+	];
+	let mut pending = Vec::new();
+	for entry in WalkDir::new(project_root).min_depth(1).max_depth(2) {
+		let entry =
+			entry.with_context(|| format!("{} can't read: {:?}", err_loc!(), project_root))?;
+		let name = entry.file_name().to_string_lossy();
+		if entry.file_type().is_file()
+			&& name.ends_with(".toml")
+			&& prefixes.iter().any(|p| name.starts_with(p))
+		{
+			let path = entry
+				.path()
+				.strip_prefix(project_root)
+				.unwrap_or(entry.path());
+			pending.push(path.to_string_lossy().into_owned());
+		}
+	}
+	pending.sort();
+	Ok(pending)
+}
+
+fn renumber_links(links: &mut [Link], requirement_ids: &IdMap) -> Result<()> {
+	for link in links.iter_mut() {
+		let (Some(from_id), Some(to_id)) = (
+			requirement_ids.get(&link.from_id),
+			requirement_ids.get(&link.to_id),
+		) else {
+			return Err(anyhow!(format!(
+				"{} link ID: {} refers to a missing requirement",
+				err_loc!(),
+				link.id
+			)));
+		};
+		(link.from_id, link.to_id) = (*from_id, *to_id);
+	}
+	Ok(())
+}
+
+/// Gives components and requirements dense IDs so a record's ID is its position in its table,
+/// or past the archived IDs once records were purged, rewriting every reference to them. The old
+/// to new IDs are written to "id-map-<time>.tsv".
+fn renumber_all(ctx: &mut AppCtx) -> Result<()> {
+	let component_ids = dense_ids(
+		&ctx.project_root,
+		&ctx.config.tables.component,
+		ctx.components.iter().map(|c| c.get_id()),
+	)?;
+	let requirement_ids = dense_ids(
+		&ctx.project_root,
+		&ctx.config.tables.requirement,
+		ctx.requirements.iter().map(|r| r.get_id()),
+	)?;
+	if is_identity(&component_ids) && is_identity(&requirement_ids) {
+		println!("IDs are already dense, nothing to re-number");
+		return Ok(());
+	}
+	let pending = pending_files(&ctx.project_root)?;
+	if !pending.is_empty() {
+		return Err(anyhow!(format!(
+			"{} insert or delete these drafts and edit files first: {}",
+			err_loc!(),
+			pending.join(", ")
+		)));
+	}
+
+	// re-number copies so nothing changes if a reference is broken
+	let mut components = ctx.components.clone();
+	for component in components.iter_mut() {
+		component.renumber(&component_ids)?;
+	}
+	let mut requirements = ctx.requirements.clone();
+	for requirement in requirements.iter_mut() {
+		requirement.renumber(&requirement_ids, &component_ids)?;
+	}
+	let mut links = ctx.links.clone();
+	renumber_links(&mut links, &requirement_ids)?;
+	let mut history = ctx.history.clone();
+	for revision in history.iter_mut() {
+		revision.renumber(&requirement_ids)?;
+	}
+	let mut test_cases = ctx.test_cases.clone();
+	for test_case in test_cases.iter_mut() {
+		test_case.renumber(&requirement_ids)?;
+	}
+	components.sort();
+	requirements.sort();
+
+	let changed = |ids: &IdMap| ids.iter().filter(|(old, new)| old != new).count();
+	let confirmed = inquire::Confirm::new(&format!(
+		"Re-number {} components and {} requirements?",
+		changed(&component_ids),
+		changed(&requirement_ids)
+	))
+	.with_default(false)
+	.with_help_message("documents that cite the old IDs will need updating from the ID map")
+	.prompt()
+	.map_err(|iqe| anyhow!(iqe).context(formatcp!("{} error prompting to confirm", err_loc!())))?;
+	if !confirmed {
+		return Ok(());
+	}
+
+	let mapping = ctx
+		.components
+		.iter()
+		.map(|c| IdMapping {
+			table: "component",
+			old_id: c.get_id(),
+			new_id: component_ids[&c.get_id()],
+			key: c.get_key(),
+		})
+		.chain(ctx.requirements.iter().map(|r| IdMapping {
+			table: "requirement",
+			old_id: r.get_id(),
+			new_id: requirement_ids[&r.get_id()],
+			key: r.get_key(),
+		}));
+	let mapping =
+		serialize_table(mapping).context(formatcp!("{} can't serialize ID map", err_loc!()))?;
+	let map_path = ctx.project_root.join(format!(
+		"id-map-{}.tsv",
+		Local::now().format("%Y%m%dT%H%M%S")
+	));
+	let map_file = std::fs::OpenOptions::new()
+		.write(true)
+		.create_new(true)
+		.open(&map_path)
+		.with_context(|| format!("{} can't create ID map at: {:?}", err_loc!(), &map_path))?;
+	write_flush_sync(crate::WriteFlushSync::Done(map_file), &mapping)
+		.with_context(|| format!("{} can't write ID map at: {:?}", err_loc!(), &map_path))?;
+
+	ctx.components = components;
+	ctx.requirements = requirements;
+	if let Some(git) = ctx.git.as_mut() {
+		git.start_renumbering(&map_path)?;
+	}
+	let written = ctx
+		.write_components()
+		.and_then(|()| ctx.write_requirements());
+	if let Some(git) = ctx.git.as_mut() {
+		git.end_renumbering();
+	}
+	written?;
+	if ctx.links != links {
+		ctx.links = links;
+		ctx.write_links()?;
+	}
+	if ctx.history != history {
+		ctx.history = history;
+		ctx.write_history()?;
+	}
+	if ctx.test_cases != test_cases {
+		ctx.test_cases = test_cases;
+		ctx.write_test_cases()?;
+	}
+	println!(
+		"Re-numbered IDs, the old to new IDs are in: {:?}",
+		&map_path
+	);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+	use reqtsv_lib::{LinkKind, RecordStatus};
+
+	use super::*;
+
+	#[test]
+	fn test_dense_ids() {
+		let ids = dense_ids_from([7, 0, 3].into_iter(), 0);
+		assert_eq!(IdMap::from([(0, 0), (3, 1), (7, 2)]), ids);
+		assert!(!is_identity(&ids));
+		assert!(is_identity(&dense_ids_from([2, 0, 1].into_iter(), 0)));
+		let ids = dense_ids_from([7, 0, 3].into_iter(), 5);
+		assert_eq!(IdMap::from([(0, 5), (3, 6), (7, 7)]), ids);
+		assert!(is_identity(&IdMap::new()));
+	}
+
+	#[test]
+	fn test_renumber_links() {
+		let link = |id, from_id, to_id| Link {
+			id,
+			from_id,
+			kind: LinkKind::Refines,
+			to_id,
+			creation_date: Local.with_ymd_and_hms(2025, 5, 27, 12, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+		};
+		let requirement_ids = IdMap::from([(0, 0), (3, 1), (7, 2)]);
+		let mut links = vec![link(0, 7, 3), link(1, 0, 7)];
+		renumber_links(&mut links, &requirement_ids).unwrap();
+		assert_eq!(vec![link(0, 2, 1), link(1, 0, 2)], links);
+
+		// a link to a requirement that isn't in the table
+		let mut links = vec![link(5, 0, 4)];
+		assert!(renumber_links(&mut links, &requirement_ids).is_err());
+	}
+
+	#[test]
+	fn test_pending_files() {
+		let root =
+			std::env::temp_dir().join(format!("reqtsv-pending-files-test-{}", std::process::id()));
+		if root.exists() {
+			std::fs::remove_dir_all(&root).unwrap();
+		}
+		let nested = root.join("drafts").join("old");
+		std::fs::create_dir_all(&nested).unwrap();
+		for path in [
+			root.join(format!("{REQUIREMENT_EDIT_PREFIX}1.toml")),
+			root.join("drafts")
+				.join(format!("{COMPONENT_DRAFT_PREFIX}a.toml")),
+			root.join("drafts")
+				.join(format!("{TEST_CASE_EDIT_PREFIX}2.md")),
+			// deeper than the menus look
+			nested.join(format!("{REQUIREMENT_DRAFT_PREFIX}b.toml")),
+			root.join("notes.toml"),
+		] {
+			std::fs::write(path, "").unwrap();
+		}
+		let pending = pending_files(&root).unwrap();
+		assert_eq!(
+			vec![
+				Path::new("drafts")
+					.join(format!("{COMPONENT_DRAFT_PREFIX}a.toml"))
+					.to_string_lossy()
+					.into_owned(),
+				format!("{REQUIREMENT_EDIT_PREFIX}1.toml"),
+			],
+			pending
+		);
+		std::fs::remove_dir_all(&root).unwrap();
+	}
 }
@@ -0,0 +1,529 @@
+//! Moving deleted records out of the tables in to archive tables, see
+//! [`reqtsv_lib::config::archive_table_name`].
+
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Local, NaiveDate};
+use const_format::formatcp;
+use reqtsv_lib::{
+	Link, TestResult,
+	config::{archive_table_name, new_table_name},
+	custom_field::append_custom_columns,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	AppCtx, RecordStatus, RecordType, component::Component, err_loc, parse_table,
+	requirement::Requirement, requirement::RequirementRevision, serialize_table,
+	test_case::TestCase,
+};
+
+/// What a purge takes out of each table.
+#[derive(Debug, Default)]
+struct Purge {
+	components: Vec<Component>,
+	requirements: Vec<Requirement>,
+	links: Vec<Link>,
+	history: Vec<RequirementRevision>,
+	test_cases: Vec<TestCase>,
+	test_results: Vec<TestResult>,
+}
+
+impl Purge {
+	fn is_empty(&self) -> bool {
+		self.components.is_empty()
+			&& self.requirements.is_empty()
+			&& self.links.is_empty()
+			&& self.history.is_empty()
+			&& self.test_cases.is_empty()
+			&& self.test_results.is_empty()
+	}
+
+	/// e.g. "2 components, 3 links"
+	fn summary(&self) -> String {
+		[
+			(self.components.len(), "components"),
+			(self.requirements.len(), "requirements"),
+			(self.links.len(), "links"),
+			(self.history.len(), "revisions"),
+			(self.test_cases.len(), "test cases"),
+			(self.test_results.len(), "test results"),
+		]
+		.into_iter()
+		.filter(|(count, _)| *count > 0)
+		.map(|(count, table)| format!("{count} {table}"))
+		.collect::<Vec<String>>()
+		.join(", ")
+	}
+}
+
+/// The live tables a [`Purge`] is split from.
+struct Live<'a> {
+	components: &'a [Component],
+	requirements: &'a [Requirement],
+	links: &'a [Link],
+	history: &'a [RequirementRevision],
+	test_cases: &'a [TestCase],
+	test_results: &'a [TestResult],
+}
+
+impl<'a> From<&'a AppCtx> for Live<'a> {
+	fn from(ctx: &'a AppCtx) -> Self {
+		Self {
+			components: &ctx.components,
+			requirements: &ctx.requirements,
+			links: &ctx.links,
+			history: &ctx.history,
+			test_cases: &ctx.test_cases,
+			test_results: &ctx.test_results,
+		}
+	}
+}
+
+/// Splits the deleted records, created before `cutoff` when there is one, from the tables along
+/// with the links, history and test results of them. Errors if a record that stays refers to a
+/// purged one.
+fn split_purge(live: Live, cutoff: Option<NaiveDate>) -> Result<Purge> {
+	let is_purged = |status: RecordStatus, created: DateTime<Local>| {
+		status == RecordStatus::Deleted && cutoff.is_none_or(|c| created.date_naive() < c)
+	};
+	let mut purge = Purge {
+		components: live
+			.components
+			.iter()
+			.filter(|c| is_purged(c.status(), c.creation_date()))
+			.cloned()
+			.collect(),
+		requirements: live
+			.requirements
+			.iter()
+			.filter(|r| is_purged(r.get_status(), r.creation_date()))
+			.cloned()
+			.collect(),
+		test_cases: live
+			.test_cases
+			.iter()
+			.filter(|tc| is_purged(tc.get_status(), tc.creation_date()))
+			.cloned()
+			.collect(),
+		..Default::default()
+	};
+	let component_ids: Vec<u64> = purge.components.iter().map(|c| c.get_id()).collect();
+	let requirement_ids: Vec<u64> = purge.requirements.iter().map(|r| r.get_id()).collect();
+	let test_case_ids: Vec<u64> = purge.test_cases.iter().map(|tc| tc.get_id()).collect();
+
+	let mut refused = Vec::new();
+	for component in live
+		.components
+		.iter()
+		.filter(|c| !component_ids.contains(&c.get_id()))
+	{
+		if let Some(parent_id) = component
+			.parent_id()
+			.filter(|id| component_ids.contains(id))
+		{
+			refused.push(format!(
+				"component ID: {parent_id} is the parent of component: {}",
+				component.name()
+			));
+		}
+	}
+	for requirement in live
+		.requirements
+		.iter()
+		.filter(|r| !requirement_ids.contains(&r.get_id()))
+	{
+		if component_ids.contains(&requirement.component_id()) {
+			refused.push(format!(
+				"component ID: {} is used by requirement: {}",
+				requirement.component_id(),
+				requirement.get_key()
+			));
+		}
+		if let Some(parent_id) = requirement
+			.parent_id()
+			.filter(|id| requirement_ids.contains(id))
+		{
+			refused.push(format!(
+				"requirement ID: {parent_id} is the parent of requirement: {}",
+				requirement.get_key()
+			));
+		}
+	}
+	for test_case in live
+		.test_cases
+		.iter()
+		.filter(|tc| !test_case_ids.contains(&tc.get_id()))
+	{
+		for id in test_case
+			.requirement_ids()
+			.iter()
+			.filter(|id| requirement_ids.contains(id))
+		{
+			refused.push(format!(
+				"requirement ID: {id} is verified by test case: {}",
+				test_case.get_key()
+			));
+		}
+	}
+	if !refused.is_empty() {
+		return Err(anyhow!(format!(
+			"{} can't purge records still in use: {}",
+			err_loc!(),
+			refused.join("; ")
+		)));
+	}
+
+	purge.links = live
+		.links
+		.iter()
+		.filter(|l| {
+			is_purged(l.status, l.creation_date)
+				|| requirement_ids.contains(&l.from_id)
+				|| requirement_ids.contains(&l.to_id)
+		})
+		.cloned()
+		.collect();
+	purge.history = live
+		.history
+		.iter()
+		.filter(|rev| requirement_ids.contains(&rev.requirement_id()))
+		.cloned()
+		.collect();
+	purge.test_results = live
+		.test_results
+		.iter()
+		.filter(|tr| test_case_ids.contains(&tr.test_case_id))
+		.cloned()
+		.collect();
+	Ok(purge)
+}
+
+/// The ID after the highest of `live_ids` and the IDs in the archive of `table_name`. A purged
+/// record's ID isn't handed out again, its archived history and results would be the new one's.
+pub fn next_id(
+	project_root: &Path,
+	table_name: &str,
+	live_ids: impl Iterator<Item = u64>,
+) -> Result<u64> {
+	#[derive(Deserialize)]
+	struct Archived {
+		id: u64,
+	}
+	let archive_name = archive_table_name(table_name);
+	// the new archive has what this session already purged
+	let archive_path = [new_table_name(&archive_name), archive_name]
+		.into_iter()
+		.map(|name| project_root.join(name))
+		.find(|path| path.exists());
+	let archived: Vec<Archived> = match archive_path {
+		None => Vec::new(),
+		Some(path) => {
+			let raw = std::fs::read_to_string(&path)
+				.with_context(|| format!("{} can't read archive: {:?}", err_loc!(), &path))?;
+			parse_table(&raw)
+				.with_context(|| format!("{} can't parse archive: {:?}", err_loc!(), &path))?
+		}
+	};
+	Ok(live_ids
+		.chain(archived.iter().map(|a| a.id))
+		.max()
+		.map(|id| id + 1)
+		.unwrap_or(0))
+}
+
+fn append_records<S: Serialize>(ctx: &mut AppCtx, table_name: &str, records: &[S]) -> Result<()> {
+	if records.is_empty() {
+		return Ok(());
+	}
+	let table = serialize_table(records.iter())
+		.with_context(|| format!("{} can't serialize archive of: {table_name}", err_loc!()))?;
+	ctx.append_archive(table_name, &table)
+}
+
+/// Moves deleted records to the archive tables, the live tables then only hold what is in use.
+/// Records don't keep when they were deleted so the optional cutoff is on their creation date.
+pub fn purge_deleted(ctx: &mut AppCtx) -> Result<()> {
+	let cutoff = inquire::DateSelect::new("Only purge deleted records created before:")
+		.with_help_message("Esc to purge every deleted record")
+		.prompt_skippable()
+		.context(formatcp!("{} can't prompt for purge cutoff", err_loc!()))?;
+	let purge = split_purge(Live::from(&*ctx), cutoff)?;
+	if purge.is_empty() {
+		println!("No deleted records to purge");
+		return Ok(());
+	}
+
+	let confirmed =
+		inquire::Confirm::new(&format!("Move {} to the archive tables?", purge.summary()))
+			.with_default(false)
+			.with_help_message("purged records are no longer listed or built, this can't be undone")
+			.prompt()
+			.context(formatcp!("{} can't prompt to confirm purge", err_loc!()))?;
+	if !confirmed {
+		return Ok(());
+	}
+
+	let tables = ctx.config.tables.clone();
+	append_records(ctx, &tables.component, &purge.components)?;
+	if !purge.requirements.is_empty() {
+		let table = serialize_table(purge.requirements.iter()).context(formatcp!(
+			"{} can't serialize requirement archive",
+			err_loc!()
+		))?;
+		let custom: Vec<_> = purge.requirements.iter().map(|r| r.custom()).collect();
+		let table = append_custom_columns(&table, &ctx.config.fields, &custom).context(
+			formatcp!("{} can't add custom fields to archive", err_loc!()),
+		)?;
+		ctx.append_archive(&tables.requirement, &table)?;
+	}
+	append_records(ctx, &tables.link, &purge.links)?;
+	append_records(ctx, &tables.history, &purge.history)?;
+	append_records(ctx, &tables.test_case, &purge.test_cases)?;
+	append_records(ctx, &tables.test_result, &purge.test_results)?;
+
+	if !purge.components.is_empty() {
+		ctx.components.retain(|c| !purge.components.contains(c));
+		ctx.write_components()?;
+	}
+	if !purge.requirements.is_empty() {
+		ctx.requirements.retain(|r| !purge.requirements.contains(r));
+		ctx.write_requirements()?;
+	}
+	if !purge.links.is_empty() {
+		ctx.links.retain(|l| !purge.links.contains(l));
+		ctx.write_links()?;
+	}
+	if !purge.history.is_empty() {
+		ctx.history.retain(|rev| !purge.history.contains(rev));
+		ctx.write_history()?;
+	}
+	if !purge.test_cases.is_empty() {
+		ctx.test_cases.retain(|tc| !purge.test_cases.contains(tc));
+		ctx.write_test_cases()?;
+	}
+	if !purge.test_results.is_empty() {
+		ctx.test_results
+			.retain(|tr| !purge.test_results.contains(tr));
+		ctx.write_test_results()?;
+	}
+	println!("Purged {}", purge.summary());
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use reqtsv_lib::{COMPONENT_HEADER, LINK_HEADER, REQUIREMENT_HEADER, TEST_CASE_HEADER};
+
+	use super::*;
+	use crate::{
+		parse_table,
+		project::{IdMap, dense_ids},
+	};
+
+	const DATE: &str = "2025-05-27T12:00:00-04:00";
+
+	fn components(rows: &[(u64, Option<u64>, &str)]) -> Vec<Component> {
+		let mut table = COMPONENT_HEADER.to_string();
+		for (id, parent_id, status) in rows {
+			let parent_id = parent_id.map(|p| p.to_string()).unwrap_or_default();
+			table.push_str(&format!(
+				"{id}\tCOMP{id}\t1\t{parent_id}\tComponent {id}\t\t{DATE}\t{status}\tAuthor A\t\n"
+			));
+		}
+		parse_table(&table).unwrap()
+	}
+
+	fn requirements(rows: &[(u64, u64, Option<u64>, &str)]) -> Vec<Requirement> {
+		let mut table = REQUIREMENT_HEADER.to_string();
+		for (id, component_id, parent_id, status) in rows {
+			let parent_id = parent_id.map(|p| p.to_string()).unwrap_or_default();
+			table.push_str(&format!(
+				"{id}\tCOMP-{id:04}\t{component_id}\t{parent_id}\tRequirement {id}\tFunctional\t{DATE}\tThing shall do {id}\tDoes {id}\tTest\t0\tAuthor A\tMandated\t{status}\t\t\t\n"
+			));
+		}
+		parse_table(&table).unwrap()
+	}
+
+	fn test_cases(rows: &[(u64, &str, &str)]) -> Vec<TestCase> {
+		let mut table = TEST_CASE_HEADER.to_string();
+		for (id, requirement_ids, status) in rows {
+			table.push_str(&format!(
+				"{id}\tTC-{id:04}\tTest {id}\t{requirement_ids}\tRun {id}\t{DATE}\t{status}\tAuthor A\n"
+			));
+		}
+		parse_table(&table).unwrap()
+	}
+
+	fn links(rows: &[(u64, u64, u64)]) -> Vec<Link> {
+		let mut table = LINK_HEADER.to_string();
+		for (id, from_id, to_id) in rows {
+			table.push_str(&format!(
+				"{id}\t{from_id}\trefines\t{to_id}\t{DATE}\tAccepted\n"
+			));
+		}
+		parse_table(&table).unwrap()
+	}
+
+	fn split(
+		components: &[Component],
+		requirements: &[Requirement],
+		links: &[Link],
+		test_cases: &[TestCase],
+	) -> Result<Purge> {
+		split_purge(
+			Live {
+				components,
+				requirements,
+				links,
+				history: &[],
+				test_cases,
+				test_results: &[],
+			},
+			None,
+		)
+	}
+
+	#[test]
+	fn test_next_id() {
+		let root = std::env::temp_dir().join(format!("reqtsv-next-id-test-{}", std::process::id()));
+		if root.exists() {
+			std::fs::remove_dir_all(&root).unwrap();
+		}
+		std::fs::create_dir_all(&root).unwrap();
+		let table_name = "component.tsv";
+		assert_eq!(0, next_id(&root, table_name, [].into_iter()).unwrap());
+		assert_eq!(3, next_id(&root, table_name, [2, 0].into_iter()).unwrap());
+
+		// the highest ID was purged
+		let archive = root.join(archive_table_name(table_name));
+		let mut table = COMPONENT_HEADER.to_string();
+		table.push_str(&format!(
+			"5\tCOMP5\t1\t\tComponent 5\t\t{DATE}\tDeleted\tAuthor A\t\n"
+		));
+		std::fs::write(&archive, &table).unwrap();
+		assert_eq!(6, next_id(&root, table_name, [2, 0].into_iter()).unwrap());
+		assert_eq!(8, next_id(&root, table_name, [7].into_iter()).unwrap());
+
+		// and another this session
+		table.push_str(&format!(
+			"6\tCOMP6\t1\t\tComponent 6\t\t{DATE}\tDeleted\tAuthor A\t\n"
+		));
+		std::fs::write(
+			root.join(new_table_name(&archive_table_name(table_name))),
+			&table,
+		)
+		.unwrap();
+		assert_eq!(7, next_id(&root, table_name, [2, 0].into_iter()).unwrap());
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn test_split_purge() {
+		let comps = components(&[(0, None, "Accepted"), (1, None, "Deleted")]);
+		let reqs = requirements(&[
+			(0, 0, None, "Accepted"),
+			(1, 0, None, "Deleted"),
+			(2, 0, None, "Accepted"),
+		]);
+		let tcs = test_cases(&[(0, "0", "Accepted"), (1, "1", "Deleted")]);
+		let lnks = links(&[(0, 0, 2), (1, 1, 2)]);
+		let purge = split(&comps, &reqs, &lnks, &tcs).unwrap();
+		assert_eq!(
+			vec![1],
+			purge
+				.components
+				.iter()
+				.map(|c| c.get_id())
+				.collect::<Vec<_>>()
+		);
+		assert_eq!(
+			vec![1],
+			purge
+				.requirements
+				.iter()
+				.map(|r| r.get_id())
+				.collect::<Vec<_>>()
+		);
+		// the links of a purged requirement go with it
+		assert_eq!(
+			vec![1],
+			purge.links.iter().map(|l| l.id).collect::<Vec<_>>()
+		);
+		assert_eq!(
+			"1 components, 1 requirements, 1 links, 1 test cases",
+			purge.summary()
+		);
+
+		// nothing deleted
+		let comps = components(&[(0, None, "Accepted")]);
+		assert!(split(&comps, &[], &[], &[]).unwrap().is_empty());
+	}
+
+	#[test]
+	fn test_purge_then_renumber() {
+		let root =
+			std::env::temp_dir().join(format!("reqtsv-purge-renumber-test-{}", std::process::id()));
+		if root.exists() {
+			std::fs::remove_dir_all(&root).unwrap();
+		}
+		std::fs::create_dir_all(&root).unwrap();
+		let table_name = "component.tsv";
+		let comps = components(&[
+			(0, None, "Accepted"),
+			(3, None, "Deleted"),
+			(4, None, "Accepted"),
+			(7, None, "Accepted"),
+		]);
+		let purge = split(&comps, &[], &[], &[]).unwrap();
+		let archive = serialize_table(purge.components.iter()).unwrap();
+		std::fs::write(root.join(archive_table_name(table_name)), archive).unwrap();
+
+		// the live records are re-numbered past the purged one
+		let live = comps.iter().filter(|c| !purge.components.contains(c));
+		let ids = dense_ids(&root, table_name, live.map(|c| c.get_id())).unwrap();
+		assert_eq!(IdMap::from([(0, 4), (4, 5), (7, 6)]), ids);
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn test_split_purge_refused() {
+		let refused = |purge: Result<Purge>| purge.unwrap_err().to_string();
+
+		// a requirement of a purged component
+		let comps = components(&[(0, None, "Deleted")]);
+		let reqs = requirements(&[(0, 0, None, "Accepted")]);
+		assert!(
+			refused(split(&comps, &reqs, &[], &[]))
+				.contains("component ID: 0 is used by requirement: COMP-0000")
+		);
+
+		// a child of a purged component
+		let comps = components(&[(0, None, "Deleted"), (1, Some(0), "Accepted")]);
+		assert!(
+			refused(split(&comps, &[], &[], &[]))
+				.contains("component ID: 0 is the parent of component: Component 1")
+		);
+
+		// a child of a purged requirement
+		let comps = components(&[(0, None, "Accepted")]);
+		let reqs = requirements(&[(0, 0, None, "Deleted"), (1, 0, Some(0), "Accepted")]);
+		assert!(
+			refused(split(&comps, &reqs, &[], &[]))
+				.contains("requirement ID: 0 is the parent of requirement: COMP-0001")
+		);
+
+		// a test case verifying a purged requirement
+		let reqs = requirements(&[(0, 0, None, "Deleted")]);
+		let tcs = test_cases(&[(0, "0", "Accepted")]);
+		assert!(
+			refused(split(&comps, &reqs, &[], &tcs))
+				.contains("requirement ID: 0 is verified by test case: TC-0000")
+		);
+
+		// purged together is fine
+		let comps = components(&[(0, None, "Deleted"), (1, Some(0), "Deleted")]);
+		assert_eq!(2, split(&comps, &[], &[], &[]).unwrap().components.len());
+	}
+}
@@ -5,6 +5,9 @@ use anyhow::anyhow;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local};
 use const_format::formatcp;
+use reqtsv_lib::config::ProjectConfig;
+use reqtsv_lib::custom_field::{CustomFields, FieldKind};
+use reqtsv_lib::{Tags, creates_cycle};
 use serde::Deserialize;
 use serde::Serialize;
 use strum::{Display, IntoEnumIterator};
@@ -12,15 +15,18 @@ use strum_macros::EnumIter;
 use strum_macros::EnumString;
 
 use crate::component::Component;
+use crate::project::IdMap;
+use crate::purge::next_id;
 use crate::{
 	AppCtx, EditFile, RecordStatus, RecordType,
 	select_menu::{AfterRun, SelectMenu},
 	update_record,
 };
-use crate::{contains_any, err_loc};
+use crate::{check_tags, contains_any, err_loc, fmt_draft_author_field, fmt_tags_field};
 use crate::{create_draft_file, mut_record_by_id};
-use crate::{create_edit_file, prompt_for_record_id};
+use crate::{create_edit_file, find_id_by_id_or_key, prompt_for_record_id, ref_record_by_id};
 use crate::{delete_record, escape_normalize_nl};
+use crate::{link, list};
 
 pub const REQUIREMENT_TABLE_NAME: &'static str = "requirement.tsv";
 pub const REQUIREMENT_OLD_TABLE_NAME: &'static str = "requiremnt.old.tsv";
@@ -38,6 +44,27 @@ pub enum RequirementFunctional {
 	NonFunctional,
 }
 
+#[derive(
+	Debug,
+	Default,
+	Serialize,
+	Deserialize,
+	Copy,
+	Clone,
+	PartialEq,
+	Eq,
+	EnumString,
+	Display,
+	EnumIter,
+)]
+pub enum VerificationMethod {
+	#[default]
+	Test,
+	Analysis,
+	Inspection,
+	Demonstration,
+}
+
 #[derive(
 	Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Hash, Display, EnumIter,
 )]
@@ -48,19 +75,160 @@ pub enum RequirementPriority {
 	Low,
 }
 
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Requirement {
 	id: u64,
+	#[serde(default)]
+	key: String,
 	component_id: u64,
+	#[serde(default)]
+	parent_id: Option<u64>,
 	title: String,
 	functional: RequirementFunctional,
 	creation_date: DateTime<Local>,
 	requirement_text: String,
+	#[serde(default)]
+	acceptance_criteria: String,
+	#[serde(default)]
+	verification_method: VerificationMethod,
 	version: usize,
 	author: String,
 	priority: RequirementPriority,
 	status: RecordStatus,
+	#[serde(default)]
+	status_justification: String,
 	risks: String,
+	#[serde(default)]
+	tags: Tags,
+	/// Written as extra columns by [`AppCtx::write_requirements`].
+	#[serde(skip)]
+	custom: CustomFields,
+}
+
+/// A requirement as it was before an edit replaced it, see [`AppCtx::history`].
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RequirementRevision {
+	requirement_id: u64,
+	version: usize,
+	title: String,
+	functional: RequirementFunctional,
+	requirement_text: String,
+	acceptance_criteria: String,
+	verification_method: VerificationMethod,
+	author: String,
+	priority: RequirementPriority,
+	risks: String,
+	tags: Tags,
+	superseded_on: DateTime<Local>,
+}
+
+impl From<&Requirement> for RequirementRevision {
+	fn from(value: &Requirement) -> Self {
+		Self {
+			requirement_id: value.id,
+			version: value.version,
+			title: value.title.clone(),
+			functional: value.functional,
+			requirement_text: value.requirement_text.clone(),
+			acceptance_criteria: value.acceptance_criteria.clone(),
+			verification_method: value.verification_method,
+			author: value.author.clone(),
+			priority: value.priority,
+			risks: value.risks.clone(),
+			tags: value.tags.clone(),
+			superseded_on: Local::now(),
+		}
+	}
+}
+
+impl Display for RequirementRevision {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "version = {}", self.version)?;
+		writeln!(f, "superseded_on = \"{}\"", self.superseded_on)?;
+		writeln!(f, "title = \"{}\"", self.title)?;
+		writeln!(f, "functional = \"{}\"", self.functional)?;
+		writeln!(f, "requirement_text = \"{}\"", self.requirement_text)?;
+		writeln!(f, "acceptance_criteria = \"{}\"", self.acceptance_criteria)?;
+		writeln!(f, "verification_method = \"{}\"", self.verification_method)?;
+		writeln!(f, "author = \"{}\"", self.author)?;
+		writeln!(f, "priority = \"{}\"", self.priority)?;
+		writeln!(f, "risks = \"{}\"", self.risks)?;
+		writeln!(f, "tags = \"{}\"", self.tags)
+	}
+}
+
+impl Requirement {
+	pub fn title(&self) -> &str {
+		&self.title
+	}
+
+	pub fn component_id(&self) -> u64 {
+		self.component_id
+	}
+
+	pub fn parent_id(&self) -> Option<u64> {
+		self.parent_id
+	}
+
+	pub fn creation_date(&self) -> DateTime<Local> {
+		self.creation_date
+	}
+
+	pub fn author(&self) -> &str {
+		&self.author
+	}
+
+	pub fn custom(&self) -> &CustomFields {
+		&self.custom
+	}
+
+	pub fn set_custom(&mut self, custom: CustomFields) {
+		self.custom = custom
+	}
+
+	/// Moves the requirement to its new ID and points it at the new IDs of what it refers to.
+	pub(crate) fn renumber(
+		&mut self,
+		requirement_ids: &IdMap,
+		component_ids: &IdMap,
+	) -> Result<()> {
+		self.component_id = *component_ids.get(&self.component_id).ok_or_else(|| {
+			anyhow!(format!(
+				"{} requirement: {} refers to missing component ID: {}",
+				err_loc!(),
+				self.get_key(),
+				self.component_id
+			))
+		})?;
+		if let Some(parent_id) = self.parent_id {
+			self.parent_id = Some(*requirement_ids.get(&parent_id).ok_or_else(|| {
+				anyhow!(format!(
+					"{} requirement: {} refers to missing parent ID: {parent_id}",
+					err_loc!(),
+					self.get_key()
+				))
+			})?);
+		}
+		self.id = requirement_ids[&self.id];
+		Ok(())
+	}
+}
+
+impl RequirementRevision {
+	pub fn requirement_id(&self) -> u64 {
+		self.requirement_id
+	}
+
+	pub(crate) fn renumber(&mut self, requirement_ids: &IdMap) -> Result<()> {
+		self.requirement_id = *requirement_ids.get(&self.requirement_id).ok_or_else(|| {
+			anyhow!(format!(
+				"{} history of missing requirement ID: {}",
+				err_loc!(),
+				self.requirement_id
+			))
+		})?;
+		Ok(())
+	}
 }
 
 impl RecordType for Requirement {
@@ -70,6 +238,14 @@ impl RecordType for Requirement {
 		self.id
 	}
 
+	fn get_key(&self) -> Cow<'_, str> {
+		if self.key.is_empty() {
+			Cow::Owned(self.id.to_string())
+		} else {
+			Cow::Borrowed(&self.key)
+		}
+	}
+
 	fn get_tbl_mut(app_ctx: &mut AppCtx) -> &mut Vec<Self> {
 		&mut app_ctx.requirements
 	}
@@ -83,17 +259,51 @@ impl RecordType for Requirement {
 	}
 
 	fn set_accepted(&mut self) {
-		self.status = RecordStatus::Accepted
+		// an edit only revives a deleted requirement, the lifecycle is moved with "Change Status"
+		if matches!(self.status, RecordStatus::Draft | RecordStatus::Deleted) {
+			self.status = RecordStatus::Proposed
+		}
+	}
+
+	fn archive(app_ctx: &mut AppCtx, previous: Self) -> Result<()> {
+		let revision = RequirementRevision::from(&previous);
+		if app_ctx
+			.history
+			.iter()
+			.any(|r| r.requirement_id == revision.requirement_id && r.version == revision.version)
+		{
+			return Err(anyhow!(format!(
+				"{} history already holds version: {} of ID: {}",
+				err_loc!(),
+				revision.version,
+				previous.id
+			)));
+		}
+		app_ctx.history.push(revision);
+		app_ctx.write_history()
 	}
 
 	fn update_from_edit(&mut self, ef: Self::EditFile) {
 		self.functional = ef.functional;
 		self.title = ef.title;
 		self.requirement_text = ef.requirement_text;
+		self.acceptance_criteria = ef.acceptance_criteria;
+		self.verification_method = ef.verification_method;
 		self.version = self.version + 1;
 		self.author = ef.author;
 		self.priority = ef.priority;
 		self.risks = ef.risks;
+		self.tags = Tags::from(ef.tags);
+		// only fields in the edit file change, values of fields dropped from the config are kept
+		for (name, value) in ef.custom {
+			if let toml::Value::String(value) = value {
+				if value.is_empty() {
+					self.custom.0.remove(&name);
+				} else {
+					self.custom.0.insert(name, value);
+				}
+			}
+		}
 	}
 
 	fn check_for_conflict(&self, rhs: &Self::EditFile) -> Result<()> {
@@ -122,7 +332,11 @@ impl RecordType for Requirement {
 		self.status
 	}
 
-	fn fmt_as_edit(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
+	fn fmt_as_edit(
+		&self,
+		f: &mut impl std::fmt::Write,
+		config: &ProjectConfig,
+	) -> std::fmt::Result {
 		writeln!(
 			f,
 			"# Do not include any tab characters in the document\n# Do not include any new-lines in the title field"
@@ -145,15 +359,42 @@ impl RecordType for Requirement {
 		)?;
 		write!(f, "requirement_text = \"")?;
 		if self.requirement_text.contains("\\n") {
-			write!(f, "\"\"")?;
-			for line in self.requirement_text.split("\\n") {
-				writeln!(f, "{line}")?;
-			}
-			write!(f, "\"\"")?;
+			// a new-line before the closing quotes would be read back as part of the text
+			write!(f, "\"\"{}\"\"", self.requirement_text.replace("\\n", "\n"))?;
 		} else {
 			write!(f, "{}", self.requirement_text)?;
 		}
 		writeln!(f, "\"\n")?;
+		writeln!(
+			f,
+			"# If writing on multiple lines use triple quotes (e.g. \"\"\"stuff\"\"\")"
+		)?;
+		write!(f, "acceptance_criteria = \"")?;
+		if self.acceptance_criteria.contains("\\n") {
+			write!(
+				f,
+				"\"\"{}\"\"",
+				self.acceptance_criteria.replace("\\n", "\n")
+			)?;
+		} else {
+			write!(f, "{}", self.acceptance_criteria)?;
+		}
+		writeln!(f, "\"\n")?;
+		write!(f, "# Write only: ")?;
+		let mut vm_iter = VerificationMethod::iter().peekable();
+		while let Some(var) = vm_iter.next() {
+			write!(f, "\"{var}\"")?;
+			if vm_iter.peek().is_some() {
+				write!(f, ", ")?;
+			} else {
+				writeln!(f)?;
+			}
+		}
+		writeln!(
+			f,
+			"verification_method = \"{}\"\n",
+			self.verification_method
+		)?;
 		writeln!(f, "# Do not include any new-lines in the author field")?;
 		writeln!(f, "author = \"{}\"\n", self.author)?;
 		write!(f, "# Write only: ")?;
@@ -173,22 +414,24 @@ impl RecordType for Requirement {
 		)?;
 		write!(f, "risks = \"")?;
 		if self.risks.contains("\\n") {
-			write!(f, "\"\"")?;
-			for line in self.risks.split("\\n") {
-				writeln!(f, "{line}")?;
-			}
-			write!(f, "\"\"")?;
+			write!(f, "\"\"{}\"\"", self.risks.replace("\\n", "\n"))?;
 		} else {
 			write!(f, "{}", self.risks)?;
 		}
-		writeln!(f, "\"")
+		writeln!(f, "\"\n")?;
+		fmt_tags_field(f, &self.tags.0)?;
+		fmt_custom_fields(f, config, Some(&self.custom))
 	}
 }
 
 impl Display for Requirement {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		writeln!(f, "id = {}", self.id)?;
+		writeln!(f, "key = \"{}\"", self.key)?;
 		writeln!(f, "component_id = {}", self.component_id)?;
+		if let Some(parent_id) = self.parent_id {
+			writeln!(f, "parent_id = {parent_id}")?;
+		}
 		writeln!(f, "title = \"{}\"", self.title)?;
 		writeln!(f, "functtional = \"{}\"", self.functional)?;
 		writeln!(f, "creation_date = \"{}\"", self.creation_date)?;
@@ -203,10 +446,29 @@ impl Display for Requirement {
 			write!(f, "{}", self.requirement_text)?;
 			writeln!(f, "\"")?;
 		}
+		write!(f, "acceptance_criteria = \"")?;
+		if self.acceptance_criteria.contains("\\n") {
+			write!(f, "\"\"")?;
+			for line in self.acceptance_criteria.split("\\n") {
+				writeln!(f, "{line}")?;
+			}
+			writeln!(f, "\"\"")?;
+		} else {
+			write!(f, "{}", self.acceptance_criteria)?;
+			writeln!(f, "\"")?;
+		}
+		writeln!(f, "verification_method = \"{}\"", self.verification_method)?;
 		writeln!(f, "version = \"{}\"", self.version)?;
 		writeln!(f, "author = \"{}\"", self.author)?;
 		writeln!(f, "priority = \"{}\"", self.priority)?;
 		writeln!(f, "status = \"{}\"", self.status)?;
+		if !self.status_justification.is_empty() {
+			writeln!(
+				f,
+				"status_justification = \"{}\"",
+				self.status_justification
+			)?;
+		}
 		write!(f, "risks = \"")?;
 		if self.risks.contains("\\n") {
 			write!(f, "\"\"")?;
@@ -217,7 +479,12 @@ impl Display for Requirement {
 		} else {
 			write!(f, "{}", self.risks)?;
 		}
-		writeln!(f, "\"")
+		writeln!(f, "\"")?;
+		writeln!(f, "tags = \"{}\"", self.tags)?;
+		for (name, value) in self.custom.0.iter() {
+			writeln!(f, "{name} = \"{value}\"")?;
+		}
+		Ok(())
 	}
 }
 
@@ -233,15 +500,21 @@ impl PartialOrd for Requirement {
 	}
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, PartialEq)]
 pub struct RequirementEdit {
 	functional: RequirementFunctional,
 	title: String,
 	requirement_text: String,
+	acceptance_criteria: String,
+	verification_method: VerificationMethod,
 	author: String,
 	priority: RequirementPriority,
-	status: RecordStatus,
 	risks: String,
+	#[serde(default)]
+	tags: Vec<String>,
+	/// Values of the fields declared in the project config.
+	#[serde(default)]
+	custom: toml::Table,
 }
 
 impl Default for RequirementEdit {
@@ -250,16 +523,41 @@ impl Default for RequirementEdit {
 			functional: RequirementFunctional::Functional,
 			title: "write title here".into(),
 			requirement_text: "write requirement here".into(),
+			acceptance_criteria: "write acceptance criteria here".into(),
+			verification_method: VerificationMethod::Test,
 			author: "write author name here".into(),
 			priority: RequirementPriority::Mandated,
-			status: RecordStatus::Draft,
 			risks: "write risks here".into(),
+			tags: Vec::new(),
+			custom: toml::Table::new(),
+		}
+	}
+}
+
+impl From<&Requirement> for RequirementEdit {
+	fn from(value: &Requirement) -> Self {
+		Self {
+			functional: value.functional,
+			title: value.title.clone(),
+			requirement_text: value.requirement_text.clone(),
+			acceptance_criteria: value.acceptance_criteria.clone(),
+			verification_method: value.verification_method,
+			author: value.author.clone(),
+			priority: value.priority,
+			risks: value.risks.clone(),
+			tags: value.tags.0.clone(),
+			custom: value
+				.custom
+				.0
+				.iter()
+				.map(|(name, value)| (name.clone(), toml::Value::String(value.clone())))
+				.collect(),
 		}
 	}
 }
 
 impl EditFile for RequirementEdit {
-	fn sanitize(mut self) -> Result<Self> {
+	fn sanitize(mut self, config: &ProjectConfig) -> Result<Self> {
 		if self.risks.contains('\t') {
 			return Err(anyhow!(formatcp!(
 				"{} risks contains one or more tab characters",
@@ -272,6 +570,12 @@ impl EditFile for RequirementEdit {
 				err_loc!()
 			)));
 		}
+		if self.acceptance_criteria.contains('\t') {
+			return Err(anyhow!(formatcp!(
+				"{} acceptance criteria contains one or more tab characters",
+				err_loc!()
+			)));
+		}
 		if contains_any(&['\n', '\t', '\r'], &self.author) {
 			return Err(anyhow!(formatcp!(
 				"{} author contains one or more tab or new line characters",
@@ -284,8 +588,29 @@ impl EditFile for RequirementEdit {
 				err_loc!()
 			)));
 		}
+		check_tags(&self.tags)?;
+		if let Some(unknown) = self
+			.custom
+			.keys()
+			.find(|name| !config.fields.iter().any(|def| &def.name == *name))
+		{
+			return Err(anyhow!(format!(
+				"{} {unknown} is not a custom field of this project",
+				err_loc!()
+			)));
+		}
+		for def in config.fields.iter() {
+			let value = def
+				.normalize(self.custom.get(&def.name))
+				.context(formatcp!("{} bad custom field", err_loc!()))?;
+			self.custom
+				.insert(def.name.clone(), toml::Value::String(value));
+		}
 		if let Cow::Owned(o) = escape_normalize_nl(&self.requirement_text) {
-			self.risks = o
+			self.requirement_text = o
+		}
+		if let Cow::Owned(o) = escape_normalize_nl(&self.acceptance_criteria) {
+			self.acceptance_criteria = o
 		}
 		if let Cow::Owned(o) = escape_normalize_nl(&self.risks) {
 			self.risks = o
@@ -293,7 +618,7 @@ impl EditFile for RequirementEdit {
 		Ok(self)
 	}
 
-	fn fmt_as_draft(f: &mut impl std::fmt::Write) -> std::fmt::Result {
+	fn fmt_as_draft(f: &mut impl std::fmt::Write, config: &ProjectConfig) -> std::fmt::Result {
 		writeln!(
 			f,
 			"# Do not include any tab characters in the document\n# Do not include any new-lines in the title field"
@@ -318,8 +643,27 @@ impl EditFile for RequirementEdit {
 			f,
 			"requirement_text = \"\"\"write requirement here\nuse more than one line if you want\"\"\"\n"
 		)?;
-		writeln!(f, "# Do not include any new-lines in the author field")?;
-		writeln!(f, "author = \"type author name here\"\n")?;
+		writeln!(
+			f,
+			"# If writing on multiple lines use triple quotes (e.g. \"\"\"stuff\"\"\")"
+		)?;
+		writeln!(
+			f,
+			"acceptance_criteria = \"\"\"write how to tell the requirement is met\nuse more than one line if you want\"\"\"\n"
+		)?;
+		write!(f, "# Write only: ")?;
+		let mut vm_iter = VerificationMethod::iter().peekable();
+		while let Some(var) = vm_iter.next() {
+			write!(f, "\"{var}\"")?;
+			if vm_iter.peek().is_some() {
+				write!(f, ", ")?;
+			} else {
+				writeln!(f)?;
+			}
+		}
+		writeln!(f, "verification_method = \"\"\n")?;
+		fmt_draft_author_field(f, config, "type author name here")?;
+		writeln!(f)?;
 		write!(f, "# Write only: ")?;
 		let mut rp_iter = RequirementPriority::iter().peekable();
 		while let Some(var) = rp_iter.next() {
@@ -338,22 +682,36 @@ impl EditFile for RequirementEdit {
 		writeln!(
 			f,
 			"risks = \"\"\"write risks here\nuse more than one line if you want\"\"\"\n"
-		)
+		)?;
+		fmt_tags_field(f, &[])?;
+		fmt_custom_fields(f, config, None)
 	}
 }
 
 #[derive(Debug, Copy, Clone, strum_macros::Display, EnumIter, EnumString)]
 pub enum RequirementMenu {
+	#[strum(serialize = "List")]
+	List,
 	#[strum(serialize = "Create New Draft")]
 	NewDraft,
 	#[strum(serialize = "Insert & Accept Draft")]
 	Insert,
 	#[strum(serialize = "Change Component")]
 	ChangeComponent,
+	#[strum(serialize = "Change Status")]
+	ChangeStatus,
+	#[strum(serialize = "Set Parent")]
+	SetParent,
+	#[strum(serialize = "Add Link")]
+	AddLink,
+	#[strum(serialize = "Remove Link")]
+	RemoveLink,
 	#[strum(serialize = "Create an Edit File")]
 	Edit,
 	#[strum(serialize = "Update Using an Edit File")]
 	Update,
+	#[strum(serialize = "View History")]
+	History,
 	#[strum(serialize = "Delete")]
 	Delete,
 	#[strum(serialize = "Back to Main Menu")]
@@ -367,13 +725,19 @@ impl SelectMenu for RequirementMenu {
 
 	fn run(&mut self, ctx: &mut AppCtx) -> Result<()> {
 		match self {
+			RequirementMenu::List => list::list_requirements(ctx),
 			RequirementMenu::NewDraft => {
 				create_draft_file::<RequirementEdit>(ctx, &REQUIREMENT_DRAFT_PREFIX)
 			}
 			RequirementMenu::Insert => insert_requirement_draft(ctx, &REQUIREMENT_DRAFT_PREFIX),
 			RequirementMenu::Edit => create_edit_file::<Requirement>(ctx, &REQUIREMENT_EDIT_PREFIX),
 			RequirementMenu::ChangeComponent => change_component(ctx),
+			RequirementMenu::ChangeStatus => change_status(ctx),
+			RequirementMenu::SetParent => set_parent(ctx),
+			RequirementMenu::AddLink => link::add_link(ctx),
+			RequirementMenu::RemoveLink => link::remove_link(ctx),
 			RequirementMenu::Update => update_record::<Requirement>(ctx, &REQUIREMENT_EDIT_PREFIX),
+			RequirementMenu::History => view_history(ctx),
 			RequirementMenu::Delete => delete_record::<Requirement>(ctx),
 			RequirementMenu::Back => Ok(()),
 		}
@@ -381,11 +745,17 @@ impl SelectMenu for RequirementMenu {
 
 	fn purpose(&self) -> &'static str {
 		match self {
+			RequirementMenu::List => "list requirements",
 			RequirementMenu::NewDraft => "create requirement draft",
 			RequirementMenu::Insert => "insert requirement",
 			RequirementMenu::ChangeComponent => "change component",
+			RequirementMenu::ChangeStatus => "change status",
+			RequirementMenu::SetParent => "set parent",
+			RequirementMenu::AddLink => "add link",
+			RequirementMenu::RemoveLink => "remove link",
 			RequirementMenu::Edit => "edit requirement",
 			RequirementMenu::Update => "update requirement",
+			RequirementMenu::History => "view history",
 			RequirementMenu::Delete => "delete requirement",
 			RequirementMenu::Back => "",
 		}
@@ -399,48 +769,56 @@ impl SelectMenu for RequirementMenu {
 	}
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct ComponentChose<'c> {
 	id: u64,
-	name: &'c str,
+	prefix: &'c str,
+	/// e.g. "Vehicle / Powertrain / Inverter"
+	path: String,
 }
 
 impl<'c> Display for ComponentChose<'c> {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "{} - {}", self.id, self.name)
+		if self.prefix.is_empty() {
+			write!(f, "{} - {}", self.id, self.path)
+		} else {
+			write!(f, "{} - {}", self.prefix, self.path)
+		}
 	}
 }
 
-impl<'c> From<&'c Component> for ComponentChose<'c> {
-	fn from(value: &'c Component) -> Self {
+impl<'c> ComponentChose<'c> {
+	fn new(component: &'c Component, components: &[Component]) -> Self {
 		Self {
-			id: value.get_id(),
-			name: value.name(),
+			id: component.get_id(),
+			prefix: component.prefix(),
+			path: component.path(components),
 		}
 	}
+
+	/// Accepted components ordered by path so sub-components follow their parent.
+	fn list(components: &'c [Component]) -> Vec<Self> {
+		let mut chose: Vec<Self> = components
+			.iter()
+			.filter(|c| matches!(c.status(), RecordStatus::Accepted))
+			.map(|c| Self::new(c, components))
+			.collect();
+		chose.sort_by(|a, b| a.path.cmp(&b.path));
+		chose
+	}
 }
 
 fn change_component(ctx: &mut AppCtx) -> Result<()> {
 	// pick requirement
-	let req_id = match prompt_for_record_id()
+	let req_id = match prompt_for_record_id::<Requirement>(ctx)
 		.with_context(|| formatcp!("{} can't prompt for requirement ID", err_loc!()))?
 	{
 		Some(id) => id,
 		None => return Ok(()),
 	};
 
-	// get table for record type
-	let requirements = &mut ctx.requirements;
-	let requirement = mut_record_by_id(requirements, req_id)
-		.context(formatcp!("{} can't find record", err_loc!()))?;
-
 	// pick component
-	let components: Vec<ComponentChose> = ctx
-		.components
-		.iter()
-		.filter(|c| matches!(c.status(), RecordStatus::Accepted))
-		.map(|c| ComponentChose::from(c))
-		.collect();
+	let components = ComponentChose::list(&ctx.components);
 	if components.is_empty() {
 		return Err(anyhow!(formatcp!("{} there are no components", err_loc!())));
 	}
@@ -453,18 +831,184 @@ fn change_component(ctx: &mut AppCtx) -> Result<()> {
 		None => return Ok(()),
 	};
 
+	set_component(ctx, req_id, component_id)
+}
+
+/// Moves a requirement to an accepted component, it keeps its key.
+pub fn set_component(ctx: &mut AppCtx, req_id: u64, component_id: u64) -> Result<()> {
+	check_accepted_component(ctx, component_id)?;
+	let requirement = mut_record_by_id(&mut ctx.requirements, req_id)
+		.context(formatcp!("{} can't find record", err_loc!()))?;
 	requirement.component_id = component_id;
 	ctx.write_requirements()
 }
 
-fn insert_requirement_draft(ctx: &mut AppCtx, draft_prefix: &'static str) -> Result<()> {
-	// pick component
-	let components: Vec<ComponentChose> = ctx
-		.components
+/// Requirements can only be put in accepted components, see [`ComponentChose::list`].
+fn check_accepted_component(ctx: &AppCtx, component_id: u64) -> Result<()> {
+	let component = ref_record_by_id::<Component>(ctx, component_id)
+		.ok_or_else(|| anyhow!(format!("{} no component at ID: {component_id}", err_loc!())))?;
+	if component.status() != RecordStatus::Accepted {
+		return Err(anyhow!(format!(
+			"{} component: {} is {}, requirements need an accepted component",
+			err_loc!(),
+			component.get_key(),
+			component.status()
+		)));
+	}
+	Ok(())
+}
+
+/// Prints every prior version of a requirement, oldest first, then the current one.
+fn view_history(ctx: &mut AppCtx) -> Result<()> {
+	let req_id = match prompt_for_record_id::<Requirement>(ctx)
+		.context(formatcp!("{} can't prompt for requirement ID", err_loc!()))?
+	{
+		Some(id) => id,
+		None => return Ok(()),
+	};
+	let requirement = ref_record_by_id::<Requirement>(ctx, req_id)
+		.context(formatcp!("{} can't find record", err_loc!()))?;
+
+	let mut revisions: Vec<&RequirementRevision> = ctx
+		.history
 		.iter()
-		.filter(|c| matches!(c.status(), RecordStatus::Accepted))
-		.map(|c| ComponentChose::from(c))
+		.filter(|r| r.requirement_id == req_id)
 		.collect();
+	revisions.sort_by_key(|r| r.version);
+	if revisions.is_empty() {
+		println!("{} has never been edited", requirement.get_key());
+	}
+	for revision in revisions {
+		println!("{revision}");
+	}
+	println!("# current\n{requirement}");
+	Ok(())
+}
+
+fn change_status(ctx: &mut AppCtx) -> Result<()> {
+	let req_id = match prompt_for_record_id::<Requirement>(ctx)
+		.context(formatcp!("{} can't prompt for requirement ID", err_loc!()))?
+	{
+		Some(id) => id,
+		None => return Ok(()),
+	};
+	let requirement = ref_record_by_id::<Requirement>(ctx, req_id)
+		.context(formatcp!("{} can't find record", err_loc!()))?;
+	let current = requirement.status;
+
+	let next = current.requirement_transitions();
+	if next.is_empty() {
+		return Err(anyhow!(format!(
+			"{} a requirement can't move out of status: {current}",
+			err_loc!()
+		)));
+	}
+	let status = match inquire::Select::new(
+		&format!(
+			"{} is {current}, what is the new status?",
+			requirement.get_key()
+		),
+		next.to_vec(),
+	)
+	.prompt_skippable()
+	.context(formatcp!("{} can't prompt for status", err_loc!()))?
+	{
+		Some(status) => status,
+		None => return Ok(()),
+	};
+
+	let justification = match inquire::Text::new("Why is the status changing?")
+		.prompt_skippable()
+		.context(formatcp!("{} can't prompt for justification", err_loc!()))?
+	{
+		Some(j) => j,
+		None => return Ok(()),
+	};
+	set_status(ctx, req_id, status, &justification)
+}
+
+/// Moves a requirement along its lifecycle, see [`RecordStatus::requirement_transitions`].
+pub fn set_status(
+	ctx: &mut AppCtx,
+	req_id: u64,
+	status: RecordStatus,
+	justification: &str,
+) -> Result<()> {
+	let requirement = mut_record_by_id(&mut ctx.requirements, req_id)
+		.context(formatcp!("{} can't find record", err_loc!()))?;
+	let current = requirement.status;
+	if !current.can_transition_to(status) {
+		return Err(anyhow!(format!(
+			"{} {} can't move from status: {current} to: {status}",
+			err_loc!(),
+			requirement.get_key()
+		)));
+	}
+	let justification = justification.trim();
+	if justification.is_empty() {
+		return Err(anyhow!(formatcp!(
+			"{} a status change needs a justification",
+			err_loc!()
+		)));
+	}
+	if contains_any(&['\n', '\t', '\r'], justification) {
+		return Err(anyhow!(formatcp!(
+			"{} justification contains one or more tab or new line characters",
+			err_loc!()
+		)));
+	}
+
+	requirement.status = status;
+	requirement.status_justification = justification.to_string();
+	println!("{} is now {status}", requirement.get_key());
+	ctx.write_requirements()
+}
+
+fn set_parent(ctx: &mut AppCtx) -> Result<()> {
+	// pick requirement
+	let req_id = match prompt_for_record_id::<Requirement>(ctx)
+		.with_context(|| formatcp!("{} can't prompt for requirement ID", err_loc!()))?
+	{
+		Some(id) => id,
+		None => return Ok(()),
+	};
+
+	// pick parent, empty removes it
+	let ans = inquire::Text::new("What is the ID or key of the parent requirement?")
+		.with_help_message("Leave empty to remove the current parent")
+		.prompt_skippable()
+		.context(formatcp!("{} can't prompt for parent ID", err_loc!()))?;
+	let parent_id = match ans.as_deref().map(str::trim) {
+		None => return Ok(()),
+		Some("") => None,
+		Some(id_or_key) => Some(find_id_by_id_or_key::<Requirement>(ctx, id_or_key)?),
+	};
+
+	if let Some(parent_id) = parent_id {
+		let requirements = &ctx.requirements;
+		let parent_of = |id: u64| {
+			requirements
+				.iter()
+				.find(|r| r.id == id)
+				.and_then(|r| r.parent_id)
+		};
+		if creates_cycle(req_id, parent_id, parent_of) {
+			return Err(anyhow!(format!(
+				"{} requirement at ID: {parent_id} is already a descendant of ID: {req_id}",
+				err_loc!()
+			)));
+		}
+	}
+
+	let requirement = mut_record_by_id(&mut ctx.requirements, req_id)
+		.context(formatcp!("{} can't find record", err_loc!()))?;
+	requirement.parent_id = parent_id;
+	ctx.write_requirements()
+}
+
+fn insert_requirement_draft(ctx: &mut AppCtx, draft_prefix: &'static str) -> Result<()> {
+	// pick component
+	let components = ComponentChose::list(&ctx.components);
 	if components.is_empty() {
 		return Err(anyhow!(formatcp!("{} there are no components", err_loc!())));
 	}
@@ -487,7 +1031,22 @@ fn insert_requirement_draft(ctx: &mut AppCtx, draft_prefix: &'static str) -> Res
 		None => return Ok(()),
 	};
 
-	let draft_file = crate::open_edit_file::<RequirementEdit>(&draft_file_entry)?;
+	let draft_file = crate::open_edit_file::<RequirementEdit>(&draft_file_entry, &ctx.config)?;
+	insert_requirement(ctx, component_id, draft_file).map(|_id| ())
+}
+
+/// Inserts a proposed requirement in to an accepted component and returns its ID.
+pub fn insert_requirement(
+	ctx: &mut AppCtx,
+	component_id: u64,
+	draft_file: RequirementEdit,
+) -> Result<u64> {
+	check_accepted_component(ctx, component_id)?;
+	let id = next_id(
+		&ctx.project_root,
+		&ctx.config.tables.requirement,
+		ctx.requirements.iter().map(|r| r.id),
+	)?;
 
 	// get table for record type
 	let records: &mut Vec<Requirement> = Requirement::get_tbl_mut(ctx);
@@ -502,25 +1061,179 @@ fn insert_requirement_draft(ctx: &mut AppCtx, draft_prefix: &'static str) -> Res
 		return Err(e);
 	}
 
-	// use max() here because Ord is based on the ID
-	let id = records.iter().max().map(|c| c.get_id()).unwrap_or(0);
+	// the component hands out the key so it must be saved too
+	let key = crate::mut_record_by_id(&mut ctx.components, component_id)
+		.context(formatcp!("{} can't find component", err_loc!()))?
+		.allocate_requirement_key()
+		.context(formatcp!("{} can't allocate requirement key", err_loc!()))?;
 
 	let requirement = Requirement {
 		id,
+		key,
 		component_id,
+		parent_id: None,
 		title: draft_file.title,
 		functional: draft_file.functional,
 		creation_date: Local::now(),
 		requirement_text: draft_file.requirement_text,
+		acceptance_criteria: draft_file.acceptance_criteria,
+		verification_method: draft_file.verification_method,
 		version: 0,
 		author: draft_file.author,
 		priority: draft_file.priority,
-		status: RecordStatus::Accepted,
+		status: RecordStatus::Proposed,
+		status_justification: String::new(),
 		risks: draft_file.risks,
+		tags: Tags::from(draft_file.tags),
+		custom: CustomFields(
+			draft_file
+				.custom
+				.into_iter()
+				.filter_map(|(name, value)| match value {
+					toml::Value::String(value) if !value.is_empty() => Some((name, value)),
+					_ => None,
+				})
+				.collect(),
+		),
 	};
 
 	// insert into requirement table...
-	records.push(requirement);
-	println!("Inserted new requirement at ID: {id}");
-	ctx.write_requirements()
+	println!("Inserted new requirement: {} at ID: {id}", requirement.key);
+	ctx.requirements.push(requirement);
+	ctx.write_components()?;
+	ctx.write_requirements()?;
+	Ok(id)
+}
+
+/// Writes the `[custom]` table of a draft or edit file, `values` is `None` for a new draft.
+fn fmt_custom_fields(
+	f: &mut impl std::fmt::Write,
+	config: &ProjectConfig,
+	values: Option<&CustomFields>,
+) -> std::fmt::Result {
+	if config.fields.is_empty() {
+		return Ok(());
+	}
+	writeln!(f, "\n[custom]")?;
+	for def in config.fields.iter() {
+		let required = if def.required { ", required" } else { "" };
+		writeln!(f, "# {}: {}{required}", def.label(), def.kind)?;
+		let value = values.map(|v| v.get(&def.name)).unwrap_or("");
+		let value = match def.kind {
+			FieldKind::Integer if !value.is_empty() => value.to_string(),
+			FieldKind::Text => toml::Value::String(value.replace("\\n", "\n")).to_string(),
+			_ => toml::Value::String(value.to_string()).to_string(),
+		};
+		writeln!(f, "{} = {value}", def.name)?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+
+	use super::*;
+
+	fn requirement(
+		acceptance_criteria: &str,
+		verification_method: VerificationMethod,
+	) -> Requirement {
+		Requirement {
+			id: 0,
+			key: "COMPA-0001".into(),
+			component_id: 0,
+			parent_id: None,
+			title: "Requirement A".into(),
+			functional: RequirementFunctional::Functional,
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			requirement_text: "Thing shall do A".into(),
+			acceptance_criteria: acceptance_criteria.into(),
+			verification_method,
+			version: 0,
+			author: "Author A".into(),
+			priority: RequirementPriority::Mandated,
+			status: RecordStatus::Accepted,
+			status_justification: String::new(),
+			risks: "Risk A".into(),
+			tags: Tags::default(),
+			custom: CustomFields::default(),
+		}
+	}
+
+	fn round_trip(requirement: &Requirement) -> RequirementEdit {
+		let config = ProjectConfig::default();
+		let mut edit_file = String::new();
+		requirement.fmt_as_edit(&mut edit_file, &config).unwrap();
+		let edit: RequirementEdit = toml::from_str(&edit_file).unwrap();
+		edit.sanitize(&config).unwrap()
+	}
+
+	#[test]
+	fn test_edit_round_trip() {
+		let one_line = requirement("Show it does A", VerificationMethod::Analysis);
+		let edit = round_trip(&one_line);
+		assert_eq!("Show it does A", edit.acceptance_criteria);
+		assert_eq!(VerificationMethod::Analysis, edit.verification_method);
+		assert!(RequirementEdit::from(&one_line) == edit);
+
+		// stored escaped, written out and read back over several lines
+		let multi_line = requirement(
+			"Show it does A\\nand nothing else",
+			VerificationMethod::Demonstration,
+		);
+		let edit = round_trip(&multi_line);
+		assert_eq!(
+			"Show it does A\\nand nothing else",
+			edit.acceptance_criteria
+		);
+		assert_eq!(VerificationMethod::Demonstration, edit.verification_method);
+		assert!(RequirementEdit::from(&multi_line) == edit);
+	}
+
+	#[test]
+	fn test_sanitize_acceptance_criteria() {
+		let config = ProjectConfig::default();
+		let edit = RequirementEdit {
+			acceptance_criteria: "Show it does A\r\nand B\nand C".into(),
+			..RequirementEdit::default()
+		};
+		let edit = edit.sanitize(&config).unwrap();
+		assert_eq!("Show it does A\\nand B\\nand C", edit.acceptance_criteria);
+
+		let edit = RequirementEdit {
+			acceptance_criteria: "Show\tit".into(),
+			..RequirementEdit::default()
+		};
+		assert!(edit.sanitize(&config).is_err());
+
+		// an unknown method is refused when the edit is read
+		let edit_file = "title = \"A\"\nfunctional = \"Functional\"\nrequirement_text = \"A\"\nacceptance_criteria = \"A\"\nverification_method = \"Guess\"\nauthor = \"A\"\npriority = \"Mandated\"\nrisks = \"A\"\n";
+		assert!(toml::from_str::<RequirementEdit>(edit_file).is_err());
+		let edit_file = edit_file.replace("Guess", "Inspection");
+		let edit: RequirementEdit = toml::from_str(&edit_file).unwrap();
+		assert_eq!(VerificationMethod::Inspection, edit.verification_method);
+	}
+
+	#[test]
+	fn test_renumber() {
+		let mut req = requirement("Does A", VerificationMethod::Test);
+		(req.id, req.component_id, req.parent_id) = (7, 3, Some(2));
+		let mut revision = RequirementRevision::from(&req);
+		let requirement_ids = IdMap::from([(2, 0), (7, 1)]);
+		let component_ids = IdMap::from([(3, 0)]);
+
+		req.renumber(&requirement_ids, &component_ids).unwrap();
+		assert_eq!((1, 0, Some(0)), (req.id, req.component_id, req.parent_id));
+		revision.renumber(&requirement_ids).unwrap();
+		assert_eq!(1, revision.requirement_id);
+
+		// a parent that isn't in the table
+		(req.id, req.component_id, req.parent_id) = (7, 3, Some(5));
+		assert!(req.renumber(&requirement_ids, &component_ids).is_err());
+
+		// history of a requirement that isn't in the table
+		revision.requirement_id = 9;
+		assert!(revision.renumber(&requirement_ids).is_err());
+	}
 }
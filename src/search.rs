@@ -0,0 +1,62 @@
+//! Searches the text of requirements and components from the main menu, see
+//! [`reqtsv_lib::search`].
+
+use anyhow::{Context, Result};
+use const_format::formatcp;
+use inline_colorization::*;
+use reqtsv_lib::{
+	RecordStatus,
+	search::{Hit, HitKind, SearchIndex},
+};
+
+use crate::{AppCtx, err_loc};
+
+/// Results shown in the menu, the best ones.
+const MENU_HITS: usize = 20;
+
+pub fn search_menu(ctx: &mut AppCtx) -> Result<()> {
+	let query = match inquire::Text::new("What are you looking for?")
+		.with_help_message("Words in titles, text, risks, and component names and descriptions")
+		.prompt_skippable()
+		.context(formatcp!("{} can't prompt for search", err_loc!()))?
+	{
+		Some(query) => query,
+		None => return Ok(()),
+	};
+	let (components, requirements) = ctx.lib_records()?;
+	let hits = SearchIndex::new(&components, &requirements).search(&query);
+	print_hits(&hits, MENU_HITS, false);
+	Ok(())
+}
+
+/// Prints up to `limit` hits with the matched words highlighted, deleted records are left out
+/// unless `deleted`.
+pub fn print_hits(hits: &[Hit], limit: usize, deleted: bool) {
+	let hits: Vec<&Hit> = hits
+		.iter()
+		.filter(|hit| deleted || hit.status != RecordStatus::Deleted)
+		.collect();
+	if hits.is_empty() {
+		println!("No matches");
+		return;
+	}
+	for hit in hits.iter().take(limit) {
+		let kind = match hit.kind {
+			HitKind::Component => "component",
+			HitKind::Requirement => "requirement",
+		};
+		println!(
+			"{color_cyan}{}{color_reset}\t{}\t{}\t{kind}",
+			hit.key, hit.status, hit.heading
+		);
+		println!(
+			"\t{}: {}",
+			hit.snippet.field,
+			hit.snippet
+				.highlighted(formatcp!("{style_bold}{color_yellow}"), style_reset)
+		);
+	}
+	if hits.len() > limit {
+		println!("{} more matches", hits.len() - limit);
+	}
+}
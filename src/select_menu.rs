@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-use crate::{AppCtx, err_loc};
+use crate::{
+	AppCtx, err_loc,
+	undo::{OperationLog, Snapshot, record_operation},
+};
 use anyhow::{Context, Result};
 use inline_colorization::*;
 use inquire::InquireError;
@@ -11,6 +14,11 @@ pub trait SelectMenu: Sized + Display + std::str::FromStr {
 	fn after(&self) -> AfterRun;
 	/// the error message will say "Can't {purpose} due to {error}"
 	fn purpose(&self) -> &'static str;
+	/// `false` for operations that change files outside the tables, running one clears the undo
+	/// and redo logs
+	fn undoable(&self) -> bool {
+		true
+	}
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,7 +53,15 @@ where
 
 		let purpose = operation.purpose();
 
-		if let Err(e) = operation.run(&mut ctx) {
+		let before = Snapshot::take(ctx);
+		let res = operation.run(&mut ctx);
+		// logged even on error, a failed operation may have changed some records
+		if operation.undoable() {
+			record_operation(ctx, purpose, before);
+		} else if before != Snapshot::take(ctx) {
+			ctx.operations = OperationLog::default();
+		}
+		if let Err(e) = res {
 			match e.downcast_ref::<InquireError>() {
 				Some(_) => return Err(e),
 				None => {
@@ -0,0 +1,543 @@
+use std::{borrow::Cow, fmt::Display};
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Local};
+use const_format::formatcp;
+use reqtsv_lib::{
+	IdList, TestOutcome, TestResult, config::ProjectConfig, format_requirement_key,
+	format_test_case_key, latest_result,
+};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use strum_macros::EnumString;
+
+use crate::{
+	AppCtx, EditFile, RecordStatus, RecordType, contains_any, create_draft_file, create_edit_file,
+	delete_record, err_loc, escape_normalize_nl, file_list_chose, find_id_by_id_or_key,
+	fmt_draft_author_field, open_edit_file,
+	project::IdMap,
+	prompt_for_record_id,
+	purge::next_id,
+	ref_record_by_id,
+	requirement::Requirement,
+	select_menu::{AfterRun, SelectMenu},
+	update_record,
+};
+
+pub use reqtsv_lib::{
+	TEST_CASE_DRAFT_PREFIX, TEST_CASE_EDIT_PREFIX, TEST_CASE_HEADER, TEST_CASE_NEW_TABLE_NAME,
+	TEST_CASE_TABLE_NAME, TEST_RESULT_HEADER, TEST_RESULT_NEW_TABLE_NAME, TEST_RESULT_TABLE_NAME,
+};
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct TestCaseEdit {
+	title: String,
+	/// Keys (e.g. "PWR-0001") or IDs of the requirements this test case verifies.
+	requirements: Vec<String>,
+	procedure: String,
+	author: String,
+	/// `requirements` resolved against the requirement table, see [`RecordType::resolve_edit`].
+	#[serde(skip)]
+	requirement_ids: Vec<u64>,
+}
+
+impl Default for TestCaseEdit {
+	fn default() -> Self {
+		Self {
+			title: "write test case title".into(),
+			requirements: Vec::new(),
+			procedure: "write procedure".into(),
+			author: "author name or initials here".into(),
+			requirement_ids: Vec::new(),
+		}
+	}
+}
+
+impl EditFile for TestCaseEdit {
+	fn sanitize(mut self, _config: &ProjectConfig) -> Result<Self> {
+		if contains_any(&['\n', '\r', '\t'], self.title.as_str()) {
+			return Err(anyhow!(formatcp!(
+				"{} title contains one or more non-space whitespace characters",
+				err_loc!()
+			)));
+		}
+		if self.requirements.is_empty() {
+			return Err(anyhow!(formatcp!(
+				"{} a test case must verify at least one requirement",
+				err_loc!()
+			)));
+		}
+		if self.procedure.contains('\t') {
+			return Err(anyhow!(formatcp!(
+				"{} procedure contains one or more tab characters",
+				err_loc!()
+			)));
+		}
+		if contains_any(&['\n', '\r', '\t'], self.author.as_str()) {
+			return Err(anyhow!(formatcp!(
+				"{} author contains one or more non-space whitespace characters",
+				err_loc!()
+			)));
+		}
+		if let Cow::Owned(o) = escape_normalize_nl(&self.procedure) {
+			self.procedure = o
+		}
+		Ok(self)
+	}
+
+	fn fmt_as_draft(f: &mut impl std::fmt::Write, config: &ProjectConfig) -> std::fmt::Result {
+		writeln!(
+			f,
+			"# Do not include any tab characters in the document\n# Do not include any new-lines in the title field"
+		)?;
+		writeln!(f, "title = \"type title here\"\n")?;
+		writeln!(
+			f,
+			"# Keys (e.g. \"PWR-0001\") or IDs of the requirements this test case verifies"
+		)?;
+		writeln!(f, "requirements = []\n")?;
+		writeln!(
+			f,
+			"# If writing on multiple lines use triple quotes (e.g. \"\"\"stuff\"\"\")"
+		)?;
+		writeln!(
+			f,
+			"procedure = \"\"\"write the steps and expected results here\nuse more than one line if you want\"\"\"\n"
+		)?;
+		fmt_draft_author_field(f, config, "author name here")
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct TestCase {
+	id: u64,
+	/// Empty for test cases made before keys were stored.
+	#[serde(default)]
+	key: String,
+	title: String,
+	requirement_ids: IdList,
+	procedure: String,
+	creation_date: DateTime<Local>,
+	status: RecordStatus,
+	author: String,
+}
+
+impl RecordType for TestCase {
+	type EditFile = TestCaseEdit;
+
+	fn get_id(&self) -> u64 {
+		self.id
+	}
+
+	fn get_key(&self) -> Cow<'_, str> {
+		if self.key.is_empty() {
+			Cow::Owned(format_test_case_key(self.id))
+		} else {
+			Cow::Borrowed(&self.key)
+		}
+	}
+
+	fn get_tbl_mut(app_ctx: &mut AppCtx) -> &mut Vec<Self> {
+		&mut app_ctx.test_cases
+	}
+
+	fn get_tbl(app_ctx: &AppCtx) -> &Vec<Self> {
+		&app_ctx.test_cases
+	}
+
+	fn write_table(app_ctx: &mut AppCtx) -> Result<()> {
+		app_ctx.write_test_cases()
+	}
+
+	fn resolve_edit(app_ctx: &AppCtx, ef: &mut Self::EditFile) -> Result<()> {
+		let ids = ef
+			.requirements
+			.iter()
+			.map(|id_or_key| find_id_by_id_or_key::<Requirement>(app_ctx, id_or_key.trim()))
+			.collect::<Result<Vec<u64>>>()
+			.context(formatcp!(
+				"{} test case lists an unknown requirement",
+				err_loc!()
+			))?;
+		ef.requirement_ids = ids;
+		Ok(())
+	}
+
+	fn update_from_edit(&mut self, ef: Self::EditFile) {
+		self.title = ef.title;
+		self.requirement_ids = IdList::from(ef.requirement_ids);
+		self.procedure = ef.procedure;
+		self.author = ef.author;
+	}
+
+	fn set_accepted(&mut self) {
+		self.status = RecordStatus::Accepted
+	}
+
+	fn check_for_conflict(&self, rhs: &Self::EditFile) -> Result<()> {
+		if self.title == rhs.title {
+			Err(anyhow!(
+				"{} record with title: \"{}\"",
+				err_loc!(),
+				self.title
+			))
+		} else {
+			Ok(())
+		}
+	}
+
+	fn set_deleted(&mut self) {
+		self.status = RecordStatus::Deleted;
+	}
+
+	fn get_status(&self) -> RecordStatus {
+		self.status
+	}
+
+	fn fmt_as_edit(
+		&self,
+		f: &mut impl std::fmt::Write,
+		_config: &ProjectConfig,
+	) -> std::fmt::Result {
+		writeln!(
+			f,
+			"# Do not include any tab characters in the document\n# Do not include any new-lines in the title field"
+		)?;
+		writeln!(f, "title = \"{}\"\n", self.title)?;
+		writeln!(
+			f,
+			"# Keys (e.g. \"PWR-0001\") or IDs of the requirements this test case verifies"
+		)?;
+		let ids: Vec<String> = self
+			.requirement_ids
+			.iter()
+			.map(|id| id.to_string())
+			.collect();
+		writeln!(f, "requirements = {}\n", toml::Value::from(ids))?;
+		writeln!(
+			f,
+			"# If writing on multiple lines use triple quotes (e.g. \"\"\"stuff\"\"\")"
+		)?;
+		write!(f, "procedure = \"")?;
+		if self.procedure.contains("\\n") {
+			write!(f, "\"\"")?;
+			for line in self.procedure.split("\\n") {
+				writeln!(f, "{}", line)?;
+			}
+			write!(f, "\"\"")?;
+		} else {
+			write!(f, "{}", self.procedure)?;
+		}
+		writeln!(f, "\"\n")?;
+		writeln!(f, "# Do not include any new-lines in the author field")?;
+		writeln!(f, "author = \"{}\"", self.author)
+	}
+}
+
+impl TestCase {
+	pub fn requirement_ids(&self) -> &[u64] {
+		&self.requirement_ids.0
+	}
+
+	pub fn creation_date(&self) -> DateTime<Local> {
+		self.creation_date
+	}
+
+	/// Points the test case at the new IDs of its requirements.
+	pub(crate) fn renumber(&mut self, requirement_ids: &IdMap) -> Result<()> {
+		let mut renumbered = Vec::with_capacity(self.requirement_ids.0.len());
+		for id in self.requirement_ids.iter() {
+			renumbered.push(*requirement_ids.get(&id).ok_or_else(|| {
+				anyhow!(format!(
+					"{} test case: {} refers to missing requirement ID: {id}",
+					err_loc!(),
+					self.get_key()
+				))
+			})?);
+		}
+		self.requirement_ids = IdList::from(renumbered);
+		Ok(())
+	}
+}
+
+impl Ord for TestCase {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		self.id.cmp(&other.id)
+	}
+}
+
+impl PartialOrd for TestCase {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Display for TestCase {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "id = {}", self.id)?;
+		writeln!(f, "key = \"{}\"", self.get_key())?;
+		writeln!(f, "title = \"{}\"", self.title)?;
+		writeln!(f, "requirement_ids = \"{}\"", self.requirement_ids)?;
+		write!(f, "procedure = \"")?;
+		if self.procedure.contains("\\n") {
+			writeln!(f, "\"\"")?;
+			for line in self.procedure.split("\\n") {
+				writeln!(f, "{}", line)?;
+			}
+			writeln!(f, "\"\"")?;
+		} else {
+			write!(f, "{}", self.procedure)?;
+			writeln!(f, "\"")?;
+		}
+		writeln!(f, "creation date = \"{}\"", self.creation_date)?;
+		writeln!(f, "status = \"{}\"", self.status)?;
+		writeln!(f, "author = \"{}\"", self.author)
+	}
+}
+
+#[derive(Debug, Copy, Clone, strum_macros::Display, EnumIter, EnumString)]
+pub enum TestCaseMenu {
+	#[strum(serialize = "Create New Draft")]
+	NewDraft,
+	#[strum(serialize = "Insert & Accept Draft")]
+	Insert,
+	#[strum(serialize = "Record a Result")]
+	RecordResult,
+	#[strum(serialize = "View Results")]
+	ViewResults,
+	#[strum(serialize = "Create an Edit File")]
+	Edit,
+	#[strum(serialize = "Update Using an Edit File")]
+	Update,
+	#[strum(serialize = "Delete")]
+	Delete,
+	#[strum(serialize = "Back to Main Menu")]
+	Back,
+}
+
+impl SelectMenu for TestCaseMenu {
+	fn get_opts() -> Vec<Self> {
+		Self::iter().collect()
+	}
+
+	fn run(&mut self, ctx: &mut AppCtx) -> Result<()> {
+		match self {
+			TestCaseMenu::NewDraft => {
+				create_draft_file::<TestCaseEdit>(ctx, TEST_CASE_DRAFT_PREFIX)
+			}
+			TestCaseMenu::Insert => insert_test_case_draft(ctx),
+			TestCaseMenu::RecordResult => record_result(ctx),
+			TestCaseMenu::ViewResults => view_results(ctx),
+			TestCaseMenu::Edit => create_edit_file::<TestCase>(ctx, TEST_CASE_EDIT_PREFIX),
+			TestCaseMenu::Update => update_record::<TestCase>(ctx, TEST_CASE_EDIT_PREFIX),
+			TestCaseMenu::Delete => delete_record::<TestCase>(ctx),
+			TestCaseMenu::Back => Ok(()),
+		}
+	}
+
+	fn purpose(&self) -> &'static str {
+		match self {
+			TestCaseMenu::NewDraft => "create draft",
+			TestCaseMenu::Insert => "insert test case",
+			TestCaseMenu::RecordResult => "record result",
+			TestCaseMenu::ViewResults => "view results",
+			TestCaseMenu::Edit => "edit test case",
+			TestCaseMenu::Update => "update test case",
+			TestCaseMenu::Delete => "delete test case",
+			TestCaseMenu::Back => "",
+		}
+	}
+
+	fn after(&self) -> AfterRun {
+		match self {
+			TestCaseMenu::Back => AfterRun::GoBack,
+			_ => AfterRun::Continue,
+		}
+	}
+}
+
+fn insert_test_case_draft(ctx: &mut AppCtx) -> Result<()> {
+	// prompt with drafts as list opts
+	let draft_file_entry = match file_list_chose(ctx.as_ref(), |e| {
+		e.file_type().is_file()
+			&& e.file_name()
+				.to_str()
+				.is_some_and(|s| s.starts_with(TEST_CASE_DRAFT_PREFIX) && s.ends_with(".toml"))
+	})? {
+		Some(dfe) => dfe,
+		None => return Ok(()),
+	};
+
+	let mut draft = open_edit_file::<TestCaseEdit>(&draft_file_entry, &ctx.config)?;
+	TestCase::resolve_edit(ctx, &mut draft)?;
+
+	if let Some(e) = ctx
+		.test_cases
+		.iter()
+		.find_map(|tc| tc.check_for_conflict(&draft).err())
+	{
+		return Err(e);
+	}
+
+	let id = next_id(
+		&ctx.project_root,
+		&ctx.config.tables.test_case,
+		ctx.test_cases.iter().map(|tc| tc.id),
+	)?;
+	let test_case = TestCase {
+		id,
+		key: format_requirement_key(&ctx.config.id_prefixes.test_case, id + 1),
+		title: draft.title,
+		requirement_ids: IdList::from(draft.requirement_ids),
+		procedure: draft.procedure,
+		creation_date: Local::now(),
+		status: RecordStatus::Accepted,
+		author: draft.author,
+	};
+	println!("Inserted test case: {} at ID: {id}", test_case.get_key());
+	ctx.test_cases.push(test_case);
+	ctx.write_test_cases()
+}
+
+fn record_result(ctx: &mut AppCtx) -> Result<()> {
+	let test_case_id = match prompt_for_record_id::<TestCase>(ctx)
+		.context(formatcp!("{} can't prompt for test case ID", err_loc!()))?
+	{
+		Some(id) => id,
+		None => return Ok(()),
+	};
+	if ref_record_by_id::<TestCase>(ctx, test_case_id)
+		.is_some_and(|tc| tc.status == RecordStatus::Deleted)
+	{
+		return Err(anyhow!(format!(
+			"{} test case at ID: {test_case_id} is deleted",
+			err_loc!()
+		)));
+	}
+
+	let outcome = match inquire::Select::new("What was the outcome?", TestOutcome::iter().collect())
+		.prompt_skippable()
+		.context(formatcp!("{} can't prompt for outcome", err_loc!()))?
+	{
+		Some(outcome) => outcome,
+		None => return Ok(()),
+	};
+
+	let evidence = match inquire::Text::new("Where is the evidence?")
+		.with_help_message("e.g. a report path, log file, or CI run URL")
+		.prompt_skippable()
+		.context(formatcp!("{} can't prompt for evidence", err_loc!()))?
+	{
+		Some(evidence) => evidence,
+		None => return Ok(()),
+	};
+	if evidence.contains('\t') {
+		return Err(anyhow!(formatcp!(
+			"{} evidence contains one or more tab characters",
+			err_loc!()
+		)));
+	}
+
+	let author = match inquire::Text::new("Who ran it?")
+		.with_default(&ctx.config.default_author)
+		.prompt_skippable()
+		.context(formatcp!("{} can't prompt for author name", err_loc!()))?
+	{
+		Some(author) => author,
+		None => return Ok(()),
+	};
+	if author.contains('\t') {
+		return Err(anyhow!(formatcp!(
+			"{} author contains one or more tab characters",
+			err_loc!()
+		)));
+	}
+
+	let id = next_id(
+		&ctx.project_root,
+		&ctx.config.tables.test_result,
+		ctx.test_results.iter().map(|r| r.id),
+	)?;
+	let key = ref_record_by_id::<TestCase>(ctx, test_case_id)
+		.map(|tc| tc.get_key().into_owned())
+		.unwrap_or_else(|| format_test_case_key(test_case_id));
+	println!("Recorded {outcome} for {key} at ID: {id}");
+	ctx.test_results.push(TestResult {
+		id,
+		test_case_id,
+		outcome,
+		run_date: Local::now(),
+		evidence,
+		author,
+	});
+	ctx.write_test_results()
+}
+
+/// Prints a test case, its runs oldest first, and the requirements it verifies.
+fn view_results(ctx: &mut AppCtx) -> Result<()> {
+	let test_case_id = match prompt_for_record_id::<TestCase>(ctx)
+		.context(formatcp!("{} can't prompt for test case ID", err_loc!()))?
+	{
+		Some(id) => id,
+		None => return Ok(()),
+	};
+	let test_case = ref_record_by_id::<TestCase>(ctx, test_case_id)
+		.context(formatcp!("{} can't find record", err_loc!()))?;
+	println!("{test_case}");
+
+	let mut results: Vec<&TestResult> = ctx
+		.test_results
+		.iter()
+		.filter(|r| r.test_case_id == test_case_id)
+		.collect();
+	results.sort_by_key(|r| (r.run_date, r.id));
+	if results.is_empty() {
+		println!("{} has never been run", test_case.get_key());
+	}
+	for result in results {
+		println!(
+			"{} {} by {} evidence: {}",
+			result.run_date, result.outcome, result.author, result.evidence
+		);
+	}
+
+	let latest = latest_result(test_case_id, &ctx.test_results)
+		.map(|r| r.outcome.to_string())
+		.unwrap_or_else(|| "Not Run".into());
+	for requirement_id in test_case.requirement_ids.iter() {
+		let key = ref_record_by_id::<Requirement>(ctx, requirement_id)
+			.map(|r| r.get_key().into_owned())
+			.unwrap_or_else(|| format!("missing ID: {requirement_id}"));
+		println!("verifies {key}: {latest}");
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+
+	use super::*;
+
+	#[test]
+	fn test_renumber() {
+		let mut test_case = TestCase {
+			id: 0,
+			key: "TC-0001".into(),
+			title: "Test A".into(),
+			requirement_ids: IdList::from(vec![7, 3, 5]),
+			procedure: "Run A".into(),
+			creation_date: Local.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap(),
+			status: RecordStatus::Accepted,
+			author: "Author A".into(),
+		};
+		test_case
+			.renumber(&IdMap::from([(3, 0), (5, 2), (7, 1)]))
+			.unwrap();
+		assert_eq!(IdList::from(vec![1, 0, 2]), test_case.requirement_ids);
+
+		// a requirement that isn't in the table
+		assert!(test_case.renumber(&IdMap::from([(0, 0), (1, 1)])).is_err());
+	}
+}
@@ -0,0 +1,316 @@
+//! Undo and redo of the changes made to the tables during one `reqtsv` session.
+
+use anyhow::{Context, Result, anyhow};
+use const_format::formatcp;
+use reqtsv_lib::{Link, TestResult};
+
+use crate::{
+	AppCtx, component::Component, err_loc, requirement::Requirement,
+	requirement::RequirementRevision, test_case::TestCase,
+};
+
+/// The in memory tables at one point in a session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+	components: Vec<Component>,
+	requirements: Vec<Requirement>,
+	links: Vec<Link>,
+	history: Vec<RequirementRevision>,
+	test_cases: Vec<TestCase>,
+	test_results: Vec<TestResult>,
+}
+
+impl Snapshot {
+	pub fn take(ctx: &AppCtx) -> Self {
+		Self {
+			components: ctx.components.clone(),
+			requirements: ctx.requirements.clone(),
+			links: ctx.links.clone(),
+			history: ctx.history.clone(),
+			test_cases: ctx.test_cases.clone(),
+			test_results: ctx.test_results.clone(),
+		}
+	}
+
+	/// e.g. "requirements: 1 changed, history: 1 added"
+	fn changes_to(&self, after: &Snapshot) -> String {
+		let mut changes = Vec::new();
+		count_changes(
+			"components",
+			&self.components,
+			&after.components,
+			&mut changes,
+		);
+		count_changes(
+			"requirements",
+			&self.requirements,
+			&after.requirements,
+			&mut changes,
+		);
+		count_changes("links", &self.links, &after.links, &mut changes);
+		count_changes("history", &self.history, &after.history, &mut changes);
+		count_changes(
+			"test cases",
+			&self.test_cases,
+			&after.test_cases,
+			&mut changes,
+		);
+		count_changes(
+			"test results",
+			&self.test_results,
+			&after.test_results,
+			&mut changes,
+		);
+		changes.join(", ")
+	}
+}
+
+fn count_changes<R: PartialEq>(table: &str, before: &[R], after: &[R], changes: &mut Vec<String>) {
+	let changed = before.iter().zip(after).filter(|(b, a)| b != a).count();
+	let mut counts = Vec::new();
+	if changed > 0 {
+		counts.push(format!("{changed} changed"));
+	}
+	if after.len() > before.len() {
+		counts.push(format!("{} added", after.len() - before.len()));
+	}
+	if before.len() > after.len() {
+		counts.push(format!("{} removed", before.len() - after.len()));
+	}
+	if !counts.is_empty() {
+		changes.push(format!("{table}: {}", counts.join(" ")));
+	}
+}
+
+/// A menu action and the tables as they were before it, or after it once undone.
+#[derive(Debug)]
+pub struct Operation {
+	pub description: String,
+	state: Snapshot,
+}
+
+#[derive(Debug, Default)]
+pub struct OperationLog {
+	undo: Vec<Operation>,
+	redo: Vec<Operation>,
+}
+
+impl OperationLog {
+	/// Description of what [`undo`] would take back.
+	pub fn next_undo(&self) -> Option<&str> {
+		self.undo.last().map(|op| op.description.as_str())
+	}
+
+	/// Description of what [`redo`] would apply again.
+	pub fn next_redo(&self) -> Option<&str> {
+		self.redo.last().map(|op| op.description.as_str())
+	}
+
+	fn record(&mut self, purpose: &str, before: Snapshot, after: &Snapshot) {
+		if before == *after {
+			return;
+		}
+		let description = format!("{purpose} ({})", before.changes_to(after));
+		self.undo.push(Operation {
+			description,
+			state: before,
+		});
+		self.redo.clear();
+	}
+
+	/// Pops the operation to undo, its state is what to restore. `current` is kept to redo it.
+	fn step_undo(&mut self, current: Snapshot) -> Option<Operation> {
+		let op = self.undo.pop()?;
+		self.redo.push(Operation {
+			description: op.description.clone(),
+			state: current,
+		});
+		Some(op)
+	}
+
+	/// Pops the operation to redo, its state is what to restore. `current` is kept to undo it.
+	fn step_redo(&mut self, current: Snapshot) -> Option<Operation> {
+		let op = self.redo.pop()?;
+		self.undo.push(Operation {
+			description: op.description.clone(),
+			state: current,
+		});
+		Some(op)
+	}
+}
+
+/// Logs `purpose` if it changed the tables since `before` was taken. A new change can't be
+/// redone over so the redo log is cleared.
+pub fn record_operation(ctx: &mut AppCtx, purpose: &str, before: Snapshot) {
+	let after = Snapshot::take(ctx);
+	ctx.operations.record(purpose, before, &after);
+}
+
+pub fn undo(ctx: &mut AppCtx) -> Result<()> {
+	let Some(description) = ctx.operations.next_undo() else {
+		println!("Nothing to undo");
+		return Ok(());
+	};
+	if !confirm(&format!("Undo {description}?"))? {
+		return Ok(());
+	}
+	let current = Snapshot::take(ctx);
+	let op = ctx.operations.step_undo(current).expect("checked above");
+	let res = restore(ctx, op.state);
+	if res.is_ok() {
+		println!("Undone: {}", &op.description);
+	}
+	res
+}
+
+pub fn redo(ctx: &mut AppCtx) -> Result<()> {
+	let Some(description) = ctx.operations.next_redo() else {
+		println!("Nothing to redo");
+		return Ok(());
+	};
+	if !confirm(&format!("Redo {description}?"))? {
+		return Ok(());
+	}
+	let current = Snapshot::take(ctx);
+	let op = ctx.operations.step_redo(current).expect("checked above");
+	let res = restore(ctx, op.state);
+	if res.is_ok() {
+		println!("Redone: {}", &op.description);
+	}
+	res
+}
+
+fn confirm(message: &str) -> Result<bool> {
+	inquire::Confirm::new(message)
+		.with_default(true)
+		.prompt()
+		.map_err(|iqe| anyhow!(iqe).context(formatcp!("{} error prompting to confirm", err_loc!())))
+}
+
+/// Puts `state` in `ctx` and writes every table that differs.
+fn restore(ctx: &mut AppCtx, state: Snapshot) -> Result<()> {
+	if ctx.components != state.components {
+		ctx.components = state.components;
+		ctx.write_components()
+			.context(formatcp!("{} can't restore components", err_loc!()))?;
+	}
+	if ctx.requirements != state.requirements {
+		ctx.requirements = state.requirements;
+		ctx.write_requirements()
+			.context(formatcp!("{} can't restore requirements", err_loc!()))?;
+	}
+	if ctx.links != state.links {
+		ctx.links = state.links;
+		ctx.write_links()
+			.context(formatcp!("{} can't restore links", err_loc!()))?;
+	}
+	if ctx.history != state.history {
+		ctx.history = state.history;
+		ctx.write_history()
+			.context(formatcp!("{} can't restore history", err_loc!()))?;
+	}
+	if ctx.test_cases != state.test_cases {
+		ctx.test_cases = state.test_cases;
+		ctx.write_test_cases()
+			.context(formatcp!("{} can't restore test cases", err_loc!()))?;
+	}
+	if ctx.test_results != state.test_results {
+		ctx.test_results = state.test_results;
+		ctx.write_test_results()
+			.context(formatcp!("{} can't restore test results", err_loc!()))?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+	use reqtsv_lib::{LinkKind, RecordStatus};
+
+	use super::*;
+
+	fn link(id: u64, to_id: u64) -> Link {
+		Link {
+			id,
+			from_id: 0,
+			kind: LinkKind::Refines,
+			to_id,
+			creation_date: chrono::Local
+				.with_ymd_and_hms(2025, 5, 27, 12, 0, 0)
+				.unwrap(),
+			status: RecordStatus::Accepted,
+		}
+	}
+
+	fn snapshot(links: Vec<Link>) -> Snapshot {
+		Snapshot {
+			components: Vec::new(),
+			requirements: Vec::new(),
+			links,
+			history: Vec::new(),
+			test_cases: Vec::new(),
+			test_results: Vec::new(),
+		}
+	}
+
+	#[test]
+	fn test_count_changes() {
+		let mut changes = Vec::new();
+		count_changes("links", &[1, 2], &[1, 2], &mut changes);
+		assert!(changes.is_empty());
+		count_changes("links", &[1, 2], &[1, 3, 4], &mut changes);
+		count_changes("history", &[1, 2, 3], &[5], &mut changes);
+		assert_eq!(
+			vec!["links: 1 changed 1 added", "history: 1 changed 2 removed"],
+			changes
+		);
+		assert_eq!(
+			"links: 1 added",
+			snapshot(vec![]).changes_to(&snapshot(vec![link(0, 1)]))
+		);
+	}
+
+	#[test]
+	fn test_record_operation() {
+		let mut log = OperationLog::default();
+		let empty = snapshot(vec![]);
+		// nothing changed
+		log.record("Edit link", empty.clone(), &empty);
+		assert_eq!(None, log.next_undo());
+
+		let one = snapshot(vec![link(0, 1)]);
+		log.record("Add link", empty.clone(), &one);
+		assert_eq!(Some("Add link (links: 1 added)"), log.next_undo());
+		let op = log.step_undo(one.clone()).unwrap();
+		assert_eq!(empty, op.state);
+		assert_eq!(Some("Add link (links: 1 added)"), log.next_redo());
+
+		// a new change can't be redone over
+		log.record("Add link", empty.clone(), &one);
+		assert_eq!(None, log.next_redo());
+	}
+
+	#[test]
+	fn test_undo_redo() {
+		let mut log = OperationLog::default();
+		let empty = snapshot(vec![]);
+		let one = snapshot(vec![link(0, 1)]);
+		let changed = snapshot(vec![link(0, 2)]);
+		log.record("Add link", empty.clone(), &one);
+		log.record("Edit link", one.clone(), &changed);
+
+		// undo back to the start then redo to the end, each step restores the state before it
+		let mut current = changed.clone();
+		for expected in [&one, &empty] {
+			current = log.step_undo(current).unwrap().state;
+			assert_eq!(expected, &current);
+		}
+		assert!(log.step_undo(current.clone()).is_none());
+		for expected in [&one, &changed] {
+			current = log.step_redo(current).unwrap().state;
+			assert_eq!(expected, &current);
+		}
+		assert!(log.step_redo(current).is_none());
+		assert_eq!(Some("Edit link (links: 1 changed)"), log.next_undo());
+	}
+}